@@ -1,10 +1,71 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{program::invoke, system_instruction};
+use anchor_lang::solana_program::{
+    program::{invoke, invoke_signed},
+    system_instruction,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{
+        self, spl_token::instruction::AuthorityType, spl_token::native_mint, CloseAccount, Mint,
+        MintTo, SetAuthority, Token, TokenAccount, TransferChecked,
+    },
+};
 
 declare_id!("CeS7WEPrgnfvgLrVPw3BmTDkt9hz6Cu9oUb1ZPjCMymm");
 
 // constants
 pub const ANCHOR_DISCRIMINATOR_SIZE: usize = 8;
+pub const MAX_SPLITS: usize = 4;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const MAX_FEE_HISTORY: usize = 32;
+pub const MAX_TAGS: usize = 5;
+pub const MAX_TAG_LEN: usize = 24;
+pub const MAX_MEDIA_URLS: usize = 4;
+pub const MAX_MEDIA_URL_LEN: usize = 256;
+// creators earn 100 bps off the platform fee for every five successful campaigns,
+// up to three steps
+pub const LOYALTY_CAMPAIGNS_PER_DISCOUNT: u64 = 5;
+pub const MAX_LOYALTY_DISCOUNT: u64 = 3;
+pub const LOYALTY_DISCOUNT_BPS: u64 = 100;
+// pyth push oracle: price update accounts are owned by the receiver program
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+pub const MAX_PRICE_AGE: u64 = 60;
+// campaigns with a deadline run for at least a day and can be extended by at most 30 days
+pub const MIN_CAMPAIGN_DURATION: u64 = 24 * 60 * 60;
+pub const MAX_DEADLINE_EXTENSION: u64 = 30 * 24 * 60 * 60;
+// ceiling for a Transaction account including its discriminator; one is created per
+// donation and withdrawal so any new field has to fit here to keep rent predictable
+pub const MAX_TRANSACTION_SPACE: usize = 160;
+// platform fees round up to the next lamport so the platform never under-collects;
+// set to false to floor the fee in the creator's favour instead
+pub const PLATFORM_FEE_ROUND_UP: bool = true;
+// governance can move the fee bounds but never above half of every withdrawal
+pub const MAX_FEE_BOUND_BPS: u64 = 5_000;
+// donate_batch stays within compute limits at five campaigns per instruction
+pub const MAX_BATCH_DONATIONS: usize = 5;
+// bump these whenever a field is appended to the account; migrate_campaign
+// upgrades campaigns still stamped with an older version
+pub const CAMPAIGN_VERSION: u8 = 10;
+pub const PROGRAM_STATE_VERSION: u8 = 4;
+// the leaderboard keeps the top ten donors so each insert stays cheap
+pub const LEADERBOARD_SIZE: usize = 10;
+// refund_many pays out at most five donations per instruction
+pub const MAX_REFUND_BATCH: usize = 5;
+// perk tiers are a short ascending list of lamport thresholds
+pub const MAX_PERK_TIERS: usize = 5;
+// a creator profile indexes at most 32 open campaigns
+pub const MAX_CREATOR_CAMPAIGNS: usize = 32;
+// 1 SOL: the minimum goal and match pool top-up, and the default donation and
+// withdrawal floors until the platform changes them
+pub const MIN_AMOUNT_LAMPORTS: u64 = 1_000_000_000;
+// flags a campaign can collect before it is surfaced for admin review
+pub const DEFAULT_FLAG_THRESHOLD: u64 = 3;
+// a match pool can step its ratio down over at most four progress tiers, and a tier
+// can at most triple a donation
+pub const MAX_MATCH_TIERS: usize = 4;
+pub const MAX_TIER_MATCH_RATIO_BPS: u64 = 3 * BPS_DENOMINATOR;
 
 // error codes
 #[error_code]
@@ -37,6 +98,198 @@ pub enum ErrorCode {
     InvalidPlatformAddress,
     #[msg("Invalid platform fee percentage.")]
     InvalidPlatformFee,
+    #[msg("Milestone description exceeds the maximum length of 256 characters.")]
+    MilestoneDescriptionTooLong,
+    #[msg("Invalid milestone amount. Amount must be greater than zero.")]
+    InvalidMilestoneAmount,
+    #[msg("Milestones cannot be added once donations have started.")]
+    MilestonesLocked,
+    #[msg("Total milestone amount exceeds the campaign goal.")]
+    MilestoneExceedsGoal,
+    #[msg("Milestones must be released in order.")]
+    MilestoneOutOfOrder,
+    #[msg("Milestone has already been released.")]
+    MilestoneAlreadyReleased,
+    #[msg("Campaign funds must be released through its milestones.")]
+    MilestoneReleaseRequired,
+    #[msg("The program is paused.")]
+    ProgramPaused,
+    #[msg("In-kind description exceeds the maximum length of 128 characters.")]
+    InKindDescriptionTooLong,
+    #[msg("Invalid in-kind value. Estimated value must be greater than zero.")]
+    InvalidInKindValue,
+    #[msg("In-kind pledge has already been confirmed.")]
+    InKindAlreadyConfirmed,
+    #[msg("Withdrawal amount exceeds the campaign balance.")]
+    WithdrawalExceedsBalance,
+    #[msg("Campaign has not reached its goal.")]
+    CampaignNotCompleted,
+    #[msg("Refunds are only available for cancelled or unfunded campaigns.")]
+    RefundNotAvailable,
+    #[msg("Refunds must be processed in donation order.")]
+    RefundOutOfOrder,
+    #[msg("Donation has already been refunded.")]
+    AlreadyRefunded,
+    #[msg("Campaign has not started yet.")]
+    CampaignNotStarted,
+    #[msg("Start time cannot be changed once the campaign has started.")]
+    CampaignAlreadyStarted,
+    #[msg("The provided beneficiary address is invalid.")]
+    InvalidBeneficiary,
+    #[msg("Beneficiary splits must have at most four entries summing to 10000 basis points.")]
+    InvalidSplitConfig,
+    #[msg("Deadline must be after the campaign start time.")]
+    InvalidDeadline,
+    #[msg("Campaign deadline has passed.")]
+    CampaignExpired,
+    #[msg("Campaign deadline has not passed yet.")]
+    CampaignNotExpired,
+    #[msg("Donation is still credited to the campaign and cannot be closed.")]
+    DonationStillCredited,
+    #[msg("No platform fee history is available for the requested time.")]
+    FeeHistoryUnavailable,
+    #[msg("The provided category is not registered.")]
+    InvalidCategory,
+    #[msg("Category name exceeds the maximum length of 32 characters.")]
+    CategoryNameTooLong,
+    #[msg("A campaign can have at most 5 tags.")]
+    TooManyTags,
+    #[msg("Each tag must be between 1 and 24 characters.")]
+    InvalidTagLength,
+    #[msg("Withdrawal exceeds the campaign's limit for the current window.")]
+    WithdrawalLimitExceeded,
+    #[msg("Vesting duration must be non-zero and cannot be shortened.")]
+    InvalidVestingSchedule,
+    #[msg("No vested funds are available to claim.")]
+    NothingToClaim,
+    #[msg("Match ratio must be between 1 and 10000 basis points.")]
+    InvalidMatchRatio,
+    #[msg("Creator response exceeds the maximum length of 280 characters.")]
+    ResponseTooLong,
+    #[msg("Goal exceeds the platform's maximum campaign goal.")]
+    GoalTooLarge,
+    #[msg("Update text exceeds the maximum length of 512 characters.")]
+    UpdateTooLong,
+    #[msg("The platform authority cannot create campaigns.")]
+    AuthorityCannotCreate,
+    #[msg("Campaign is already active.")]
+    CampaignAlreadyActive,
+    #[msg("Refunds have already started for this campaign.")]
+    RefundsInProgress,
+    #[msg("Threshold must be greater than zero.")]
+    InvalidThreshold,
+    #[msg("The price feed account is missing or not the trusted SOL/USD feed.")]
+    InvalidPriceFeed,
+    #[msg("The price feed is stale or reports an invalid price.")]
+    StalePrice,
+    #[msg("This transaction record has already been processed.")]
+    TransactionAlreadyProcessed,
+    #[msg("The matching pool has already been distributed.")]
+    MatchAlreadyDistributed,
+    #[msg("Donor records must cover every unique donor of the campaign exactly once.")]
+    InvalidDonorRecords,
+    #[msg("Donations from this wallet are too frequent for this campaign.")]
+    DonationTooFrequent,
+    #[msg("Withdrawals plus refunds would exceed the amount raised.")]
+    ExceedsNetRaised,
+    #[msg("A donor cannot refer themselves.")]
+    SelfReferral,
+    #[msg("The referral stats account is missing or belongs to another referrer.")]
+    InvalidReferrer,
+    #[msg("Campaign must run for at least one day.")]
+    CampaignTooShort,
+    #[msg("Deadline extensions exceed the maximum allowed.")]
+    ExtensionCapExceeded,
+    #[msg("Campaign goal has not been reached yet.")]
+    GoalNotReached,
+    #[msg("Too many donations in one batch.")]
+    BatchTooLarge,
+    #[msg("A campaign appears more than once in the batch.")]
+    DuplicateBatchCampaign,
+    #[msg("Caps must satisfy soft cap <= goal <= hard cap.")]
+    InvalidCapConfig,
+    #[msg("Donation would exceed the campaign's hard cap.")]
+    HardCapReached,
+    #[msg("Transferred lamports do not match the donation amount.")]
+    TransferMismatch,
+    #[msg("Invalid platform fee bounds.")]
+    InvalidFeeBounds,
+    #[msg("Platform fee is already expressed in basis points.")]
+    FeeAlreadyMigrated,
+    #[msg("Campaign must be cancelled or completed before closing.")]
+    CampaignNotSettled,
+    #[msg("Campaign still holds donor funds.")]
+    CampaignBalanceNotEmpty,
+    #[msg("Donor is not on the campaign's whitelist.")]
+    DonorNotWhitelisted,
+    #[msg("Withdrawals are paused.")]
+    WithdrawalsPaused,
+    #[msg("Campaign soft cap has not been reached yet.")]
+    SoftCapNotReached,
+    #[msg("Donation exceeds the per-donor cap for this campaign.")]
+    DonorCapExceeded,
+    #[msg("Currency symbol must be between 1 and 8 characters.")]
+    InvalidCurrencySymbol,
+    #[msg("Display decimals cannot exceed 9.")]
+    InvalidDisplayDecimals,
+    #[msg("Refunds are open for this campaign, withdrawals are blocked.")]
+    RefundsOpen,
+    #[msg("Campaign forwards donations to the beneficiary; there is nothing to withdraw.")]
+    AutoForwardEnabled,
+    #[msg("Account is already on the current layout.")]
+    AlreadyMigrated,
+    #[msg("Goal cannot be lowered once donations have started.")]
+    GoalDecreaseNotAllowed,
+    #[msg("Campaign has not completed.")]
+    CampaignNotFinished,
+    #[msg("Completion receipt already minted.")]
+    ReceiptAlreadyMinted,
+    #[msg("Perk thresholds must be non-zero and strictly ascending.")]
+    InvalidPerkThresholds,
+    #[msg("Too many media URLs.")]
+    TooManyMediaUrls,
+    #[msg("Media URLs must be https and at most 256 characters.")]
+    InvalidMediaUrl,
+    #[msg("Donation is below the required share of the remaining goal.")]
+    DonationBelowRemainingShare,
+    #[msg("Minimum donation share cannot exceed 10000 bps.")]
+    InvalidMinDonationBps,
+    #[msg("Creator has too many campaigns.")]
+    TooManyCampaigns,
+    #[msg("Campaign has not opted into sweeping on completion.")]
+    SweepNotEnabled,
+    #[msg("Campaign already finalized.")]
+    AlreadyFinalized,
+    #[msg("Token account is not wrapped SOL.")]
+    InvalidWsolMint,
+    #[msg("Amount floors must be greater than zero.")]
+    InvalidAmountFloor,
+    #[msg("Campaign is frozen pending platform review.")]
+    CampaignFrozen,
+    #[msg("Campaign is not frozen.")]
+    CampaignNotFrozen,
+    #[msg("Pledge amount or period is invalid.")]
+    InvalidPledge,
+    #[msg("Pledge is already active.")]
+    PledgeAlreadyActive,
+    #[msg("Pledge is not active.")]
+    PledgeInactive,
+    #[msg("Pledge is not due yet.")]
+    PledgeNotDue,
+    #[msg("Donation is not a multiple of the campaign's donation increment.")]
+    InvalidDonationIncrement,
+    #[msg("A creator seed needs the seed transaction, donor record and leaderboard accounts.")]
+    MissingSeedTransaction,
+    #[msg("Title must not be blank or contain control characters.")]
+    InvalidTitle,
+    #[msg("Description must not be blank or contain control characters.")]
+    InvalidDescription,
+    #[msg("Match tiers must ascend by progress and stay within the ratio limit.")]
+    InvalidMatchTiers,
+    #[msg("Batch accounts do not match the donations.")]
+    BatchAccountsMismatch,
+    #[msg("A withdrawal limit can only be tightened.")]
+    WithdrawalLimitLoosened,
 }
 
 // state accounts
@@ -45,8 +298,27 @@ pub enum ErrorCode {
 pub struct ProgramState {
     pub initialized: bool,
     pub campaign_count: u64,
-    pub platform_fee: u64,
+    pub platform_fee_bps: u64,
     pub platform_address: Pubkey,
+    pub paused: bool,
+    pub create_fee: u64,
+    pub max_goal: u64,
+    pub authority_cannot_create: bool,
+    pub sol_usd_feed: Pubkey,
+    // bounds for platform and per-campaign fees, in basis points
+    pub min_fee_bps: u64,
+    pub max_fee_bps: u64,
+    // false for deployments that still store whole-percent fees
+    pub fee_in_bps: bool,
+    // freezes payouts to creators while donations keep flowing
+    pub withdrawals_paused: bool,
+    pub version: u8,
+    // lamports creators must leave above the rent minimum when withdrawing
+    pub rent_buffer: u64,
+    pub min_donation_floor: u64,
+    pub min_withdrawal_floor: u64,
+    // CampaignFlagged fires once a campaign collects more flags than this
+    pub flag_threshold: u64,
 }
 
 #[account]
@@ -63,10 +335,148 @@ pub struct Campaign {
     pub goal: u64,
     pub amount_raised: u64,
     pub timestamp: u64,
+    // every donation bumps this, repeat donors included; it is the index in the
+    // Transaction PDA seeds, so distinct wallets are counted in unique_donors instead
     pub donors: u64,
     pub withdrawals: u64,
     pub balance: u64,
     pub active: bool,
+    pub milestone_count: u64,
+    pub milestones_released: u64,
+    pub milestone_total: u64,
+    pub in_kind_count: u64,
+    pub in_kind_value: u64,
+    pub verified: bool,
+    pub refund_cursor: u64,
+    pub start_time: u64,
+    pub beneficiary: Pubkey,
+    pub top_donor: Pubkey,
+    pub largest_donation: u64,
+    pub leaderboard_min_increment: u64,
+    #[max_len(4)]
+    pub splits: Vec<Split>,
+    pub deadline: u64,
+    pub status: CampaignStatus,
+    // basis points
+    pub fee_override: Option<u64>,
+    pub category_id: u64,
+    pub unique_donors: u64,
+    // tags cost at most 4 + 5 * (4 + 24) = 144 bytes of account space
+    #[max_len(5, 24)]
+    pub tags: Vec<String>,
+    pub withdrawal_window: u64,
+    pub withdrawal_limit: u64,
+    pub window_start: u64,
+    pub withdrawn_in_window: u64,
+    #[max_len(280)]
+    pub creator_response: String,
+    pub update_count: u64,
+    pub success_recorded: bool,
+    pub usd_denominated: bool,
+    // usd amounts are in cents, priced at reference_price * 10^reference_expo usd per sol
+    pub goal_usd: u64,
+    pub raised_usd: u64,
+    pub reference_price: i64,
+    pub reference_expo: i32,
+    pub price_timestamp: u64,
+    pub donation_cooldown: u64,
+    pub withdrawn_total: u64,
+    pub refunded_total: u64,
+    pub total_extension: u64,
+    pub lock_until_goal: bool,
+    // zero disables either cap; a hard cap lets donations continue past the goal
+    pub soft_cap: u64,
+    pub hard_cap: u64,
+    pub whitelist_enabled: bool,
+    pub require_soft_cap: bool,
+    pub created_slot: u64,
+    pub max_per_donor: u64,
+    // display metadata only, amounts are always stored in lamports
+    pub display_decimals: u8,
+    #[max_len(8)]
+    pub currency_symbol: String,
+    pub refunds_open: bool,
+    // snapshot taken at the first proportional refund: every remaining donation is paid
+    // proportional_pool / proportional_base of its amount
+    pub proportional_pool: u64,
+    pub proportional_base: u64,
+    pub auto_forward: bool,
+    pub version: u8,
+    pub goal_reached_at: u64,
+    pub receipt_minted: bool,
+    #[max_len(MAX_PERK_TIERS)]
+    pub perk_thresholds: Vec<u64>,
+    // extra media shown alongside image_url
+    #[max_len(MAX_MEDIA_URLS, MAX_MEDIA_URL_LEN)]
+    pub media_urls: Vec<String>,
+    // each donation must cover this share of what is left to the goal, 0 disables it
+    pub min_donation_bps_of_remaining: u64,
+    // lets anyone crank the remaining balance to the beneficiary once completed
+    pub sweep_on_complete: bool,
+    pub finalized: bool,
+    // freeze donations and withdrawals once the goal is hit, until the platform releases it
+    pub freeze_on_goal: bool,
+    pub frozen: bool,
+    // one per reporting donor, see Flag
+    pub flags: u64,
+    // donations must be a multiple of this many lamports, 0 disables it
+    pub donation_increment: u64,
+    // the creator's own contribution made at creation, already part of amount_raised
+    pub creator_seed_amount: u64,
+    // sponsor match credited to amount_raised, which no donor paid and none can reclaim
+    pub matched_total: u64,
+}
+
+// reason codes carried by DonatableStatus, checked in the same order donate checks them
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DonatableReason {
+    Donatable = 0,
+    ProgramPaused = 1,
+    Frozen = 2,
+    Inactive = 3,
+    NotStarted = 4,
+    Expired = 5,
+    GoalReached = 6,
+    HardCapReached = 7,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum CampaignStatus {
+    Active,
+    Cancelled,
+    Expired,
+    Completed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Split {
+    pub recipient: Pubkey,
+    pub bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchDonation {
+    pub cid: u64,
+    pub amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct FeeEntry {
+    pub effective_ts: u64,
+    // basis points
+    pub fee: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Milestone {
+    pub cid: u64,
+    pub index: u64,
+    pub amount: u64,
+    #[max_len(256)]
+    pub description: String,
+    pub released: bool,
 }
 
 #[account]
@@ -77,336 +487,6907 @@ pub struct Transaction {
     pub amount: u64,
     pub timestamp: u64,
     pub credited: bool,
+    pub matched_amount: u64,
+    pub processed: bool,
+    pub referrer: Option<Pubkey>,
+    pub tip: u64,
+    pub anonymous: bool,
+    // slot breaks ties between records created within the same second
+    pub slot: u64,
+    // keccak over (cid, donor, amount, timestamp), zero for withdrawals
+    #[cfg(feature = "receipt-hash")]
+    pub receipt_hash: [u8; 32],
+    // set on the donation a creator makes to their own campaign at creation
+    pub creator_seed: bool,
 }
 
-#[program]
-pub mod crowdfunding {
-    use super::*;
+const _: () = assert!(ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE <= MAX_TRANSACTION_SPACE);
 
-    // initialize the program
-    pub fn initialize(ctx: Context<InitializeCtx>) -> Result<()> {
-        let state = &mut ctx.accounts.program_state;
-        let deployer = &ctx.accounts.deployer;
+#[account]
+#[derive(InitSpace)]
+pub struct InKindPledge {
+    pub cid: u64,
+    pub donor: Pubkey,
+    pub index: u64,
+    #[max_len(128)]
+    pub description: String,
+    pub estimated_value: u64,
+    pub timestamp: u64,
+    pub confirmed: bool,
+}
 
-        if state.initialized {
-            msg!("Program already initialized");
-            return Err(ErrorCode::AlreadyInitialized.into());
-        }
+#[account]
+#[derive(InitSpace)]
+pub struct CampaignTemplate {
+    pub creator: Pubkey,
+    pub source_cid: u64,
+    #[max_len(64)]
+    pub title: String,
+    #[max_len(512)]
+    pub description: String,
+    #[max_len(256)]
+    pub image_url: String,
+    pub goal: u64,
+    pub timestamp: u64,
+}
 
-        state.campaign_count = 0;
-        state.platform_fee = 5;
-        state.platform_address = deployer.key();
-        state.initialized = true;
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LeaderboardEntry {
+    pub donor: Pubkey,
+    pub total: u64,
+}
 
-        msg!("Program initialized successfully");
-        Ok(())
-    }
+#[account]
+#[derive(InitSpace)]
+pub struct FeeHistory {
+    #[max_len(32)]
+    pub entries: Vec<FeeEntry>,
+}
 
-    // create a new campaign
-    pub fn create_campaign(
-        ctx: Context<CreateCampaignCtx>,
-        title: String,
-        description: String,
-        image_url: String,
-        goal: u64,
-    ) -> Result<()> {
-        let campaign = &mut ctx.accounts.campaign;
-        let state = &mut ctx.accounts.program_state;
+#[account]
+#[derive(InitSpace)]
+pub struct Category {
+    pub id: u64,
+    #[max_len(32)]
+    pub name: String,
+}
 
-        if title.len() > 64 {
-            msg!("Title too long");
-            return Err(ErrorCode::TitleTooLong.into());
-        }
-        if description.len() > 512 {
-            msg!("Description too long");
-            return Err(ErrorCode::DescriptionTooLong.into());
-        }
-        if image_url.len() > 256 {
-            msg!("Image URL too long");
-            return Err(ErrorCode::ImageUrlTooLong.into());
-        }
-        if goal < 1_000_000_000 {
-            msg!("Invalid goal amount");
-            return Err(ErrorCode::InvalidGoalAmount.into());
-        }
+#[account]
+#[derive(InitSpace)]
+pub struct DonorRecord {
+    pub cid: u64,
+    pub donor: Pubkey,
+    pub donor_rank: u64,
+    pub donation_count: u64,
+    pub total_contributed: u64,
+    pub last_donation_ts: u64,
+}
 
-        state.campaign_count += 1;
+#[account]
+#[derive(InitSpace)]
+pub struct VestingVault {
+    pub cid: u64,
+    pub total_deposited: u64,
+    pub vested_base: u64,
+    pub claimed: u64,
+    pub start_ts: u64,
+    pub duration: u64,
+}
 
-        campaign.cid = state.campaign_count;
-        campaign.creator = ctx.accounts.creator.key();
-        campaign.title = title;
-        campaign.description = description;
-        campaign.image_url = image_url;
-        campaign.goal = goal;
-        campaign.amount_raised = 0;
-        campaign.donors = 0;
-        campaign.withdrawals = 0;
-        campaign.timestamp = Clock::get()?.unix_timestamp as u64;
-        campaign.active = true;
+#[account]
+#[derive(InitSpace)]
+pub struct MatchPool {
+    pub cid: u64,
+    pub sponsor: Pubkey,
+    pub matched_remaining: u64,
+    pub match_ratio_bps: u64,
+    pub total_matched: u64,
+    pub distributed: bool,
+    // when set, replaces match_ratio_bps with a ratio picked by campaign progress
+    #[max_len(MAX_MATCH_TIERS)]
+    pub tiers: Vec<MatchTier>,
+}
 
-        msg!("Campaign created successfully");
-        Ok(())
-    }
+// applies while the campaign's progress, in bps of the goal, is below below_progress_bps
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct MatchTier {
+    pub below_progress_bps: u64,
+    pub ratio_bps: u64,
+}
 
-    // update campaign details
-    pub fn update_campaign(
-        ctx: Context<UpdateCampaignCtx>,
-        cid: u64,
-        title: String,
-        description: String,
-        image_url: String,
-        goal: u64,
-    ) -> Result<()> {
-        let campaign = &mut ctx.accounts.campaign;
-        let creator = &mut ctx.accounts.creator;
+#[account]
+#[derive(InitSpace)]
+pub struct Update {
+    pub cid: u64,
+    pub index: u64,
+    #[max_len(512)]
+    pub text: String,
+    pub timestamp: u64,
+}
 
-        if campaign.creator != creator.key() {
-            msg!("Unauthorized update attempt");
-            return Err(ErrorCode::Unauthorized.into());
-        }
+#[account]
+#[derive(InitSpace)]
+pub struct ThresholdSubscription {
+    pub cid: u64,
+    pub subscriber: Pubkey,
+    pub threshold: u64,
+    pub timestamp: u64,
+}
 
-        if campaign.cid != cid {
-            msg!("Campaign not found for update");
-            return Err(ErrorCode::CampaignNotFound.into());
-        }
+#[account]
+#[derive(InitSpace)]
+pub struct CreatorProfile {
+    pub creator: Pubkey,
+    pub campaigns_created: u64,
+    pub successful_campaigns: u64,
+    // cids of the creator's campaigns, so clients can list them with one fetch
+    #[max_len(MAX_CREATOR_CAMPAIGNS)]
+    pub campaign_ids: Vec<u64>,
+}
 
-        if title.len() > 64 {
-            msg!("Title too long on update");
-            return Err(ErrorCode::TitleTooLong.into());
-        }
-        if description.len() > 512 {
-            msg!("Description too long on update");
-            return Err(ErrorCode::DescriptionTooLong.into());
-        }
-        if image_url.len() > 256 {
-            msg!("Image URL too long on update");
-            return Err(ErrorCode::ImageUrlTooLong.into());
-        }
-        if goal < 1_000_000_000 {
-            msg!("Invalid goal amount on update");
-            return Err(ErrorCode::InvalidGoalAmount.into());
-        }
+#[account]
+#[derive(InitSpace)]
+pub struct ReferralStats {
+    pub referrer: Pubkey,
+    pub referred_total: u64,
+    pub referred_count: u64,
+}
 
-        campaign.title = title;
-        campaign.description = description;
-        campaign.image_url = image_url;
-        campaign.goal = goal;
+#[account]
+#[derive(InitSpace)]
+pub struct AllowedDonor {
+    pub cid: u64,
+    pub donor: Pubkey,
+}
 
-        msg!("Campaign updated successfully");
-        Ok(())
-    }
+// platform fees from withdrawals accumulate here until the platform sweeps them
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub total_fees_collected: u64,
+    pub total_swept: u64,
+}
 
-    // delete (deactivate) a campaign
-    pub fn delete_campaign(ctx: Context<DeleteCampaignCtx>, cid: u64) -> Result<()> {
-        let campaign = &mut ctx.accounts.campaign;
-        let creator = &mut ctx.accounts.creator;
+#[account]
+#[derive(InitSpace)]
+pub struct Leaderboard {
+    pub cid: u64,
+    // sorted by total, highest first
+    #[max_len(LEADERBOARD_SIZE)]
+    pub entries: Vec<LeaderboardEntry>,
+}
 
-        if campaign.creator != creator.key() {
-            msg!("Unauthorized delete attempt");
-            return Err(ErrorCode::Unauthorized.into());
-        }
+// a recurring gift, pulled through the donor's wSOL account with this PDA as delegate
+#[account]
+#[derive(InitSpace)]
+pub struct Pledge {
+    pub cid: u64,
+    pub donor: Pubkey,
+    pub amount_per_period: u64,
+    pub period_seconds: u64,
+    pub next_due_ts: u64,
+    pub active: bool,
+    pub executed_count: u64,
+}
 
-        if campaign.cid != cid {
-            msg!("Campaign not found for delete");
-            return Err(ErrorCode::CampaignNotFound.into());
-        }
+// marks that a donor has flagged a campaign, its existence blocks a second flag
+#[account]
+#[derive(InitSpace)]
+pub struct Flag {
+    pub cid: u64,
+    pub reporter: Pubkey,
+    pub timestamp: u64,
+}
 
-        if !campaign.active {
-            msg!("Campaign already inactive");
-            return Err(ErrorCode::InactiveCampaign.into());
-        }
+// events
+#[event]
+pub struct CampaignVerificationChanged {
+    pub cid: u64,
+    pub verified: bool,
+    pub timestamp: u64,
+}
 
-        campaign.active = false;
+// anonymous donations report the default pubkey as donor; the transaction still
+// records the real owner, so this only hides the donor from event consumers
+#[event]
+pub struct DonationReceived {
+    pub cid: u64,
+    pub donor: Pubkey,
+    pub amount: u64,
+    pub amount_raised: u64,
+    pub donor_rank: u64,
+    pub first_time_donor: bool,
+    pub timestamp: u64,
+}
 
-        msg!("Campaign deactivated successfully");
-        Ok(())
-    }
+#[event]
+pub struct UpdatePosted {
+    pub cid: u64,
+    pub index: u64,
+    pub timestamp: u64,
+}
 
-    // donate to a campaign
-    pub fn donate(ctx: Context<DonateCtx>, cid: u64, amount: u64) -> Result<()> {
-        let campaign = &mut ctx.accounts.campaign;
-        let donor = &mut ctx.accounts.donor;
-        let transaction = &mut ctx.accounts.transaction;
+// subscriptions are matched off-chain: any threshold in
+// (previous_amount_raised, amount_raised] was crossed by this donation
+#[event]
+pub struct ThresholdCrossed {
+    pub cid: u64,
+    pub previous_amount_raised: u64,
+    pub amount_raised: u64,
+    pub timestamp: u64,
+}
 
-        if campaign.cid != cid {
-            msg!("Campaign not found for donation");
-            return Err(ErrorCode::CampaignNotFound.into());
-        }
+// canonical progress for clients: remaining saturates at zero and percent_bps caps
+// at 10_000 once the goal is reached or overshot
+#[event]
+pub struct ProgressSnapshot {
+    pub cid: u64,
+    pub raised: u64,
+    pub goal: u64,
+    pub remaining: u64,
+    pub percent_bps: u64,
+}
 
-        if !campaign.active {
-            msg!("Inactive campaign for donation");
-            return Err(ErrorCode::InactiveCampaign.into());
-        }
+#[event]
+pub struct RefundsEnabled {
+    pub cid: u64,
+    pub balance: u64,
+    pub timestamp: u64,
+}
 
-        if amount < 1_000_000_000 {
-            msg!("Donation amount too low");
-            return Err(ErrorCode::InvalidDonationAmount.into());
-        }
+#[event]
+pub struct CampaignDeactivatedByAdmin {
+    pub cid: u64,
+    pub creator: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: u64,
+}
 
-        if campaign.amount_raised >= campaign.goal {
-            msg!("Campaign goal already reached");
-            return Err(ErrorCode::CampaignGoalActualized.into());
-        }
+#[event]
+pub struct PlatformAddressChanged {
+    pub previous_address: Pubkey,
+    pub new_address: Pubkey,
+    pub timestamp: u64,
+}
 
-        let tx_instruction = system_instruction::transfer(
-            &donor.key(),
-            &campaign.key(),
-            amount,
-        );
+#[event]
+pub struct GoalRaised {
+    pub cid: u64,
+    pub previous_goal: u64,
+    pub new_goal: u64,
+    pub timestamp: u64,
+}
 
-        let result = invoke(
-            &tx_instruction,
-            &[donor.to_account_info(), campaign.to_account_info()],
-        );
+#[event]
+pub struct PerkTier {
+    pub cid: u64,
+    pub donor: Pubkey,
+    // number of thresholds the donor's total has reached, 0 means no perk
+    pub tier: u8,
+}
 
-        if let Err(_e) = result {
-            msg!("Donation transfer failed");
-            return Err(ErrorCode::InsufficientFund.into());
-        }
+#[event]
+pub struct CampaignFrozen {
+    pub cid: u64,
+    pub timestamp: u64,
+}
 
-        campaign.amount_raised += amount;
-        campaign.balance += amount;
-        campaign.donors += 1;
+#[event]
+pub struct CampaignUnfrozen {
+    pub cid: u64,
+    pub timestamp: u64,
+}
 
-        transaction.amount = amount;
-        transaction.cid = cid;
-        transaction.owner = donor.key();
-        transaction.timestamp = Clock::get()?.unix_timestamp as u64;
-        transaction.credited = true;
+#[event]
+pub struct DonorsReconciled {
+    pub cid: u64,
+    pub previous_count: u64,
+    pub actual_count: u64,
+    pub timestamp: u64,
+}
 
-        msg!("Donation successful");
-        Ok(())
+#[event]
+pub struct PledgeExecuted {
+    pub cid: u64,
+    pub donor: Pubkey,
+    pub amount: u64,
+    pub next_due_ts: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct CampaignFlagged {
+    pub cid: u64,
+    pub flags: u64,
+    pub threshold: u64,
+    pub timestamp: u64,
+}
+
+// full campaign snapshot for indexers; the title is capped at 64 chars so this
+// stays far below the log size limit
+#[event]
+pub struct CampaignSnapshot {
+    pub cid: u64,
+    pub creator: Pubkey,
+    pub title: String,
+    pub goal: u64,
+    pub deadline: u64,
+    pub status: CampaignStatus,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct WithdrawalPreview {
+    pub cid: u64,
+    pub requested: u64,
+    pub platform_fee: u64,
+    pub creator_amount: u64,
+    pub usable_balance: u64,
+}
+
+#[event]
+pub struct DonatableStatus {
+    pub cid: u64,
+    pub donatable: bool,
+    // a DonatableReason code, 0 when donatable
+    pub reason: u8,
+}
+
+// helpers
+fn validate_splits(splits: &[Split]) -> Result<()> {
+    if splits.is_empty() {
+        return Ok(());
     }
 
-    // withdraw funds from a campaign
-    pub fn withdraw(ctx: Context<WithdrawCtx>, cid: u64, amount: u64) -> Result<()> {
-        let campaign = &mut ctx.accounts.campaign;
-        let creator = &ctx.accounts.creator;
-        let transaction = &mut ctx.accounts.transaction;
-        let state = &mut ctx.accounts.program_state;
-        let platform_account_info = &ctx.accounts.platform_address;
+    let total_bps: u64 = splits.iter().map(|split| split.bps as u64).sum();
+    if splits.len() > MAX_SPLITS
+        || splits.iter().any(|split| split.bps == 0)
+        || total_bps != BPS_DENOMINATOR
+    {
+        msg!("Invalid beneficiary split configuration");
+        return Err(ErrorCode::InvalidSplitConfig.into());
+    }
 
-        if campaign.cid != cid {
-            msg!("Campaign not found for withdrawal");
-            return Err(ErrorCode::CampaignNotFound.into());
-        }
+    Ok(())
+}
 
-        if campaign.creator != creator.key() {
-            msg!("Unauthorized withdrawal attempt");
-            return Err(ErrorCode::Unauthorized.into());
-        }
+fn validate_tags(tags: &[String]) -> Result<()> {
+    if tags.len() > MAX_TAGS {
+        msg!("Too many campaign tags");
+        return Err(ErrorCode::TooManyTags.into());
+    }
 
-        //fixing the amount such that  less than 1 sol cant be deducted
-        if amount < 1_000_000_000 {
-            msg!("Withdrawal amount too low");
-            return Err(ErrorCode::InvalidWithdrawalAmount.into());
-        }
+    if tags.iter().any(|tag| tag.is_empty() || tag.len() > MAX_TAG_LEN) {
+        msg!("Invalid campaign tag length");
+        return Err(ErrorCode::InvalidTagLength.into());
+    }
 
-        if amount > campaign.balance {
-            msg!("Withdrawal exceeds campaign balance");
+    Ok(())
+}
+
+fn validate_media_urls(media_urls: &[String]) -> Result<()> {
+    if media_urls.len() > MAX_MEDIA_URLS {
+        msg!("Too many media URLs");
+        return Err(ErrorCode::TooManyMediaUrls.into());
+    }
+
+    if media_urls
+        .iter()
+        .any(|url| !url.starts_with("https://") || url.len() > MAX_MEDIA_URL_LEN)
+    {
+        msg!("Invalid media URL");
+        return Err(ErrorCode::InvalidMediaUrl.into());
+    }
+
+    Ok(())
+}
+
+fn validate_title(title: &str) -> Result<()> {
+    if title.trim().is_empty() || title.chars().any(|c| c.is_ascii_control()) {
+        msg!("Title is blank or contains control characters");
+        return Err(ErrorCode::InvalidTitle.into());
+    }
+
+    Ok(())
+}
+
+// descriptions are multi-line, so line breaks and tabs are the only control characters allowed
+fn validate_description(description: &str) -> Result<()> {
+    if description.trim().is_empty()
+        || description
+            .chars()
+            .any(|c| c.is_ascii_control() && !matches!(c, '\n' | '\r' | '\t'))
+    {
+        msg!("Description is blank or contains control characters");
+        return Err(ErrorCode::InvalidDescription.into());
+    }
+
+    Ok(())
+}
+
+// zero caps are disabled, so only the caps that are set are compared with the goal
+fn validate_caps(soft_cap: u64, goal: u64, hard_cap: u64) -> Result<()> {
+    if (soft_cap != 0 && soft_cap > goal) || (hard_cap != 0 && hard_cap < goal) {
+        msg!("Invalid campaign caps");
+        return Err(ErrorCode::InvalidCapConfig.into());
+    }
+
+    Ok(())
+}
+
+// without a hard cap donations stop once the goal is reached: the check runs before the
+// donation is added, so the donation that lands exactly on (or past) the goal is accepted
+// and marks the campaign Completed, and every donation after it fails
+fn check_hard_cap(campaign: &Campaign, amount: u64) -> Result<()> {
+    if campaign.hard_cap == 0 {
+        if campaign.amount_raised >= campaign.goal {
+            msg!("Campaign goal already reached");
             return Err(ErrorCode::CampaignGoalActualized.into());
         }
+        return Ok(());
+    }
 
-        if platform_account_info.key() != state.platform_address {
-            msg!("Invalid platform address for withdrawal");
-            return Err(ErrorCode::InvalidPlatformAddress.into());
+    if campaign.amount_raised.saturating_add(amount) > campaign.hard_cap {
+        msg!("Donation exceeds hard cap");
+        return Err(ErrorCode::HardCapReached.into());
+    }
+
+    Ok(())
+}
+
+// the platform donation floor is checked separately and still applies when this is lower
+fn check_min_donation_share(campaign: &Campaign, amount: u64) -> Result<()> {
+    if campaign.min_donation_bps_of_remaining == 0 {
+        return Ok(());
+    }
+
+    let remaining = campaign.goal.saturating_sub(campaign.amount_raised);
+    let required = (remaining as u128) * (campaign.min_donation_bps_of_remaining as u128)
+        / (BPS_DENOMINATOR as u128);
+    if (amount as u128) < required {
+        msg!("Donation below required share of remaining goal");
+        return Err(ErrorCode::DonationBelowRemainingShare.into());
+    }
+
+    Ok(())
+}
+
+// zero leaves campaigns accepting any amount above the platform floor, which checked_rem
+// covers by returning None
+fn check_donation_increment(campaign: &Campaign, amount: u64) -> Result<()> {
+    if amount.checked_rem(campaign.donation_increment).unwrap_or(0) != 0 {
+        msg!("Donation not a multiple of the campaign increment");
+        return Err(ErrorCode::InvalidDonationIncrement.into());
+    }
+
+    Ok(())
+}
+
+// a campaign counts as funded once it reaches its soft cap, or its goal without one;
+// unfunded campaigns end as Expired and stay refundable
+fn soft_cap_met(campaign: &Campaign) -> bool {
+    if campaign.soft_cap == 0 {
+        campaign.amount_raised >= campaign.goal
+    } else {
+        campaign.amount_raised >= campaign.soft_cap
+    }
+}
+
+// the donor must have lost and the campaign gained exactly the donated amount
+fn check_transfer(
+    donor_before: u64,
+    donor_after: u64,
+    campaign_before: u64,
+    campaign_after: u64,
+    amount: u64,
+) -> Result<()> {
+    if donor_before.checked_sub(donor_after) != Some(amount)
+        || campaign_after.checked_sub(campaign_before) != Some(amount)
+    {
+        msg!("Donation transfer moved an unexpected amount");
+        return Err(ErrorCode::TransferMismatch.into());
+    }
+
+    Ok(())
+}
+
+// checks shared by update_campaign and update_campaign_partial for a new goal
+fn validate_goal_update(campaign: &Campaign, state: &ProgramState, goal: u64) -> Result<()> {
+    if goal < MIN_AMOUNT_LAMPORTS {
+        msg!("Invalid goal amount on update");
+        return Err(ErrorCode::InvalidGoalAmount.into());
+    }
+    if state.max_goal != 0 && goal > state.max_goal {
+        msg!("Goal exceeds platform maximum on update");
+        return Err(ErrorCode::GoalTooLarge.into());
+    }
+    if goal < campaign.milestone_total {
+        msg!("Goal below total milestone amount on update");
+        return Err(ErrorCode::MilestoneExceedsGoal.into());
+    }
+    // stretch goals only move up once donors have committed against the old one
+    if campaign.amount_raised > 0 && goal < campaign.goal {
+        msg!("Goal lowered after donations started");
+        return Err(ErrorCode::GoalDecreaseNotAllowed.into());
+    }
+    validate_caps(campaign.soft_cap, goal, campaign.hard_cap)
+}
+
+// what a creator payout may take from the campaign, keeping rent plus the platform buffer
+fn usable_balance(campaign: &AccountInfo, state: &ProgramState) -> Result<u64> {
+    let rent_balance = Rent::get()?.minimum_balance(campaign.data_len());
+    Ok(campaign
+        .lamports()
+        .saturating_sub(rent_balance)
+        .saturating_sub(state.rent_buffer))
+}
+
+// funds already refunded to donors can never be withdrawn by the creator; balance
+// tracks the same ledger, so this is a backstop for the balance checks
+fn check_net_raised(campaign: &Campaign, amount: u64) -> Result<()> {
+    let committed = campaign
+        .withdrawn_total
+        .checked_add(campaign.refunded_total)
+        .and_then(|value| value.checked_add(amount))
+        .ok_or(ErrorCode::ExceedsNetRaised)?;
+    if committed > campaign.amount_raised {
+        msg!("Withdrawal exceeds net raised after refunds");
+        return Err(ErrorCode::ExceedsNetRaised.into());
+    }
+
+    Ok(())
+}
+
+// a zero window or limit leaves withdrawals unlimited and returns None, otherwise
+// the window start and running total the withdrawal would leave behind
+fn check_withdrawal_limit(
+    campaign: &Campaign,
+    amount: u64,
+    now: u64,
+) -> Result<Option<(u64, u64)>> {
+    if campaign.withdrawal_window == 0 || campaign.withdrawal_limit == 0 {
+        return Ok(None);
+    }
+
+    let (window_start, withdrawn_so_far) =
+        if now.saturating_sub(campaign.window_start) >= campaign.withdrawal_window {
+            (now, 0)
+        } else {
+            (campaign.window_start, campaign.withdrawn_in_window)
+        };
+
+    let withdrawn_in_window = withdrawn_so_far
+        .checked_add(amount)
+        .ok_or(ErrorCode::WithdrawalLimitExceeded)?;
+    if withdrawn_in_window > campaign.withdrawal_limit {
+        msg!("Withdrawal limit exceeded for the current window");
+        return Err(ErrorCode::WithdrawalLimitExceeded.into());
+    }
+
+    Ok(Some((window_start, withdrawn_in_window)))
+}
+
+fn apply_withdrawal_limit(campaign: &mut Campaign, amount: u64, now: u64) -> Result<()> {
+    if let Some((window_start, withdrawn_in_window)) =
+        check_withdrawal_limit(campaign, amount, now)?
+    {
+        campaign.window_start = window_start;
+        campaign.withdrawn_in_window = withdrawn_in_window;
+    }
+
+    Ok(())
+}
+
+// every check withdraw makes before touching state, shared with preview_withdrawal;
+// release_milestone passes milestone_release so milestone campaigns can pay out
+fn check_withdrawal(
+    campaign: &Account<Campaign>,
+    state: &ProgramState,
+    creator: Pubkey,
+    beneficiary: Pubkey,
+    cid: u64,
+    amount: u64,
+    milestone_release: bool,
+) -> Result<()> {
+    if state.paused {
+        msg!("Program paused, withdrawal rejected");
+        return Err(ErrorCode::ProgramPaused.into());
+    }
+
+    if state.withdrawals_paused {
+        msg!("Withdrawals paused, withdrawal rejected");
+        return Err(ErrorCode::WithdrawalsPaused.into());
+    }
+
+    // once a campaign is cancelled or ends unfunded what is left belongs to the donors
+    if campaign.refunds_open
+        || (campaign.status == CampaignStatus::Expired && !soft_cap_met(campaign))
+    {
+        msg!("Refunds open, withdrawal rejected");
+        return Err(ErrorCode::RefundsOpen.into());
+    }
+
+    if campaign.cid != cid {
+        msg!("Campaign not found for withdrawal");
+        return Err(ErrorCode::CampaignNotFound.into());
+    }
+
+    if campaign.frozen {
+        msg!("Campaign frozen, withdrawal rejected");
+        return Err(ErrorCode::CampaignFrozen.into());
+    }
+
+    if campaign.creator != creator {
+        msg!("Unauthorized withdrawal attempt");
+        return Err(ErrorCode::Unauthorized.into());
+    }
+
+    if campaign.milestone_count > 0 && !milestone_release {
+        msg!("Campaign funds are locked behind milestones");
+        return Err(ErrorCode::MilestoneReleaseRequired.into());
+    }
+
+    // only funds held back while forwarding was gated are left to withdraw
+    if campaign.auto_forward && campaign.balance == 0 {
+        msg!("Campaign forwards donations automatically");
+        return Err(ErrorCode::AutoForwardEnabled.into());
+    }
+
+    // creators can promise that nothing moves before the goal is met
+    if campaign.lock_until_goal && campaign.amount_raised < campaign.goal {
+        msg!("Funds locked until goal is reached");
+        return Err(ErrorCode::GoalNotReached.into());
+    }
+
+    // a softer promise than lock_until_goal: funds move once the soft cap is met
+    if campaign.require_soft_cap && campaign.amount_raised < campaign.soft_cap {
+        msg!("Funds locked until soft cap is reached");
+        return Err(ErrorCode::SoftCapNotReached.into());
+    }
+
+    if amount < state.min_withdrawal_floor {
+        msg!("Withdrawal amount too low");
+        return Err(ErrorCode::InvalidWithdrawalAmount.into());
+    }
+
+    if amount > campaign.balance {
+        msg!("Withdrawal exceeds campaign balance");
+        return Err(ErrorCode::WithdrawalExceedsBalance.into());
+    }
+
+    check_net_raised(campaign, amount)?;
+
+    if beneficiary != campaign.beneficiary {
+        msg!("Invalid beneficiary for withdrawal");
+        return Err(ErrorCode::InvalidBeneficiary.into());
+    }
+
+    if amount > usable_balance(&campaign.to_account_info(), state)? {
+        msg!("Withdrawal exceeds campaign's usable balance");
+        return Err(ErrorCode::InsufficientFund.into());
+    }
+
+    Ok(())
+}
+
+// the platform fee after the creator's loyalty discount, and what the payout leaves them
+fn withdrawal_fee(
+    campaign: &Campaign,
+    state: &ProgramState,
+    successful_campaigns: u64,
+    amount: u64,
+) -> Result<(u64, u64)> {
+    let loyalty_discount = (successful_campaigns / LOYALTY_CAMPAIGNS_PER_DISCOUNT)
+        .min(MAX_LOYALTY_DISCOUNT)
+        * LOYALTY_DISCOUNT_BPS;
+    let fee_bps = campaign
+        .fee_override
+        .unwrap_or(state.platform_fee_bps)
+        .saturating_sub(loyalty_discount);
+    let platform_fee = compute_platform_fee(amount, fee_bps)?;
+    let creator_amount = amount
+        .checked_sub(platform_fee)
+        .ok_or(ErrorCode::InvalidPlatformFee)?;
+
+    Ok((platform_fee, creator_amount))
+}
+
+// a funded campaign counts toward the creator's track record once
+fn record_success(campaign: &mut Campaign, creator_profile: &mut CreatorProfile) {
+    if !campaign.success_recorded && campaign.amount_raised >= campaign.goal {
+        campaign.success_recorded = true;
+        creator_profile.creator = campaign.creator;
+        creator_profile.successful_campaigns += 1;
+    }
+}
+
+// amount vested so far: everything vested before the last deposit plus a linear
+// share of the remainder over the vault's duration
+fn vested_amount(vault: &VestingVault, now: u64) -> Result<u64> {
+    let elapsed = now.saturating_sub(vault.start_ts).min(vault.duration);
+    let unvested = vault
+        .total_deposited
+        .checked_sub(vault.vested_base)
+        .ok_or(ErrorCode::InvalidVestingSchedule)?;
+    let vesting = (unvested as u128) * (elapsed as u128) / (vault.duration as u128);
+
+    Ok(vault.vested_base + vesting as u64)
+}
+
+// read the sol/usd price from a pyth PriceUpdateV2 account; the layout after the
+// discriminator is write_authority (32), verification_level (1 for Full) and the
+// price message: feed_id (32), price (i64), conf (u64), exponent (i32), publish_time (i64)
+fn read_sol_usd_price(feed: &AccountInfo, now: u64) -> Result<(i64, i32, u64)> {
+    if *feed.owner != PYTH_RECEIVER_PROGRAM_ID {
+        msg!("Price feed not owned by the pyth receiver");
+        return Err(ErrorCode::InvalidPriceFeed.into());
+    }
+
+    let data = feed.try_borrow_data()?;
+    if data.len() < 101 || data[..8] != PRICE_UPDATE_V2_DISCRIMINATOR || data[40] != 1 {
+        msg!("Price feed is not a fully verified price update");
+        return Err(ErrorCode::InvalidPriceFeed.into());
+    }
+
+    let read_i64 = |offset: usize| i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+    let price = read_i64(73);
+    let expo = i32::from_le_bytes(data[89..93].try_into().unwrap());
+    let publish_time = read_i64(93);
+
+    if price <= 0 || publish_time < 0 || now.saturating_sub(publish_time as u64) > MAX_PRICE_AGE {
+        msg!("Stale or invalid price");
+        return Err(ErrorCode::StalePrice.into());
+    }
+
+    Ok((price, expo, publish_time as u64))
+}
+
+// value of a lamport amount in usd cents at the given pyth price
+// any overflow means the feed reported a price or exponent no real sol price has
+fn lamports_to_usd_cents(lamports: u64, price: i64, expo: i32) -> Result<u64> {
+    let price = u128::try_from(price).map_err(|_| ErrorCode::InvalidPriceFeed)?;
+    let scale = 10u128
+        .checked_pow(expo.unsigned_abs())
+        .ok_or(ErrorCode::InvalidPriceFeed)?;
+    let mut numerator = (lamports as u128)
+        .checked_mul(price)
+        .and_then(|value| value.checked_mul(100))
+        .ok_or(ErrorCode::InvalidPriceFeed)?;
+    let mut denominator: u128 = 1_000_000_000;
+    if expo < 0 {
+        denominator = denominator
+            .checked_mul(scale)
+            .ok_or(ErrorCode::InvalidPriceFeed)?;
+    } else {
+        numerator = numerator
+            .checked_mul(scale)
+            .ok_or(ErrorCode::InvalidPriceFeed)?;
+    }
+
+    u64::try_from(numerator / denominator).map_err(|_| ErrorCode::InvalidPriceFeed.into())
+}
+
+// integer square root, floored
+fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+fn check_fee_bounds(state: &ProgramState, fee_bps: u64) -> Result<()> {
+    if fee_bps < state.min_fee_bps || fee_bps > state.max_fee_bps {
+        msg!("Fee outside the configured bounds");
+        return Err(ErrorCode::InvalidPlatformFee.into());
+    }
+
+    Ok(())
+}
+
+// platform fee for a withdrawal, computed in u128 so amount * fee cannot overflow
+fn compute_platform_fee(amount: u64, fee_bps: u64) -> Result<u64> {
+    let numerator = (amount as u128) * (fee_bps as u128);
+    let denominator = BPS_DENOMINATOR as u128;
+    let fee = if PLATFORM_FEE_ROUND_UP {
+        numerator.div_ceil(denominator)
+    } else {
+        numerator / denominator
+    };
+
+    match u64::try_from(fee) {
+        Ok(fee) if fee <= amount => Ok(fee),
+        _ => {
+            msg!("Platform fee exceeds withdrawal amount");
+            Err(ErrorCode::InvalidPlatformFee.into())
         }
+    }
+}
 
-        let rent_balance = Rent::get()?.minimum_balance(campaign.to_account_info().data_len());
-        if amount > **campaign.to_account_info().lamports.borrow() - rent_balance {
-            msg!("Withdrawal exceeds campaign's usable balance");
-            return Err(ErrorCode::InsufficientFund.into());
+// defaults shared by initialize and initialize_if_needed
+fn initialize_program_state(
+    state: &mut ProgramState,
+    fee_history: &mut FeeHistory,
+    deployer: Pubkey,
+) -> Result<()> {
+    state.campaign_count = 0;
+    state.platform_fee_bps = 500;
+    state.platform_address = deployer;
+    state.paused = false;
+    state.withdrawals_paused = false;
+    state.create_fee = 0;
+    state.max_goal = 0;
+    state.authority_cannot_create = false;
+    state.sol_usd_feed = Pubkey::default();
+    state.min_fee_bps = 100;
+    state.max_fee_bps = 1_500;
+    state.fee_in_bps = true;
+    state.initialized = true;
+    state.rent_buffer = 0;
+    state.min_donation_floor = MIN_AMOUNT_LAMPORTS;
+    state.min_withdrawal_floor = MIN_AMOUNT_LAMPORTS;
+    state.flag_threshold = DEFAULT_FLAG_THRESHOLD;
+    state.version = PROGRAM_STATE_VERSION;
+
+    fee_history.entries = vec![FeeEntry {
+        effective_ts: Clock::get()?.unix_timestamp as u64,
+        fee: state.platform_fee_bps,
+    }];
+
+    Ok(())
+}
+
+// clients rebuild this from the DonationReceived fields to verify a donation record
+#[cfg(feature = "receipt-hash")]
+fn receipt_hash(cid: u64, donor: &Pubkey, amount: u64, timestamp: u64) -> [u8; 32] {
+    anchor_lang::solana_program::keccak::hashv(&[
+        &cid.to_le_bytes(),
+        donor.as_ref(),
+        &amount.to_le_bytes(),
+        &timestamp.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+// indexers rebuild their view from this after every create and update
+fn emit_campaign_snapshot(campaign: &Campaign) -> Result<()> {
+    emit!(CampaignSnapshot {
+        cid: campaign.cid,
+        creator: campaign.creator,
+        title: campaign.title.clone(),
+        goal: campaign.goal,
+        deadline: campaign.deadline,
+        status: campaign.status,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
+
+// tiers ascend and their bounds are exclusive, so a campaign sitting exactly on a
+// boundary gets the next tier's ratio and nothing is matched past the last tier;
+// progress is taken before the donation, so one that crosses a boundary is matched
+// entirely at the ratio it started in
+fn match_ratio_bps(match_pool: &MatchPool, amount_raised: u64, goal: u64) -> u64 {
+    if match_pool.tiers.is_empty() {
+        return match_pool.match_ratio_bps;
+    }
+
+    let progress_bps = (amount_raised as u128) * (BPS_DENOMINATOR as u128) / (goal as u128);
+    match_pool
+        .tiers
+        .iter()
+        .find(|tier| progress_bps < tier.below_progress_bps as u128)
+        .map_or(0, |tier| tier.ratio_bps)
+}
+
+fn validate_match_tiers(tiers: &[MatchTier]) -> Result<()> {
+    if tiers.len() > MAX_MATCH_TIERS
+        || tiers.iter().any(|tier| {
+            tier.below_progress_bps == 0
+                || tier.below_progress_bps > BPS_DENOMINATOR
+                || tier.ratio_bps > MAX_TIER_MATCH_RATIO_BPS
+        })
+        || tiers
+            .windows(2)
+            .any(|pair| pair[0].below_progress_bps >= pair[1].below_progress_bps)
+    {
+        msg!("Invalid match tiers");
+        return Err(ErrorCode::InvalidMatchTiers.into());
+    }
+
+    Ok(())
+}
+
+// the first campaign-wide gate that would stop a donation right now; per-donor and
+// per-amount checks like the floor, cooldown or allow list are left to donate
+fn donatable_reason(campaign: &Campaign, state: &ProgramState, now: u64) -> DonatableReason {
+    if state.paused {
+        DonatableReason::ProgramPaused
+    } else if campaign.frozen {
+        DonatableReason::Frozen
+    } else if !campaign.active {
+        DonatableReason::Inactive
+    } else if now < campaign.start_time {
+        DonatableReason::NotStarted
+    } else if campaign.deadline != 0 && now > campaign.deadline {
+        DonatableReason::Expired
+    } else if campaign.hard_cap == 0 && campaign.amount_raised >= campaign.goal {
+        DonatableReason::GoalReached
+    } else if campaign.hard_cap != 0 && campaign.amount_raised >= campaign.hard_cap {
+        DonatableReason::HardCapReached
+    } else {
+        DonatableReason::Donatable
+    }
+}
+
+// stamp the moment a donation first carries the campaign to its goal, never moved after;
+// the campaign is complete from then on, and again after raise_goal once it catches up
+fn record_goal_reached(campaign: &mut Campaign, now: u64) {
+    if campaign.status == CampaignStatus::Active && campaign.amount_raised >= campaign.goal {
+        campaign.status = CampaignStatus::Completed;
+    }
+
+    if campaign.goal_reached_at == 0 && campaign.amount_raised >= campaign.goal {
+        campaign.goal_reached_at = now;
+
+        // only the first crossing freezes, so an unfrozen campaign stays open past its goal
+        if campaign.freeze_on_goal {
+            campaign.frozen = true;
+            emit!(CampaignFrozen {
+                cid: campaign.cid,
+                timestamp: now,
+            });
         }
+    }
+}
 
-        let platform_fee = amount * state.platform_fee / 100;
-        let creator_amount = amount - platform_fee;
+// upsert the donor's running total, keep the entries sorted and drop the lowest when full
+fn update_leaderboard(leaderboard: &mut Leaderboard, donor: Pubkey, total: u64) {
+    match leaderboard
+        .entries
+        .iter_mut()
+        .find(|entry| entry.donor == donor)
+    {
+        Some(entry) => entry.total = total,
+        None => leaderboard.entries.push(LeaderboardEntry { donor, total }),
+    }
 
-        **campaign.to_account_info().try_borrow_mut_lamports()? -= creator_amount;
-        **creator.to_account_info().try_borrow_mut_lamports()? += creator_amount;
+    // stable sort, so on a tie the donor who got there first keeps the higher spot
+    leaderboard
+        .entries
+        .sort_by_key(|entry| std::cmp::Reverse(entry.total));
+    leaderboard.entries.truncate(LEADERBOARD_SIZE);
+}
 
-        **campaign.to_account_info().try_borrow_mut_lamports()? -= platform_fee;
-        **platform_account_info.to_account_info().try_borrow_mut_lamports()? += platform_fee;
+// the cooldown and per-donor cap, both measured against the donor's record for the campaign
+fn check_donor_limits(
+    campaign: &Campaign,
+    donor_record: &DonorRecord,
+    amount: u64,
+    now: u64,
+) -> Result<()> {
+    // a zero cooldown lets a wallet donate as often as it likes
+    if donor_record.donation_count > 0
+        && now.saturating_sub(donor_record.last_donation_ts) < campaign.donation_cooldown
+    {
+        msg!("Donation too frequent");
+        return Err(ErrorCode::DonationTooFrequent.into());
+    }
 
-        campaign.withdrawals += 1;
-        campaign.balance -= amount;
+    // a zero cap leaves each wallet free to give as much as it likes
+    if campaign.max_per_donor != 0
+        && donor_record.total_contributed.saturating_add(amount) > campaign.max_per_donor
+    {
+        msg!("Donation exceeds per-donor cap");
+        return Err(ErrorCode::DonorCapExceeded.into());
+    }
 
-        transaction.amount = amount;
-        transaction.cid = cid;
-        transaction.owner = creator.key();
-        transaction.timestamp = Clock::get()?.unix_timestamp as u64;
-        transaction.credited = false;
+    Ok(())
+}
 
-        msg!("Withdrawal successful");
-        Ok(())
+// bookkeeping every donation path shares once the lamports have landed: the top donor,
+// the donor's record and rank, the leaderboard and the DonationReceived event
+fn record_donation(
+    campaign: &mut Campaign,
+    donor_record: &mut DonorRecord,
+    leaderboard: &mut Leaderboard,
+    donor: Pubkey,
+    amount: u64,
+    anonymous: bool,
+    timestamp: u64,
+) {
+    // a new top donation must beat the current one by the configured increment
+    if campaign.largest_donation == 0
+        || amount > campaign.largest_donation.saturating_add(campaign.leaderboard_min_increment)
+    {
+        campaign.top_donor = donor;
+        campaign.largest_donation = amount;
     }
 
-    // update platform settings
-    pub fn update_platform_settings(
-        ctx: Context<UpdatePlatformSettingsCtx>,
-        new_platform_fee: u64,
+    // the first donation from a wallet assigns its unique donor rank
+    let first_time_donor = donor_record.donation_count == 0;
+    if first_time_donor {
+        campaign.unique_donors += 1;
+        donor_record.cid = campaign.cid;
+        donor_record.donor = donor;
+        donor_record.donor_rank = campaign.unique_donors;
+    }
+    donor_record.donation_count += 1;
+    donor_record.total_contributed += amount;
+    donor_record.last_donation_ts = timestamp;
+
+    // anonymous donations stay off the leaderboard
+    leaderboard.cid = campaign.cid;
+    if !anonymous {
+        update_leaderboard(leaderboard, donor, donor_record.total_contributed);
+    }
+
+    // on-chain data is public either way, anonymity only keeps the donor out of feeds
+    emit!(DonationReceived {
+        cid: campaign.cid,
+        donor: if anonymous { Pubkey::default() } else { donor },
+        amount,
+        amount_raised: campaign.amount_raised,
+        donor_rank: donor_record.donor_rank,
+        first_time_donor,
+        timestamp,
+    });
+}
+
+// create a PDA passed in remaining accounts the way init_if_needed would, returning
+// whether it was created and so still needs its discriminator written
+fn init_pda_if_needed<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    space: usize,
+    seeds: &[&[u8]],
+    program_id: &Pubkey,
+) -> Result<bool> {
+    if target.owner == program_id {
+        return Ok(false);
+    }
+
+    // anyone can send lamports to the address first, so like anchor's init this tops up,
+    // allocates and assigns instead of create_account, which fails on a funded account
+    let rent = Rent::get()?.minimum_balance(space);
+    let top_up = rent.saturating_sub(target.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, target.key, top_up),
+            &[payer.clone(), target.clone()],
+        )?;
+    }
+
+    invoke_signed(
+        &system_instruction::allocate(target.key, space as u64),
+        std::slice::from_ref(target),
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(target.key, program_id),
+        std::slice::from_ref(target),
+        &[seeds],
+    )?;
+
+    Ok(true)
+}
+
+// grow a campaign written under an older layout to the current size, with the payer
+// topping up its rent, and read it back; the new tail is zeroed so fields appended since
+// read as zero and are backfilled to what create_campaign would have set. The campaign is
+// stamped with the current version and the version it was stored at is returned
+fn load_resized_campaign<'info>(
+    info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<(Campaign, u8)> {
+    let space = ANCHOR_DISCRIMINATOR_SIZE + Campaign::INIT_SPACE;
+    if info.data_len() < space {
+        let top_up = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(info.lamports());
+        if top_up > 0 {
+            invoke(
+                &system_instruction::transfer(payer.key, info.key, top_up),
+                &[payer.clone(), info.clone(), system_program.clone()],
+            )?;
+        }
+        info.realloc(space, true)?;
+    }
+
+    let mut campaign = Campaign::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    let previous_version = campaign.version;
+    campaign.version = CAMPAIGN_VERSION;
+
+    // payouts to a zeroed beneficiary would go to the system program's address
+    if campaign.beneficiary == Pubkey::default() {
+        campaign.beneficiary = campaign.creator;
+    }
+
+    if campaign.currency_symbol.is_empty() {
+        campaign.display_decimals = 9;
+        campaign.currency_symbol = "SOL".to_string();
+    }
+
+    // before status existed the only way to go inactive was cancelling
+    if !campaign.active && campaign.status == CampaignStatus::Active {
+        campaign.status = CampaignStatus::Cancelled;
+        campaign.refunds_open = true;
+    }
+
+    Ok((campaign, previous_version))
+}
+
+// pay out to the beneficiary, or across the splits passed in remaining accounts
+// each split gets its floored share and the last one also receives the rounding dust
+fn distribute_payout<'info>(
+    source: &AccountInfo<'info>,
+    beneficiary: &AccountInfo<'info>,
+    splits: &[Split],
+    recipients: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    if splits.is_empty() {
+        **source.try_borrow_mut_lamports()? -= amount;
+        **beneficiary.try_borrow_mut_lamports()? += amount;
+        return Ok(());
+    }
+
+    if recipients.len() < splits.len() {
+        msg!("Missing split recipient accounts");
+        return Err(ErrorCode::InvalidSplitConfig.into());
+    }
+
+    let mut distributed: u64 = 0;
+    for (i, (split, recipient)) in splits.iter().zip(recipients.iter()).enumerate() {
+        if recipient.key() != split.recipient {
+            msg!("Split recipient account mismatch");
+            return Err(ErrorCode::InvalidSplitConfig.into());
+        }
+
+        let share = if i == splits.len() - 1 {
+            amount
+                .checked_sub(distributed)
+                .ok_or(ErrorCode::InvalidSplitConfig)?
+        } else {
+            (amount as u128)
+                .checked_mul(split.bps as u128)
+                .and_then(|value| value.checked_div(BPS_DENOMINATOR as u128))
+                .and_then(|value| u64::try_from(value).ok())
+                .ok_or(ErrorCode::InvalidSplitConfig)?
+        };
+
+        distributed = distributed
+            .checked_add(share)
+            .ok_or(ErrorCode::InvalidSplitConfig)?;
+
+        **source.try_borrow_mut_lamports()? -= share;
+        **recipient.try_borrow_mut_lamports()? += share;
+    }
+
+    Ok(())
+}
+
+#[program]
+pub mod crowdfunding {
+    use super::*;
+
+    // initialize the program
+    pub fn initialize(ctx: Context<InitializeCtx>) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let deployer = &ctx.accounts.deployer;
+        let fee_history = &mut ctx.accounts.fee_history;
+
+        if state.initialized {
+            msg!("Program already initialized");
+            return Err(ErrorCode::AlreadyInitialized.into());
+        }
+
+        initialize_program_state(state, fee_history, deployer.key())?;
+
+        msg!("Program initialized successfully");
+        Ok(())
+    }
+
+    // same as initialize, but a no-op once the program is set up so deploys can rerun it
+    pub fn initialize_if_needed(ctx: Context<InitializeIfNeededCtx>) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let deployer = &ctx.accounts.deployer;
+        let fee_history = &mut ctx.accounts.fee_history;
+
+        if state.initialized {
+            msg!("Program already initialized, nothing to do");
+            return Ok(());
+        }
+
+        initialize_program_state(state, fee_history, deployer.key())?;
+
+        msg!("Program initialized successfully");
+        Ok(())
+    }
+
+    // create a new campaign
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_campaign(
+        ctx: Context<CreateCampaignCtx>,
+        title: String,
+        description: String,
+        image_url: String,
+        goal: u64,
+        start_time: u64,
+        deadline: u64,
+        beneficiary: Option<Pubkey>,
+        splits: Vec<Split>,
+        category_id: u64,
+        tags: Vec<String>,
+        donation_cooldown: u64,
+        lock_until_goal: bool,
+        soft_cap: u64,
+        hard_cap: u64,
+        require_soft_cap: bool,
+        display_decimals: Option<u8>,
+        currency_symbol: Option<String>,
+        auto_forward: bool,
+        media_urls: Vec<String>,
+        creator_seed_amount: u64,
     ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
         let state = &mut ctx.accounts.program_state;
-        let updater = &ctx.accounts.updater;
 
-        if updater.key() != state.platform_address {
-            msg!("Unauthorized platform settings update attempt");
-            return Err(ErrorCode::Unauthorized.into());
-        }
+        if state.paused {
+            msg!("Program paused, campaign creation rejected");
+            return Err(ErrorCode::ProgramPaused.into());
+        }
+
+        if ctx.accounts.platform_address.key() != state.platform_address {
+            msg!("Invalid platform address for campaign creation");
+            return Err(ErrorCode::InvalidPlatformAddress.into());
+        }
+
+        if state.authority_cannot_create && ctx.accounts.creator.key() == state.platform_address {
+            msg!("Platform authority cannot create campaigns");
+            return Err(ErrorCode::AuthorityCannotCreate.into());
+        }
+
+        if title.len() > 64 {
+            msg!("Title too long");
+            return Err(ErrorCode::TitleTooLong.into());
+        }
+        if description.len() > 512 {
+            msg!("Description too long");
+            return Err(ErrorCode::DescriptionTooLong.into());
+        }
+        validate_title(&title)?;
+        validate_description(&description)?;
+        if image_url.len() > 256 {
+            msg!("Image URL too long");
+            return Err(ErrorCode::ImageUrlTooLong.into());
+        }
+        if goal < MIN_AMOUNT_LAMPORTS {
+            msg!("Invalid goal amount");
+            return Err(ErrorCode::InvalidGoalAmount.into());
+        }
+        // a zero max goal means campaigns are uncapped
+        if state.max_goal != 0 && goal > state.max_goal {
+            msg!("Goal exceeds platform maximum");
+            return Err(ErrorCode::GoalTooLarge.into());
+        }
+        // a zero deadline means the campaign runs until it is deleted
+        if deadline != 0
+            && (deadline <= start_time || deadline <= Clock::get()?.unix_timestamp as u64)
+        {
+            msg!("Invalid deadline");
+            return Err(ErrorCode::InvalidDeadline.into());
+        }
+        if deadline != 0
+            && deadline - start_time.max(Clock::get()?.unix_timestamp as u64) < MIN_CAMPAIGN_DURATION
+        {
+            msg!("Campaign duration too short");
+            return Err(ErrorCode::CampaignTooShort.into());
+        }
+        validate_splits(&splits)?;
+        validate_tags(&tags)?;
+        validate_media_urls(&media_urls)?;
+        validate_caps(soft_cap, goal, hard_cap)?;
+
+        if require_soft_cap && soft_cap == 0 {
+            msg!("Soft cap required without a soft cap");
+            return Err(ErrorCode::InvalidCapConfig.into());
+        }
+
+        let display_decimals = display_decimals.unwrap_or(9);
+        let currency_symbol = currency_symbol.unwrap_or_else(|| "SOL".to_string());
+        if display_decimals > 9 {
+            msg!("Invalid display decimals");
+            return Err(ErrorCode::InvalidDisplayDecimals.into());
+        }
+        if currency_symbol.is_empty() || currency_symbol.len() > 8 {
+            msg!("Invalid currency symbol");
+            return Err(ErrorCode::InvalidCurrencySymbol.into());
+        }
+
+        // category zero means uncategorized, anything else must be registered
+        if category_id != 0 {
+            match &ctx.accounts.category {
+                Some(category) if category.id == category_id => {}
+                _ => {
+                    msg!("Unregistered category");
+                    return Err(ErrorCode::InvalidCategory.into());
+                }
+            }
+        }
+
+        if state.create_fee > 0 {
+            let fee_instruction = system_instruction::transfer(
+                &ctx.accounts.creator.key(),
+                &ctx.accounts.platform_address.key(),
+                state.create_fee,
+            );
+
+            let result = invoke(
+                &fee_instruction,
+                &[
+                    ctx.accounts.creator.to_account_info(),
+                    ctx.accounts.platform_address.to_account_info(),
+                ],
+            );
+
+            if let Err(_e) = result {
+                msg!("Campaign creation fee transfer failed");
+                return Err(ErrorCode::InsufficientFund.into());
+            }
+        }
+
+        state.campaign_count += 1;
+
+        let creator_profile = &mut ctx.accounts.creator_profile;
+        if creator_profile.campaign_ids.len() >= MAX_CREATOR_CAMPAIGNS {
+            msg!("Creator campaign index is full");
+            return Err(ErrorCode::TooManyCampaigns.into());
+        }
+        creator_profile.creator = ctx.accounts.creator.key();
+        creator_profile.campaigns_created += 1;
+        creator_profile.campaign_ids.push(state.campaign_count);
+
+        campaign.cid = state.campaign_count;
+        campaign.creator = ctx.accounts.creator.key();
+        campaign.title = title;
+        campaign.description = description;
+        campaign.image_url = image_url;
+        campaign.goal = goal;
+        campaign.amount_raised = 0;
+        campaign.donors = 0;
+        campaign.withdrawals = 0;
+        campaign.timestamp = Clock::get()?.unix_timestamp as u64;
+        campaign.created_slot = Clock::get()?.slot;
+        campaign.active = true;
+        campaign.milestone_count = 0;
+        campaign.milestones_released = 0;
+        campaign.milestone_total = 0;
+        campaign.in_kind_count = 0;
+        campaign.in_kind_value = 0;
+        campaign.verified = false;
+        campaign.refund_cursor = 0;
+        campaign.start_time = start_time;
+        campaign.deadline = deadline;
+        campaign.status = CampaignStatus::Active;
+        campaign.beneficiary = beneficiary.unwrap_or(ctx.accounts.creator.key());
+        campaign.splits = splits;
+        campaign.category_id = category_id;
+        campaign.tags = tags;
+        campaign.donation_cooldown = donation_cooldown;
+        campaign.lock_until_goal = lock_until_goal;
+        campaign.soft_cap = soft_cap;
+        campaign.hard_cap = hard_cap;
+        campaign.require_soft_cap = require_soft_cap;
+        campaign.display_decimals = display_decimals;
+        campaign.currency_symbol = currency_symbol;
+        campaign.auto_forward = auto_forward;
+        campaign.media_urls = media_urls;
+        campaign.version = CAMPAIGN_VERSION;
+
+        // the creator can open with their own donation, recorded as the first transaction
+        if creator_seed_amount > 0 {
+            let creator = &ctx.accounts.creator;
+            let seed_transaction = match ctx.accounts.seed_transaction.as_mut() {
+                Some(seed_transaction) => seed_transaction,
+                None => {
+                    msg!("Missing seed transaction account");
+                    return Err(ErrorCode::MissingSeedTransaction.into());
+                }
+            };
+            let (seed_donor_record, seed_leaderboard) = match (
+                ctx.accounts.seed_donor_record.as_mut(),
+                ctx.accounts.seed_leaderboard.as_mut(),
+            ) {
+                (Some(seed_donor_record), Some(seed_leaderboard)) => {
+                    (seed_donor_record, seed_leaderboard)
+                }
+                _ => {
+                    msg!("Missing seed donor record or leaderboard account");
+                    return Err(ErrorCode::MissingSeedTransaction.into());
+                }
+            };
+
+            if creator_seed_amount < state.min_donation_floor {
+                msg!("Creator seed amount too low");
+                return Err(ErrorCode::InvalidDonationAmount.into());
+            }
+
+            // auto-forward campaigns only pay out as donations arrive, so a seed would sit idle
+            if campaign.auto_forward {
+                msg!("Auto-forward campaigns cannot be seeded");
+                return Err(ErrorCode::AutoForwardEnabled.into());
+            }
+
+            // the seed goes through the same donation checks as donate; the whitelist is
+            // the exception, as it is the creator who approves wallets onto it
+            let now = Clock::get()?.unix_timestamp as u64;
+            check_donor_limits(campaign, seed_donor_record, creator_seed_amount, now)?;
+            check_hard_cap(campaign, creator_seed_amount)?;
+            check_min_donation_share(campaign, creator_seed_amount)?;
+            check_donation_increment(campaign, creator_seed_amount)?;
+
+            let creator_lamports_before = creator.lamports();
+            let campaign_lamports_before = campaign.to_account_info().lamports();
+
+            let seed_instruction = system_instruction::transfer(
+                &creator.key(),
+                &campaign.key(),
+                creator_seed_amount,
+            );
+
+            let result = invoke(
+                &seed_instruction,
+                &[creator.to_account_info(), campaign.to_account_info()],
+            );
+
+            if let Err(_e) = result {
+                msg!("Creator seed transfer failed");
+                return Err(ErrorCode::InsufficientFund.into());
+            }
+
+            check_transfer(
+                creator_lamports_before,
+                creator.lamports(),
+                campaign_lamports_before,
+                campaign.to_account_info().lamports(),
+                creator_seed_amount,
+            )?;
+
+            campaign.creator_seed_amount = creator_seed_amount;
+            campaign.amount_raised += creator_seed_amount;
+            campaign.balance += creator_seed_amount;
+            campaign.donors += 1;
+            record_goal_reached(campaign, now);
+            record_donation(
+                campaign,
+                seed_donor_record,
+                seed_leaderboard,
+                creator.key(),
+                creator_seed_amount,
+                false,
+                now,
+            );
+
+            seed_transaction.amount = creator_seed_amount;
+            seed_transaction.cid = campaign.cid;
+            seed_transaction.owner = creator.key();
+            seed_transaction.timestamp = now;
+            seed_transaction.slot = Clock::get()?.slot;
+            #[cfg(feature = "receipt-hash")]
+            {
+                seed_transaction.receipt_hash =
+                    receipt_hash(campaign.cid, &creator.key(), creator_seed_amount, now);
+            }
+            seed_transaction.credited = true;
+            seed_transaction.processed = true;
+            seed_transaction.creator_seed = true;
+        }
+
+        emit_campaign_snapshot(campaign)?;
+
+        msg!("Campaign created successfully");
+        Ok(())
+    }
+
+    // update campaign details
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_campaign(
+        ctx: Context<UpdateCampaignCtx>,
+        cid: u64,
+        title: String,
+        description: String,
+        image_url: String,
+        goal: u64,
+        start_time: u64,
+        tags: Vec<String>,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &mut ctx.accounts.creator;
+        let state = &ctx.accounts.program_state;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if title.len() > 64 {
+            msg!("Title too long on update");
+            return Err(ErrorCode::TitleTooLong.into());
+        }
+        if description.len() > 512 {
+            msg!("Description too long on update");
+            return Err(ErrorCode::DescriptionTooLong.into());
+        }
+        validate_title(&title)?;
+        validate_description(&description)?;
+        if image_url.len() > 256 {
+            msg!("Image URL too long on update");
+            return Err(ErrorCode::ImageUrlTooLong.into());
+        }
+        validate_goal_update(campaign, state, goal)?;
+        if start_time != campaign.start_time
+            && Clock::get()?.unix_timestamp as u64 >= campaign.start_time
+        {
+            msg!("Start time changed after campaign started");
+            return Err(ErrorCode::CampaignAlreadyStarted.into());
+        }
+        if campaign.deadline != 0 && start_time >= campaign.deadline {
+            msg!("Start time after deadline on update");
+            return Err(ErrorCode::InvalidDeadline.into());
+        }
+        if campaign.deadline != 0 && campaign.deadline - start_time < MIN_CAMPAIGN_DURATION {
+            msg!("Campaign duration too short on update");
+            return Err(ErrorCode::CampaignTooShort.into());
+        }
+        validate_tags(&tags)?;
+
+        campaign.title = title;
+        campaign.description = description;
+        campaign.image_url = image_url;
+        campaign.goal = goal;
+        campaign.start_time = start_time;
+        campaign.tags = tags;
+
+        emit_campaign_snapshot(campaign)?;
+
+        msg!("Campaign updated successfully");
+        Ok(())
+    }
+
+    // update only the fields that are provided, leaving the rest untouched
+    pub fn update_campaign_partial(
+        ctx: Context<UpdateCampaignCtx>,
+        cid: u64,
+        title: Option<String>,
+        description: Option<String>,
+        image_url: Option<String>,
+        goal: Option<u64>,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &mut ctx.accounts.creator;
+        let state = &ctx.accounts.program_state;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if let Some(title) = &title {
+            if title.len() > 64 {
+                msg!("Title too long on update");
+                return Err(ErrorCode::TitleTooLong.into());
+            }
+            validate_title(title)?;
+        }
+        if let Some(description) = &description {
+            if description.len() > 512 {
+                msg!("Description too long on update");
+                return Err(ErrorCode::DescriptionTooLong.into());
+            }
+            validate_description(description)?;
+        }
+        if let Some(image_url) = &image_url {
+            if image_url.len() > 256 {
+                msg!("Image URL too long on update");
+                return Err(ErrorCode::ImageUrlTooLong.into());
+            }
+        }
+        if let Some(goal) = goal {
+            validate_goal_update(campaign, state, goal)?;
+        }
+
+        if let Some(title) = title {
+            campaign.title = title;
+        }
+        if let Some(description) = description {
+            campaign.description = description;
+        }
+        if let Some(image_url) = image_url {
+            campaign.image_url = image_url;
+        }
+        if let Some(goal) = goal {
+            campaign.goal = goal;
+        }
+
+        emit_campaign_snapshot(campaign)?;
+
+        msg!("Campaign partially updated successfully");
+        Ok(())
+    }
+
+    // set a stretch goal, reopening the campaign if it had already completed
+    pub fn raise_goal(ctx: Context<UpdateCampaignCtx>, cid: u64, new_goal: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+        let state = &ctx.accounts.program_state;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized goal raise attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for goal raise");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.status == CampaignStatus::Cancelled {
+            msg!("Cancelled campaign for goal raise");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        if new_goal <= campaign.goal {
+            msg!("New goal must be above the current goal");
+            return Err(ErrorCode::InvalidGoalAmount.into());
+        }
+        validate_goal_update(campaign, state, new_goal)?;
+
+        let previous_goal = campaign.goal;
+        campaign.goal = new_goal;
+
+        // a completed campaign is back in play, but still bound by its deadline
+        if campaign.status == CampaignStatus::Completed {
+            campaign.status = CampaignStatus::Active;
+            campaign.active = true;
+        }
+
+        emit!(GoalRaised {
+            cid,
+            previous_goal,
+            new_goal,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+
+        msg!("Campaign goal raised successfully");
+        Ok(())
+    }
+
+    // replace the campaign's media gallery
+    pub fn update_media(
+        ctx: Context<UpdateCampaignCtx>,
+        cid: u64,
+        media_urls: Vec<String>,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized media update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for media update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        validate_media_urls(&media_urls)?;
+        campaign.media_urls = media_urls;
+
+        msg!("Campaign media updated successfully");
+        Ok(())
+    }
+
+    // delete (deactivate) a campaign
+    pub fn delete_campaign(ctx: Context<DeleteCampaignCtx>, cid: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &mut ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized delete attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for delete");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.active {
+            msg!("Campaign already inactive");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        campaign.active = false;
+        campaign.status = CampaignStatus::Cancelled;
+        campaign.refunds_open = true;
+
+        emit!(RefundsEnabled {
+            cid,
+            balance: campaign.balance,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+
+        msg!("Campaign deactivated successfully");
+        Ok(())
+    }
+
+    // donate to a campaign
+    pub fn donate<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DonateCtx<'info>>,
+        cid: u64,
+        amount: u64,
+        referrer: Option<Pubkey>,
+        tip: u64,
+        anonymous: bool,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let donor = &mut ctx.accounts.donor;
+        let transaction = &mut ctx.accounts.transaction;
+        let state = &ctx.accounts.program_state;
+        let donor_record = &mut ctx.accounts.donor_record;
+
+        if state.paused {
+            msg!("Program paused, donation rejected");
+            return Err(ErrorCode::ProgramPaused.into());
+        }
+
+        // the record is freshly initialized, so anything already written means a replay
+        if transaction.processed || transaction.amount != 0 || transaction.timestamp != 0 {
+            msg!("Transaction record already processed");
+            return Err(ErrorCode::TransactionAlreadyProcessed.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for donation");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.frozen {
+            msg!("Campaign frozen, donation rejected");
+            return Err(ErrorCode::CampaignFrozen.into());
+        }
+
+        if !campaign.active {
+            msg!("Inactive campaign for donation");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        if (Clock::get()?.unix_timestamp as u64) < campaign.start_time {
+            msg!("Campaign not started for donation");
+            return Err(ErrorCode::CampaignNotStarted.into());
+        }
+
+        if campaign.deadline != 0 && Clock::get()?.unix_timestamp as u64 > campaign.deadline {
+            msg!("Campaign expired for donation");
+            return Err(ErrorCode::CampaignExpired.into());
+        }
+
+        if amount < state.min_donation_floor {
+            msg!("Donation amount too low");
+            return Err(ErrorCode::InvalidDonationAmount.into());
+        }
+
+        // the allowed donor account only exists for wallets the creator approved
+        if campaign.whitelist_enabled && ctx.accounts.allowed_donor.is_none() {
+            msg!("Donor not whitelisted");
+            return Err(ErrorCode::DonorNotWhitelisted.into());
+        }
+
+        if referrer == Some(donor.key()) {
+            msg!("Donor cannot refer themselves");
+            return Err(ErrorCode::SelfReferral.into());
+        }
+
+        check_donor_limits(
+            campaign,
+            donor_record,
+            amount,
+            Clock::get()?.unix_timestamp as u64,
+        )?;
+        check_hard_cap(campaign, amount)?;
+        check_min_donation_share(campaign, amount)?;
+        check_donation_increment(campaign, amount)?;
+
+        let donor_lamports_before = donor.lamports();
+        let campaign_lamports_before = campaign.to_account_info().lamports();
+
+        let tx_instruction = system_instruction::transfer(
+            &donor.key(),
+            &campaign.key(),
+            amount,
+        );
+
+        let result = invoke(
+            &tx_instruction,
+            &[donor.to_account_info(), campaign.to_account_info()],
+        );
+
+        if let Err(_e) = result {
+            msg!("Donation transfer failed");
+            return Err(ErrorCode::InsufficientFund.into());
+        }
+
+        // bookkeeping below trusts amount, so the transfer must have moved exactly that
+        check_transfer(
+            donor_lamports_before,
+            donor.lamports(),
+            campaign_lamports_before,
+            campaign.to_account_info().lamports(),
+            amount,
+        )?;
+
+        // an optional tip goes straight to the platform and never counts toward the campaign
+        if tip > 0 {
+            let platform_address = match &ctx.accounts.platform_address {
+                Some(platform_address) if platform_address.key() == state.platform_address => {
+                    platform_address
+                }
+                _ => {
+                    msg!("Invalid platform address for tip");
+                    return Err(ErrorCode::InvalidPlatformAddress.into());
+                }
+            };
+
+            let tip_instruction = system_instruction::transfer(
+                &donor.key(),
+                &platform_address.key(),
+                tip,
+            );
+
+            let result = invoke(
+                &tip_instruction,
+                &[donor.to_account_info(), platform_address.to_account_info()],
+            );
+
+            if let Err(_e) = result {
+                msg!("Tip transfer failed");
+                return Err(ErrorCode::InsufficientFund.into());
+            }
+        }
+
+        let previous_amount_raised = campaign.amount_raised;
+        campaign.amount_raised += amount;
+        campaign.balance += amount;
+        campaign.donors += 1;
+
+        transaction.amount = amount;
+        transaction.cid = cid;
+        transaction.owner = donor.key();
+        transaction.timestamp = Clock::get()?.unix_timestamp as u64;
+        transaction.slot = Clock::get()?.slot;
+        #[cfg(feature = "receipt-hash")]
+        {
+            transaction.receipt_hash =
+                receipt_hash(cid, &donor.key(), amount, transaction.timestamp);
+        }
+        transaction.credited = true;
+        transaction.matched_amount = 0;
+        transaction.processed = true;
+        transaction.referrer = referrer;
+        transaction.tip = tip;
+        transaction.anonymous = anonymous;
+
+        // referrers must have registered stats so attribution can be tallied on-chain
+        if let Some(referrer) = referrer {
+            match ctx.accounts.referral_stats.as_mut() {
+                Some(referral_stats) if referral_stats.referrer == referrer => {
+                    referral_stats.referred_total += amount;
+                    referral_stats.referred_count += 1;
+                }
+                _ => {
+                    msg!("Missing or mismatched referral stats");
+                    return Err(ErrorCode::InvalidReferrer.into());
+                }
+            }
+        }
+
+        // a sponsor's pool tops up the donation until it runs dry
+        if let Some(match_pool) = ctx.accounts.match_pool.as_mut() {
+            let headroom = if campaign.hard_cap == 0 {
+                u64::MAX
+            } else {
+                campaign.hard_cap.saturating_sub(campaign.amount_raised)
+            };
+            let ratio_bps = match_ratio_bps(match_pool, previous_amount_raised, campaign.goal);
+            let matched = ((amount as u128) * (ratio_bps as u128) / (BPS_DENOMINATOR as u128))
+                .min(match_pool.matched_remaining as u128)
+                .min(headroom as u128) as u64;
+
+            if matched > 0 {
+                **match_pool.to_account_info().try_borrow_mut_lamports()? -= matched;
+                **campaign.to_account_info().try_borrow_mut_lamports()? += matched;
+
+                match_pool.matched_remaining -= matched;
+                match_pool.total_matched += matched;
+                campaign.amount_raised += matched;
+                campaign.balance += matched;
+                campaign.matched_total += matched;
+                transaction.matched_amount = matched;
+            }
+        }
+
+        record_goal_reached(campaign, transaction.timestamp);
+
+        // auto-forward campaigns pass the net straight to the beneficiary, unless withdrawals
+        // are paused or the campaign is frozen (possibly by this very donation); then the
+        // funds stay in the campaign until a later donation forwards them or withdraw does
+        if campaign.auto_forward && !state.withdrawals_paused && !campaign.frozen {
+            let beneficiary = match &ctx.accounts.beneficiary {
+                Some(beneficiary) if beneficiary.key() == campaign.beneficiary => beneficiary,
+                _ => {
+                    msg!("Missing or invalid beneficiary for auto-forward");
+                    return Err(ErrorCode::InvalidBeneficiary.into());
+                }
+            };
+            // forwarding is a payout, so the creator's loyalty discount applies as well
+            let successful_campaigns = match &ctx.accounts.creator_profile {
+                Some(creator_profile) => creator_profile.successful_campaigns,
+                None => {
+                    msg!("Missing creator profile for auto-forward");
+                    return Err(ErrorCode::AutoForwardEnabled.into());
+                }
+            };
+            let treasury = &mut ctx.accounts.treasury;
+
+            let forwarded = campaign.balance;
+            let (platform_fee, net_amount) =
+                withdrawal_fee(campaign, state, successful_campaigns, forwarded)?;
+
+            distribute_payout(
+                &campaign.to_account_info(),
+                &beneficiary.to_account_info(),
+                &campaign.splits,
+                ctx.remaining_accounts,
+                net_amount,
+            )?;
+
+            **campaign.to_account_info().try_borrow_mut_lamports()? -= platform_fee;
+            **treasury.to_account_info().try_borrow_mut_lamports()? += platform_fee;
+            treasury.total_fees_collected += platform_fee;
+
+            campaign.balance -= forwarded;
+            campaign.withdrawn_total += forwarded;
+        }
+
+        // usd campaigns snapshot their progress at the current oracle price
+        if campaign.usd_denominated {
+            let price_feed = match &ctx.accounts.price_feed {
+                Some(price_feed) if price_feed.key() == state.sol_usd_feed => price_feed,
+                _ => {
+                    msg!("Missing or untrusted price feed");
+                    return Err(ErrorCode::InvalidPriceFeed.into());
+                }
+            };
+
+            let (price, expo, publish_time) =
+                read_sol_usd_price(&price_feed.to_account_info(), transaction.timestamp)?;
+            campaign.raised_usd = lamports_to_usd_cents(campaign.amount_raised, price, expo)?;
+            campaign.reference_price = price;
+            campaign.reference_expo = expo;
+            campaign.price_timestamp = publish_time;
+        }
+
+        record_donation(
+            campaign,
+            donor_record,
+            &mut ctx.accounts.leaderboard,
+            donor.key(),
+            amount,
+            anonymous,
+            transaction.timestamp,
+        );
+
+        emit!(ThresholdCrossed {
+            cid,
+            previous_amount_raised,
+            amount_raised: campaign.amount_raised,
+            timestamp: transaction.timestamp,
+        });
+
+        msg!("Donation successful");
+        Ok(())
+    }
+
+    // withdraw funds from a campaign
+    pub fn withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawCtx<'info>>,
+        cid: u64,
+        amount: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+        let transaction = &mut ctx.accounts.transaction;
+        let state = &mut ctx.accounts.program_state;
+        let treasury = &mut ctx.accounts.treasury;
+        let beneficiary = &ctx.accounts.beneficiary;
+
+        check_withdrawal(
+            campaign,
+            state,
+            creator.key(),
+            beneficiary.key(),
+            cid,
+            amount,
+            false,
+        )?;
+
+        // the record is freshly initialized, so anything already written means a replay
+        if transaction.processed || transaction.amount != 0 || transaction.timestamp != 0 {
+            msg!("Transaction record already processed");
+            return Err(ErrorCode::TransactionAlreadyProcessed.into());
+        }
+
+        apply_withdrawal_limit(campaign, amount, Clock::get()?.unix_timestamp as u64)?;
+
+        let creator_profile = &mut ctx.accounts.creator_profile;
+        record_success(campaign, creator_profile);
+
+        let (platform_fee, creator_amount) = withdrawal_fee(
+            campaign,
+            state,
+            creator_profile.successful_campaigns,
+            amount,
+        )?;
+
+        // checks-effects-interactions: settle the books before any lamports move,
+        // a failed payout below still reverts the whole instruction
+        campaign.withdrawals += 1;
+        campaign.balance -= amount;
+        campaign.withdrawn_total += amount;
+        treasury.total_fees_collected += platform_fee;
+
+        distribute_payout(
+            &campaign.to_account_info(),
+            &beneficiary.to_account_info(),
+            &campaign.splits,
+            ctx.remaining_accounts,
+            creator_amount,
+        )?;
+
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= platform_fee;
+        **treasury.to_account_info().try_borrow_mut_lamports()? += platform_fee;
+
+        transaction.amount = amount;
+        transaction.cid = cid;
+        transaction.owner = creator.key();
+        transaction.timestamp = Clock::get()?.unix_timestamp as u64;
+        transaction.slot = Clock::get()?.slot;
+        transaction.credited = false;
+        transaction.processed = true;
+
+        msg!("Withdrawal successful");
+        Ok(())
+    }
+
+    // add a milestone to a campaign before it receives donations
+    pub fn add_milestone(
+        ctx: Context<AddMilestoneCtx>,
+        cid: u64,
+        amount: u64,
+        description: String,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+        let milestone = &mut ctx.accounts.milestone;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for milestone");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized milestone attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if !campaign.active {
+            msg!("Inactive campaign for milestone");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        if campaign.amount_raised > 0 {
+            msg!("Milestones locked after donations");
+            return Err(ErrorCode::MilestonesLocked.into());
+        }
+
+        if description.len() > 256 {
+            msg!("Milestone description too long");
+            return Err(ErrorCode::MilestoneDescriptionTooLong.into());
+        }
+
+        if amount == 0 {
+            msg!("Invalid milestone amount");
+            return Err(ErrorCode::InvalidMilestoneAmount.into());
+        }
+
+        let milestone_total = campaign
+            .milestone_total
+            .checked_add(amount)
+            .ok_or(ErrorCode::MilestoneExceedsGoal)?;
+        if milestone_total > campaign.goal {
+            msg!("Milestones exceed campaign goal");
+            return Err(ErrorCode::MilestoneExceedsGoal.into());
+        }
+
+        campaign.milestone_count += 1;
+        campaign.milestone_total = milestone_total;
+
+        milestone.cid = cid;
+        milestone.index = campaign.milestone_count;
+        milestone.amount = amount;
+        milestone.description = description;
+        milestone.released = false;
+
+        msg!("Milestone added successfully");
+        Ok(())
+    }
+
+    // release the next milestone's funds to the creator
+    pub fn release_milestone<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReleaseMilestoneCtx<'info>>,
+        cid: u64,
+        index: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let milestone = &mut ctx.accounts.milestone;
+        let creator = &ctx.accounts.creator;
+        let state = &ctx.accounts.program_state;
+        let treasury = &mut ctx.accounts.treasury;
+        let beneficiary = &ctx.accounts.beneficiary;
+
+        if milestone.released {
+            msg!("Milestone already released");
+            return Err(ErrorCode::MilestoneAlreadyReleased.into());
+        }
+
+        if index != campaign.milestones_released + 1 {
+            msg!("Milestone released out of order");
+            return Err(ErrorCode::MilestoneOutOfOrder.into());
+        }
+
+        // a milestone is a withdrawal of a preset amount, so every withdraw rule applies
+        let amount = milestone.amount;
+        check_withdrawal(
+            campaign,
+            state,
+            creator.key(),
+            beneficiary.key(),
+            cid,
+            amount,
+            true,
+        )?;
+        apply_withdrawal_limit(campaign, amount, Clock::get()?.unix_timestamp as u64)?;
+
+        let creator_profile = &mut ctx.accounts.creator_profile;
+        record_success(campaign, creator_profile);
+
+        let (platform_fee, creator_amount) = withdrawal_fee(
+            campaign,
+            state,
+            creator_profile.successful_campaigns,
+            amount,
+        )?;
+
+        distribute_payout(
+            &campaign.to_account_info(),
+            &beneficiary.to_account_info(),
+            &campaign.splits,
+            ctx.remaining_accounts,
+            creator_amount,
+        )?;
+
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= platform_fee;
+        **treasury.to_account_info().try_borrow_mut_lamports()? += platform_fee;
+        treasury.total_fees_collected += platform_fee;
+
+        campaign.milestones_released += 1;
+        campaign.balance -= amount;
+        campaign.withdrawn_total += amount;
+        milestone.released = true;
+
+        msg!("Milestone released successfully");
+        Ok(())
+    }
+
+    // record an in-kind (goods or services) pledge to a campaign
+    pub fn record_in_kind(
+        ctx: Context<RecordInKindCtx>,
+        cid: u64,
+        description: String,
+        estimated_value: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let donor = &ctx.accounts.donor;
+        let pledge = &mut ctx.accounts.pledge;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for in-kind pledge");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.active {
+            msg!("Inactive campaign for in-kind pledge");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        if description.len() > 128 {
+            msg!("In-kind description too long");
+            return Err(ErrorCode::InKindDescriptionTooLong.into());
+        }
+
+        if estimated_value == 0 {
+            msg!("Invalid in-kind value");
+            return Err(ErrorCode::InvalidInKindValue.into());
+        }
+
+        campaign.in_kind_count += 1;
+
+        pledge.cid = cid;
+        pledge.donor = donor.key();
+        pledge.index = campaign.in_kind_count;
+        pledge.description = description;
+        pledge.estimated_value = estimated_value;
+        pledge.timestamp = Clock::get()?.unix_timestamp as u64;
+        pledge.confirmed = false;
+
+        msg!("In-kind pledge recorded successfully");
+        Ok(())
+    }
+
+    // confirm receipt of an in-kind pledge
+    pub fn confirm_in_kind(
+        ctx: Context<ConfirmInKindCtx>,
+        cid: u64,
+        _donor: Pubkey,
+        _index: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+        let pledge = &mut ctx.accounts.pledge;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for in-kind confirmation");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized in-kind confirmation attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if pledge.confirmed {
+            msg!("In-kind pledge already confirmed");
+            return Err(ErrorCode::InKindAlreadyConfirmed.into());
+        }
+
+        pledge.confirmed = true;
+        campaign.in_kind_value += pledge.estimated_value;
+
+        msg!("In-kind pledge confirmed successfully");
+        Ok(())
+    }
+
+    // update platform settings
+    pub fn update_platform_settings(
+        ctx: Context<UpdatePlatformSettingsCtx>,
+        new_platform_fee_bps: u64,
+        new_create_fee: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized platform settings update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        check_fee_bounds(state, new_platform_fee_bps)?;
+
+        state.platform_fee_bps = new_platform_fee_bps;
+        state.create_fee = new_create_fee;
+
+        // keep the most recent entries once the history is full
+        let fee_history = &mut ctx.accounts.fee_history;
+        if fee_history.entries.len() >= MAX_FEE_HISTORY {
+            fee_history.entries.remove(0);
+        }
+        fee_history.entries.push(FeeEntry {
+            effective_ts: Clock::get()?.unix_timestamp as u64,
+            fee: new_platform_fee_bps,
+        });
+
+        msg!("Platform settings updated successfully");
+        Ok(())
+    }
+
+    // pause or unpause the whole program
+    pub fn set_program_paused(ctx: Context<SetProgramPausedCtx>, paused: bool) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized pause attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        state.paused = paused;
+
+        msg!("Program pause state updated successfully");
+        Ok(())
+    }
+
+    // pause or unpause payouts to creators without touching donations
+    pub fn set_withdrawals_paused(
+        ctx: Context<SetWithdrawalsPausedCtx>,
+        paused: bool,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized withdrawal pause attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        state.withdrawals_paused = paused;
+
+        msg!("Withdrawal pause state updated successfully");
+        Ok(())
+    }
+
+    // emergency reassignment of the platform address, takes effect immediately
+    pub fn force_set_platform_address(
+        ctx: Context<ForceSetPlatformAddressCtx>,
+        new_address: Pubkey,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized platform address change attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if new_address == Pubkey::default() {
+            msg!("Platform address cannot be the default pubkey");
+            return Err(ErrorCode::InvalidPlatformAddress.into());
+        }
+
+        let previous_address = state.platform_address;
+        state.platform_address = new_address;
+
+        msg!(
+            "WARNING: platform address force-changed from {} to {}",
+            previous_address,
+            new_address
+        );
+        emit!(PlatformAddressChanged {
+            previous_address,
+            new_address,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+
+        Ok(())
+    }
+
+    // keep an operating buffer above rent in every campaign on creator payouts
+    pub fn set_rent_buffer(ctx: Context<SetRentBufferCtx>, rent_buffer: u64) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized rent buffer update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        state.rent_buffer = rent_buffer;
+
+        msg!("Rent buffer updated successfully");
+        Ok(())
+    }
+
+    // tune the smallest donation and withdrawal the program accepts
+    pub fn set_amount_floors(
+        ctx: Context<SetAmountFloorsCtx>,
+        min_donation_floor: u64,
+        min_withdrawal_floor: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized amount floor update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if min_donation_floor == 0 || min_withdrawal_floor == 0 {
+            msg!("Amount floors must be greater than zero");
+            return Err(ErrorCode::InvalidAmountFloor.into());
+        }
+
+        state.min_donation_floor = min_donation_floor;
+        state.min_withdrawal_floor = min_withdrawal_floor;
+
+        msg!("Amount floors updated successfully");
+        Ok(())
+    }
+
+    // set how many flags a campaign can collect before it is surfaced for review
+    pub fn set_flag_threshold(ctx: Context<SetFlagThresholdCtx>, flag_threshold: u64) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized flag threshold update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        state.flag_threshold = flag_threshold;
+
+        msg!("Flag threshold updated successfully");
+        Ok(())
+    }
+
+    // mark a campaign as verified after platform review
+    pub fn set_campaign_verified(
+        ctx: Context<SetCampaignVerifiedCtx>,
+        cid: u64,
+        verified: bool,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let state = &ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized verification attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for verification");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        campaign.verified = verified;
+
+        emit!(CampaignVerificationChanged {
+            cid,
+            verified,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+
+        msg!("Campaign verification updated successfully");
+        Ok(())
+    }
+
+    // save a completed campaign's metadata as a reusable template
+    pub fn save_as_template(ctx: Context<SaveAsTemplateCtx>, cid: u64) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+        let template = &mut ctx.accounts.template;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for template");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized template attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.amount_raised < campaign.goal {
+            msg!("Campaign not completed for template");
+            return Err(ErrorCode::CampaignNotCompleted.into());
+        }
+
+        template.creator = creator.key();
+        template.source_cid = cid;
+        template.title = campaign.title.clone();
+        template.description = campaign.description.clone();
+        template.image_url = campaign.image_url.clone();
+        template.goal = campaign.goal;
+        template.timestamp = Clock::get()?.unix_timestamp as u64;
+
+        msg!("Campaign saved as template successfully");
+        Ok(())
+    }
+
+    // refund the next donation in the campaign's refund queue
+    // refunds follow donation order (earliest first) and stop when funds run out
+    pub fn refund(ctx: Context<RefundCtx>, cid: u64, index: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let transaction = &mut ctx.accounts.transaction;
+        let donor = &ctx.accounts.donor;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for refund");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        // completed campaigns keep their funds, only cancelled or unfunded ones pay back
+        if !campaign.refunds_open {
+            msg!("Refunds not open for campaign");
+            return Err(ErrorCode::RefundNotAvailable.into());
+        }
+
+        if campaign.proportional_base != 0 {
+            msg!("Refunds are being paid proportionally");
+            return Err(ErrorCode::RefundsInProgress.into());
+        }
+
+        if index != campaign.refund_cursor + 1 {
+            msg!("Refund processed out of order");
+            return Err(ErrorCode::RefundOutOfOrder.into());
+        }
+
+        if !transaction.credited {
+            msg!("Donation already refunded");
+            return Err(ErrorCode::AlreadyRefunded.into());
+        }
+
+        let amount = transaction.amount;
+        if amount > campaign.balance {
+            msg!("Refund exceeds campaign balance");
+            return Err(ErrorCode::InsufficientFund.into());
+        }
+
+        let rent_balance = Rent::get()?.minimum_balance(campaign.to_account_info().data_len());
+        if amount > **campaign.to_account_info().lamports.borrow() - rent_balance {
+            msg!("Refund exceeds campaign's usable balance");
+            return Err(ErrorCode::InsufficientFund.into());
+        }
+
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **donor.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        campaign.balance -= amount;
+        campaign.refunded_total += amount;
+        campaign.refund_cursor += 1;
+        transaction.credited = false;
+
+        msg!("Refund successful");
+        Ok(())
+    }
+
+    // refund a donation pro rata when the pot no longer covers every donor in full
+    // the first call sizes the pot as usable balance against outstanding donations; each
+    // claim is paid its floored share and draws the pot down, so claims can arrive in any
+    // order and the last one collects the remainder, leaving no dust behind on a shortfall
+    pub fn refund_proportional(ctx: Context<RefundCtx>, cid: u64, index: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let transaction = &mut ctx.accounts.transaction;
+        let donor = &ctx.accounts.donor;
+        let state = &ctx.accounts.program_state;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for proportional refund");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.refunds_open {
+            msg!("Refunds not open for proportional refund");
+            return Err(ErrorCode::RefundNotAvailable.into());
+        }
+
+        if !transaction.credited {
+            msg!("Donation already refunded");
+            return Err(ErrorCode::AlreadyRefunded.into());
+        }
+
+        // only what donors paid in is owed back, and only lamports actually held can go out
+        if campaign.proportional_base == 0 {
+            let outstanding = campaign
+                .amount_raised
+                .saturating_sub(campaign.matched_total)
+                .saturating_sub(campaign.refunded_total);
+            let pot = campaign
+                .balance
+                .min(usable_balance(&campaign.to_account_info(), state)?);
+            campaign.proportional_base = outstanding;
+            campaign.proportional_pool = pot.min(outstanding);
+        }
+
+        if campaign.proportional_base == 0 {
+            msg!("Nothing left to refund proportionally");
+            return Err(ErrorCode::InsufficientFund.into());
+        }
+
+        let rent_balance = Rent::get()?.minimum_balance(campaign.to_account_info().data_len());
+        let held = campaign
+            .to_account_info()
+            .lamports()
+            .saturating_sub(rent_balance);
+        let spendable = campaign.balance.min(held);
+        let last_claim = transaction.amount >= campaign.proportional_base;
+        let amount = if last_claim && campaign.proportional_pool < campaign.proportional_base {
+            // donors take a loss, so whatever the pot could not size up goes to them too
+            spendable
+        } else if last_claim {
+            transaction.amount.min(campaign.proportional_pool)
+        } else {
+            ((transaction.amount as u128) * (campaign.proportional_pool as u128)
+                / (campaign.proportional_base as u128)) as u64
+        };
+
+        if amount > spendable {
+            msg!("Proportional refund exceeds campaign's usable balance");
+            return Err(ErrorCode::InsufficientFund.into());
+        }
+
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **donor.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        campaign.balance -= amount;
+        campaign.refunded_total += amount;
+        campaign.proportional_pool = campaign.proportional_pool.saturating_sub(amount);
+        campaign.proportional_base = campaign
+            .proportional_base
+            .saturating_sub(transaction.amount);
+        transaction.credited = false;
+
+        msg!("Proportional refund {} successful: {}", index, amount);
+        Ok(())
+    }
+
+    // refund the next donations in order, passed as (transaction, donor) pairs in
+    // remaining accounts; any invalid pair fails the whole batch
+    pub fn refund_many<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundManyCtx<'info>>,
+        cid: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for batch refund");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.refunds_open {
+            msg!("Refunds not open for batch refund");
+            return Err(ErrorCode::RefundNotAvailable.into());
+        }
+
+        if campaign.proportional_base != 0 {
+            msg!("Refunds are being paid proportionally");
+            return Err(ErrorCode::RefundsInProgress.into());
+        }
+
+        if ctx.remaining_accounts.is_empty()
+            || ctx.remaining_accounts.len() % 2 == 1
+            || ctx.remaining_accounts.len() / 2 > MAX_REFUND_BATCH
+        {
+            msg!("Invalid batch refund accounts");
+            return Err(ErrorCode::BatchTooLarge.into());
+        }
+
+        let rent_balance = Rent::get()?.minimum_balance(campaign.to_account_info().data_len());
+        for accounts in ctx.remaining_accounts.chunks(2) {
+            let (transaction_info, donor) = (&accounts[0], &accounts[1]);
+
+            // the record must be the next one in refund order for this campaign
+            let index = campaign.refund_cursor + 1;
+            let (expected_key, _) = Pubkey::find_program_address(
+                &[
+                    b"donor",
+                    donor.key().as_ref(),
+                    cid.to_le_bytes().as_ref(),
+                    index.to_le_bytes().as_ref(),
+                ],
+                ctx.program_id,
+            );
+            if transaction_info.key() != expected_key {
+                msg!("Refund processed out of order");
+                return Err(ErrorCode::RefundOutOfOrder.into());
+            }
+
+            let mut transaction = Account::<Transaction>::try_from(transaction_info)?;
+            if transaction.cid != cid || transaction.owner != donor.key() {
+                msg!("Donation record does not belong to this campaign");
+                return Err(ErrorCode::InvalidDonorRecords.into());
+            }
+
+            if !transaction.credited {
+                msg!("Donation already refunded");
+                return Err(ErrorCode::AlreadyRefunded.into());
+            }
+
+            let amount = transaction.amount;
+            if amount > campaign.balance
+                || amount > campaign.to_account_info().lamports() - rent_balance
+            {
+                msg!("Refund exceeds campaign balance");
+                return Err(ErrorCode::InsufficientFund.into());
+            }
+
+            **campaign.to_account_info().try_borrow_mut_lamports()? -= amount;
+            **donor.try_borrow_mut_lamports()? += amount;
+
+            campaign.balance -= amount;
+            campaign.refunded_total += amount;
+            campaign.refund_cursor += 1;
+            transaction.credited = false;
+            transaction.exit(ctx.program_id)?;
+        }
+
+        msg!("Batch refund successful");
+        Ok(())
+    }
+
+    // compute how much a donor could reclaim from the campaign's refund queue
+    // donation records are passed in remaining_accounts alongside their indexes
+    pub fn refundable_amount<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundableAmountCtx<'info>>,
+        cid: u64,
+        indexes: Vec<u64>,
+    ) -> Result<u64> {
+        let campaign = &ctx.accounts.campaign;
+        let donor = &ctx.accounts.donor;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for refundable amount");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.refunds_open {
+            msg!("Refunds not open for campaign");
+            return Ok(0);
+        }
+
+        if indexes.len() != ctx.remaining_accounts.len() {
+            msg!("Donation records do not match their indexes");
+            return Err(ErrorCode::InvalidDonorRecords.into());
+        }
+
+        let mut seen: Vec<u64> = Vec::new();
+        let mut total: u64 = 0;
+        for (account_info, &index) in ctx.remaining_accounts.iter().zip(indexes.iter()) {
+            if seen.contains(&index) {
+                continue;
+            }
+            seen.push(index);
+
+            let (expected_key, _) = Pubkey::find_program_address(
+                &[
+                    b"donor",
+                    donor.key().as_ref(),
+                    cid.to_le_bytes().as_ref(),
+                    index.to_le_bytes().as_ref(),
+                ],
+                ctx.program_id,
+            );
+            if account_info.key() != expected_key {
+                msg!("Transaction does not belong to donor and campaign");
+                return Err(ErrorCode::Unauthorized.into());
+            }
+
+            // the queue has already paid out everything up to the cursor
+            if campaign.proportional_base == 0 && index <= campaign.refund_cursor {
+                continue;
+            }
+
+            let transaction = Account::<Transaction>::try_from(account_info)?;
+            if !transaction.credited {
+                continue;
+            }
+
+            // once proportional refunds start claims are paid from what is left of the pot,
+            // and the last one takes the remainder
+            let amount = if campaign.proportional_base == 0 {
+                transaction.amount
+            } else if transaction.amount >= campaign.proportional_base {
+                if campaign.proportional_pool < campaign.proportional_base {
+                    campaign.balance
+                } else {
+                    transaction.amount.min(campaign.proportional_pool)
+                }
+            } else {
+                ((transaction.amount as u128) * (campaign.proportional_pool as u128)
+                    / (campaign.proportional_base as u128)) as u64
+            };
+            total = total
+                .checked_add(amount)
+                .ok_or(ErrorCode::InsufficientFund)?;
+        }
+
+        let refundable = total.min(campaign.balance);
+
+        msg!("Refundable amount: {}", refundable);
+        Ok(refundable)
+    }
+
+    // change where a campaign's withdrawals are paid out
+    pub fn update_beneficiary(
+        ctx: Context<UpdateBeneficiaryCtx>,
+        cid: u64,
+        new_beneficiary: Pubkey,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized beneficiary update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for beneficiary update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        campaign.beneficiary = new_beneficiary;
+
+        msg!("Beneficiary updated successfully");
+        Ok(())
+    }
+
+    // set how much a donation must beat the current top donation by to take the lead
+    pub fn set_leaderboard_increment(
+        ctx: Context<SetLeaderboardIncrementCtx>,
+        cid: u64,
+        increment: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized leaderboard update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for leaderboard update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        campaign.leaderboard_min_increment = increment;
+
+        msg!("Leaderboard increment updated successfully");
+        Ok(())
+    }
+
+    // permissionless crank that closes out a campaign once its deadline has passed
+    pub fn expire_campaign(ctx: Context<ExpireCampaignCtx>, cid: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for expiry");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.active {
+            msg!("Campaign already inactive");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        if campaign.deadline == 0 || Clock::get()?.unix_timestamp as u64 <= campaign.deadline {
+            msg!("Campaign expiry attempted before deadline");
+            return Err(ErrorCode::CampaignNotExpired.into());
+        }
+
+        campaign.active = false;
+        if soft_cap_met(campaign) {
+            campaign.status = CampaignStatus::Completed;
+        } else {
+            // an unfunded campaign owes its donors their money back
+            campaign.status = CampaignStatus::Expired;
+            campaign.refunds_open = true;
+
+            emit!(RefundsEnabled {
+                cid,
+                balance: campaign.balance,
+                timestamp: Clock::get()?.unix_timestamp as u64,
+            });
+        }
+
+        msg!("Campaign expired successfully");
+        Ok(())
+    }
+
+    // close a refunded donation record and return its rent to the donor
+    pub fn close_transaction(
+        ctx: Context<CloseTransactionCtx>,
+        cid: u64,
+        _index: u64,
+    ) -> Result<()> {
+        let transaction = &ctx.accounts.transaction;
+        let donor = &ctx.accounts.donor;
+
+        if transaction.owner != donor.key() || transaction.cid != cid {
+            msg!("Unauthorized transaction close attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if transaction.credited {
+            msg!("Credited donation cannot be closed");
+            return Err(ErrorCode::DonationStillCredited.into());
+        }
+
+        msg!("Transaction closed successfully");
+        Ok(())
+    }
+
+    // look up the platform fee that was in effect at a given timestamp
+    pub fn fee_at(ctx: Context<FeeAtCtx>, ts: u64) -> Result<u64> {
+        let fee_history = &ctx.accounts.fee_history;
+
+        let entry = fee_history
+            .entries
+            .iter()
+            .rev()
+            .find(|entry| entry.effective_ts <= ts);
+
+        match entry {
+            Some(entry) => {
+                msg!("Platform fee at {}: {}", ts, entry.fee);
+                Ok(entry.fee)
+            }
+            None => {
+                msg!("No fee history for requested time");
+                Err(ErrorCode::FeeHistoryUnavailable.into())
+            }
+        }
+    }
+
+    // override the platform fee for a single campaign, or clear the override with None
+    pub fn set_campaign_fee(
+        ctx: Context<SetCampaignFeeCtx>,
+        cid: u64,
+        fee: Option<u64>,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let state = &ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized campaign fee update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for fee update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if let Some(fee) = fee {
+            check_fee_bounds(state, fee)?;
+        }
+
+        campaign.fee_override = fee;
+
+        msg!("Campaign fee updated successfully");
+        Ok(())
+    }
+
+    // register a new campaign category
+    pub fn add_category(ctx: Context<AddCategoryCtx>, id: u64, name: String) -> Result<()> {
+        let category = &mut ctx.accounts.category;
+        let state = &ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized category registration attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if id == 0 {
+            msg!("Category zero is reserved for uncategorized campaigns");
+            return Err(ErrorCode::InvalidCategory.into());
+        }
+
+        if name.len() > 32 {
+            msg!("Category name too long");
+            return Err(ErrorCode::CategoryNameTooLong.into());
+        }
+
+        category.id = id;
+        category.name = name;
+
+        msg!("Category added successfully");
+        Ok(())
+    }
+
+    // cap how much the creator can withdraw within a rolling time window
+    pub fn set_withdrawal_limit(
+        ctx: Context<SetWithdrawalLimitCtx>,
+        cid: u64,
+        withdrawal_window: u64,
+        withdrawal_limit: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized withdrawal limit update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for withdrawal limit update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        // the limit guards against a compromised creator key, so that key can only
+        // tighten it: a lower limit over a window at least as long, never back to unlimited
+        let limited = campaign.withdrawal_window != 0 && campaign.withdrawal_limit != 0;
+        if limited
+            && (withdrawal_window < campaign.withdrawal_window
+                || withdrawal_limit == 0
+                || withdrawal_limit > campaign.withdrawal_limit)
+        {
+            msg!("Withdrawal limit can only be tightened");
+            return Err(ErrorCode::WithdrawalLimitLoosened.into());
+        }
+
+        // the running window carries over so tightening cannot wipe what was withdrawn
+        campaign.withdrawal_window = withdrawal_window;
+        campaign.withdrawal_limit = withdrawal_limit;
+
+        msg!("Withdrawal limit updated successfully");
+        Ok(())
+    }
+
+    // cap how much a single wallet can contribute, zero removes the cap
+    pub fn set_max_per_donor(
+        ctx: Context<SetMaxPerDonorCtx>,
+        cid: u64,
+        max_per_donor: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized donor cap update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for donor cap update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        campaign.max_per_donor = max_per_donor;
+
+        msg!("Donor cap updated successfully");
+        Ok(())
+    }
+
+    // configure the donation totals that unlock each perk tier
+    pub fn set_perk_thresholds(
+        ctx: Context<SetPerkThresholdsCtx>,
+        cid: u64,
+        perk_thresholds: Vec<u64>,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized perk threshold update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for perk threshold update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if perk_thresholds.len() > MAX_PERK_TIERS
+            || perk_thresholds.first() == Some(&0)
+            || perk_thresholds.windows(2).any(|pair| pair[0] >= pair[1])
+        {
+            msg!("Invalid perk thresholds");
+            return Err(ErrorCode::InvalidPerkThresholds.into());
+        }
+
+        campaign.perk_thresholds = perk_thresholds;
+
+        msg!("Perk thresholds updated successfully");
+        Ok(())
+    }
+
+    // require each donation to cover a share of the remaining goal
+    pub fn set_min_donation_bps(
+        ctx: Context<SetMinDonationBpsCtx>,
+        cid: u64,
+        min_donation_bps_of_remaining: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized minimum donation update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for minimum donation update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if min_donation_bps_of_remaining > BPS_DENOMINATOR {
+            msg!("Invalid minimum donation share");
+            return Err(ErrorCode::InvalidMinDonationBps.into());
+        }
+
+        campaign.min_donation_bps_of_remaining = min_donation_bps_of_remaining;
+
+        msg!("Minimum donation share updated successfully");
+        Ok(())
+    }
+
+    // require donations in round multiples of the given lamport amount
+    pub fn set_donation_increment(
+        ctx: Context<SetDonationIncrementCtx>,
+        cid: u64,
+        donation_increment: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized donation increment update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for donation increment update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        campaign.donation_increment = donation_increment;
+
+        msg!("Donation increment updated successfully");
+        Ok(())
+    }
+
+    // opt in or out of the permissionless payout on completion
+    pub fn set_sweep_on_complete(
+        ctx: Context<SetSweepOnCompleteCtx>,
+        cid: u64,
+        enabled: bool,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized sweep setting update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for sweep setting update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        campaign.sweep_on_complete = enabled;
+
+        msg!("Sweep setting updated successfully");
+        Ok(())
+    }
+
+    // opt in or out of freezing the campaign for review when it reaches its goal
+    pub fn set_freeze_on_goal(
+        ctx: Context<SetFreezeOnGoalCtx>,
+        cid: u64,
+        enabled: bool,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized freeze setting update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for freeze setting update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        campaign.freeze_on_goal = enabled;
+
+        msg!("Freeze setting updated successfully");
+        Ok(())
+    }
+
+    // withdraw into the campaign's vesting vault instead of paying out directly
+    pub fn withdraw_to_vault<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawToVaultCtx<'info>>,
+        cid: u64,
+        amount: u64,
+        duration: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let vault = &mut ctx.accounts.vault;
+        let creator = &ctx.accounts.creator;
+        let state = &ctx.accounts.program_state;
+        let treasury = &mut ctx.accounts.treasury;
+
+        // the vault pays campaign.beneficiary when it vests, so that is the payee checked here
+        check_withdrawal(
+            campaign,
+            state,
+            creator.key(),
+            campaign.beneficiary,
+            cid,
+            amount,
+            false,
+        )?;
+
+        // the schedule can be extended by later deposits but never shortened
+        if duration == 0 || duration < vault.duration {
+            msg!("Invalid vesting duration");
+            return Err(ErrorCode::InvalidVestingSchedule.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp as u64;
+        apply_withdrawal_limit(campaign, amount, now)?;
+
+        let creator_profile = &mut ctx.accounts.creator_profile;
+        record_success(campaign, creator_profile);
+
+        let (platform_fee, vault_amount) = withdrawal_fee(
+            campaign,
+            state,
+            creator_profile.successful_campaigns,
+            amount,
+        )?;
+
+        // checkpoint what has vested so far and restart the schedule with the new deposit
+        let vested_base = if vault.duration == 0 {
+            0
+        } else {
+            vested_amount(vault, now)?
+        };
+
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **vault.to_account_info().try_borrow_mut_lamports()? += vault_amount;
+        **treasury.to_account_info().try_borrow_mut_lamports()? += platform_fee;
+        treasury.total_fees_collected += platform_fee;
+
+        campaign.balance -= amount;
+        campaign.withdrawn_total += amount;
+
+        vault.cid = cid;
+        vault.vested_base = vested_base;
+        vault.total_deposited += vault_amount;
+        vault.start_ts = now;
+        vault.duration = duration;
+
+        msg!("Vault withdrawal successful");
+        Ok(())
+    }
+
+    // claim whatever has vested in the campaign's vault to the beneficiary
+    pub fn claim_vested<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimVestedCtx<'info>>,
+        cid: u64,
+    ) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let vault = &mut ctx.accounts.vault;
+        let creator = &ctx.accounts.creator;
+        let beneficiary = &ctx.accounts.beneficiary;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for vested claim");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized vested claim attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if beneficiary.key() != campaign.beneficiary {
+            msg!("Invalid beneficiary for vested claim");
+            return Err(ErrorCode::InvalidBeneficiary.into());
+        }
+
+        let vested = vested_amount(vault, Clock::get()?.unix_timestamp as u64)?;
+        let claimable = vested.saturating_sub(vault.claimed);
+        if claimable == 0 {
+            msg!("Nothing vested to claim");
+            return Err(ErrorCode::NothingToClaim.into());
+        }
+
+        distribute_payout(
+            &vault.to_account_info(),
+            &beneficiary.to_account_info(),
+            &campaign.splits,
+            ctx.remaining_accounts,
+            claimable,
+        )?;
+
+        vault.claimed += claimable;
+
+        msg!("Vested funds claimed successfully");
+        Ok(())
+    }
+
+    // sponsor funds (or tops up) a pool that matches donations to a campaign
+    pub fn fund_match_pool(
+        ctx: Context<FundMatchPoolCtx>,
+        cid: u64,
+        amount: u64,
+        match_ratio_bps: u64,
+    ) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let match_pool = &mut ctx.accounts.match_pool;
+        let sponsor = &ctx.accounts.sponsor;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for match pool");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.active {
+            msg!("Inactive campaign for match pool");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        // only the sponsor who opened the pool can top it up
+        if match_pool.sponsor != Pubkey::default() && match_pool.sponsor != sponsor.key() {
+            msg!("Unauthorized match pool funding attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if match_ratio_bps == 0 || match_ratio_bps > BPS_DENOMINATOR {
+            msg!("Invalid match ratio");
+            return Err(ErrorCode::InvalidMatchRatio.into());
+        }
+
+        if amount < MIN_AMOUNT_LAMPORTS {
+            msg!("Match pool funding amount too low");
+            return Err(ErrorCode::InvalidDonationAmount.into());
+        }
+
+        let tx_instruction = system_instruction::transfer(
+            &sponsor.key(),
+            &match_pool.key(),
+            amount,
+        );
+
+        let result = invoke(
+            &tx_instruction,
+            &[sponsor.to_account_info(), match_pool.to_account_info()],
+        );
+
+        if let Err(_e) = result {
+            msg!("Match pool funding transfer failed");
+            return Err(ErrorCode::InsufficientFund.into());
+        }
+
+        match_pool.cid = cid;
+        match_pool.sponsor = sponsor.key();
+        match_pool.match_ratio_bps = match_ratio_bps;
+        match_pool.matched_remaining += amount;
+
+        msg!("Match pool funded successfully");
+        Ok(())
+    }
+
+    // let the creator publicly respond to reports about their campaign
+    pub fn respond_to_report(ctx: Context<RespondToReportCtx>, cid: u64, text: String) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized report response attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for report response");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if text.len() > 280 {
+            msg!("Report response too long");
+            return Err(ErrorCode::ResponseTooLong.into());
+        }
+
+        campaign.creator_response = text;
+
+        msg!("Report response saved successfully");
+        Ok(())
+    }
+
+    // cap campaign goals, zero removes the cap
+    pub fn set_max_goal(ctx: Context<SetMaxGoalCtx>, max_goal: u64) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized max goal update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        state.max_goal = max_goal;
+
+        msg!("Max goal updated successfully");
+        Ok(())
+    }
+
+    // post an immutable progress update for donors
+    pub fn append_update(ctx: Context<AppendUpdateCtx>, cid: u64, text: String) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let update = &mut ctx.accounts.update;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for update post");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized update post attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if text.len() > 512 {
+            msg!("Update text too long");
+            return Err(ErrorCode::UpdateTooLong.into());
+        }
+
+        campaign.update_count += 1;
+
+        update.cid = cid;
+        update.index = campaign.update_count;
+        update.text = text;
+        update.timestamp = Clock::get()?.unix_timestamp as u64;
+
+        emit!(UpdatePosted {
+            cid,
+            index: update.index,
+            timestamp: update.timestamp,
+        });
+
+        msg!("Update posted successfully");
+        Ok(())
+    }
+
+    // keep the platform authority from also acting as a campaign creator
+    pub fn set_authority_cannot_create(
+        ctx: Context<SetAuthorityCannotCreateCtx>,
+        authority_cannot_create: bool,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized authority creation flag update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        state.authority_cannot_create = authority_cannot_create;
+
+        msg!("Authority creation flag updated successfully");
+        Ok(())
+    }
+
+    // bring a cancelled campaign back to life before its deadline
+    pub fn reactivate_campaign(ctx: Context<ReactivateCampaignCtx>, cid: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized reactivation attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for reactivation");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.active {
+            msg!("Campaign already active");
+            return Err(ErrorCode::CampaignAlreadyActive.into());
+        }
+
+        // expired and completed campaigns are closed out for good
+        if campaign.status != CampaignStatus::Cancelled
+            || (campaign.deadline != 0
+                && Clock::get()?.unix_timestamp as u64 > campaign.deadline)
+        {
+            msg!("Campaign expired, cannot reactivate");
+            return Err(ErrorCode::CampaignExpired.into());
+        }
+
+        // donors may already have been paid back, in order or pro rata
+        if campaign.refund_cursor > 0
+            || campaign.proportional_base != 0
+            || campaign.refunded_total > 0
+        {
+            msg!("Refunds started, cannot reactivate");
+            return Err(ErrorCode::RefundsInProgress.into());
+        }
+
+        campaign.active = true;
+        campaign.status = CampaignStatus::Active;
+        campaign.refunds_open = false;
+
+        msg!("Campaign reactivated successfully");
+        Ok(())
+    }
+
+    // record interest in a custom funding threshold so clients can discover it
+    pub fn subscribe_threshold(
+        ctx: Context<SubscribeThresholdCtx>,
+        cid: u64,
+        threshold: u64,
+    ) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let subscription = &mut ctx.accounts.subscription;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for threshold subscription");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if threshold == 0 {
+            msg!("Invalid threshold");
+            return Err(ErrorCode::InvalidThreshold.into());
+        }
+
+        subscription.cid = cid;
+        subscription.subscriber = ctx.accounts.subscriber.key();
+        subscription.threshold = threshold;
+        subscription.timestamp = Clock::get()?.unix_timestamp as u64;
+
+        msg!("Threshold subscription created successfully");
+        Ok(())
+    }
+
+    // set the trusted pyth sol/usd price update account
+    pub fn set_price_feed(ctx: Context<SetPriceFeedCtx>, sol_usd_feed: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized price feed update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        state.sol_usd_feed = sol_usd_feed;
+
+        msg!("Price feed updated successfully");
+        Ok(())
+    }
+
+    // denominate a campaign's goal in usd cents, zero switches back to sol
+    pub fn set_usd_goal(ctx: Context<SetUsdGoalCtx>, cid: u64, goal_usd: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized usd goal update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for usd goal update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        campaign.usd_denominated = goal_usd > 0;
+        campaign.goal_usd = goal_usd;
+
+        msg!("USD goal updated successfully");
+        Ok(())
+    }
+
+    // step the pool's match ratio down as the campaign fills, an empty list restores the flat ratio
+    pub fn set_match_tiers(
+        ctx: Context<SetMatchTiersCtx>,
+        cid: u64,
+        tiers: Vec<MatchTier>,
+    ) -> Result<()> {
+        let match_pool = &mut ctx.accounts.match_pool;
+        let sponsor = &ctx.accounts.sponsor;
+
+        if match_pool.sponsor != sponsor.key() {
+            msg!("Unauthorized match tier update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if match_pool.cid != cid {
+            msg!("Match pool not found for tier update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        validate_match_tiers(&tiers)?;
+
+        match_pool.tiers = tiers;
+
+        msg!("Match tiers updated successfully");
+        Ok(())
+    }
+
+    // quadratic-funding style payout of what is left in the pool once the campaign ends:
+    // the match is (sum of sqrt(contribution))^2 - sum of contributions, capped by the pool
+    pub fn distribute_match<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeMatchCtx<'info>>,
+        cid: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let match_pool = &mut ctx.accounts.match_pool;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for match distribution");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.active || campaign.status == CampaignStatus::Cancelled {
+            msg!("Campaign has not ended for match distribution");
+            return Err(ErrorCode::CampaignNotCompleted.into());
+        }
+
+        // a match only tops up a campaign that keeps its funds, never one paying donors back
+        if campaign.refunds_open
+            || !(campaign.status == CampaignStatus::Completed || soft_cap_met(campaign))
+        {
+            msg!("Campaign did not succeed for match distribution");
+            return Err(ErrorCode::CampaignNotCompleted.into());
+        }
+
+        if match_pool.distributed {
+            msg!("Match pool already distributed");
+            return Err(ErrorCode::MatchAlreadyDistributed.into());
+        }
+
+        let mut seen: Vec<Pubkey> = Vec::new();
+        let mut sqrt_sum: u128 = 0;
+        let mut contribution_sum: u128 = 0;
+        for account_info in ctx.remaining_accounts.iter() {
+            let donor_record = Account::<DonorRecord>::try_from(account_info)?;
+            if donor_record.cid != cid || seen.contains(&donor_record.donor) {
+                msg!("Invalid donor record for match distribution");
+                return Err(ErrorCode::InvalidDonorRecords.into());
+            }
+            seen.push(donor_record.donor);
+
+            sqrt_sum += isqrt(donor_record.total_contributed as u128);
+            contribution_sum += donor_record.total_contributed as u128;
+        }
+
+        if seen.len() as u64 != campaign.unique_donors {
+            msg!("Donor records do not cover every donor");
+            return Err(ErrorCode::InvalidDonorRecords.into());
+        }
+
+        let matched = (sqrt_sum * sqrt_sum)
+            .saturating_sub(contribution_sum)
+            .min(match_pool.matched_remaining as u128) as u64;
+
+        **match_pool.to_account_info().try_borrow_mut_lamports()? -= matched;
+        **campaign.to_account_info().try_borrow_mut_lamports()? += matched;
+
+        match_pool.matched_remaining -= matched;
+        match_pool.total_matched += matched;
+        match_pool.distributed = true;
+        campaign.amount_raised += matched;
+        campaign.balance += matched;
+        campaign.matched_total += matched;
+
+        msg!("Match distributed: {}", matched);
+        Ok(())
+    }
+
+    // open a referral stats account so donations can be attributed to the referrer
+    pub fn register_referrer(ctx: Context<RegisterReferrerCtx>) -> Result<()> {
+        let referral_stats = &mut ctx.accounts.referral_stats;
+
+        referral_stats.referrer = ctx.accounts.referrer.key();
+        referral_stats.referred_total = 0;
+        referral_stats.referred_count = 0;
+
+        msg!("Referrer registered successfully");
+        Ok(())
+    }
+
+    // push the deadline out, within the lifetime extension cap
+    pub fn extend_deadline(ctx: Context<ExtendDeadlineCtx>, cid: u64, new_deadline: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized deadline extension attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for deadline extension");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.active {
+            msg!("Inactive campaign for deadline extension");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        if campaign.deadline != 0 && Clock::get()?.unix_timestamp as u64 > campaign.deadline {
+            msg!("Campaign expired, cannot extend");
+            return Err(ErrorCode::CampaignExpired.into());
+        }
+
+        // open-ended campaigns have nothing to extend and deadlines only move forward
+        if campaign.deadline == 0 || new_deadline <= campaign.deadline {
+            msg!("Invalid deadline extension");
+            return Err(ErrorCode::InvalidDeadline.into());
+        }
+
+        let total_extension = campaign
+            .total_extension
+            .checked_add(new_deadline - campaign.deadline)
+            .ok_or(ErrorCode::ExtensionCapExceeded)?;
+        if total_extension > MAX_DEADLINE_EXTENSION {
+            msg!("Deadline extension cap exceeded");
+            return Err(ErrorCode::ExtensionCapExceeded.into());
+        }
+
+        campaign.total_extension = total_extension;
+        campaign.deadline = new_deadline;
+
+        msg!("Deadline extended successfully");
+        Ok(())
+    }
+
+    // donate to several campaigns in one instruction; remaining accounts hold
+    // (campaign, transaction, donor record, leaderboard) per entry and any failing entry
+    // rolls back the batch. donor records and leaderboards are kept like donate does, but
+    // matching, usd pricing, whitelists and auto-forward are rejected, so campaigns
+    // relying on any of them have to be donated to individually
+    pub fn donate_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DonateBatchCtx<'info>>,
+        donations: Vec<BatchDonation>,
+    ) -> Result<()> {
+        let donor = &ctx.accounts.donor;
+        let state = &ctx.accounts.program_state;
+
+        if state.paused {
+            msg!("Program paused, batch donation rejected");
+            return Err(ErrorCode::ProgramPaused.into());
+        }
+
+        if donations.len() > MAX_BATCH_DONATIONS {
+            msg!("Batch exceeds {} donations", MAX_BATCH_DONATIONS);
+            return Err(ErrorCode::BatchTooLarge.into());
+        }
+
+        // each donation brings its campaign, transaction record, donor record and leaderboard
+        if ctx.remaining_accounts.len() != donations.len() * 4 {
+            msg!("Batch accounts do not match the donations");
+            return Err(ErrorCode::BatchAccountsMismatch.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp as u64;
+        let mut seen: Vec<u64> = Vec::new();
+        for (entry, accounts) in donations.iter().zip(ctx.remaining_accounts.chunks(4)) {
+            let campaign_info = &accounts[0];
+            let transaction_info = &accounts[1];
+            let donor_record_info = &accounts[2];
+            let leaderboard_info = &accounts[3];
+            let mut campaign = Account::<Campaign>::try_from(campaign_info)?;
+
+            if campaign.cid != entry.cid {
+                msg!("Campaign not found for batch donation");
+                return Err(ErrorCode::CampaignNotFound.into());
+            }
+
+            if campaign.frozen {
+                msg!("Campaign frozen, batch donation rejected");
+                return Err(ErrorCode::CampaignFrozen.into());
+            }
+
+            if seen.contains(&entry.cid) {
+                msg!("Duplicate campaign in batch");
+                return Err(ErrorCode::DuplicateBatchCampaign.into());
+            }
+            seen.push(entry.cid);
+
+            if !campaign.active {
+                msg!("Inactive campaign for batch donation");
+                return Err(ErrorCode::InactiveCampaign.into());
+            }
+
+            if now < campaign.start_time {
+                msg!("Campaign not started for batch donation");
+                return Err(ErrorCode::CampaignNotStarted.into());
+            }
+
+            if campaign.deadline != 0 && now > campaign.deadline {
+                msg!("Campaign expired for batch donation");
+                return Err(ErrorCode::CampaignExpired.into());
+            }
+
+            if entry.amount < state.min_donation_floor {
+                msg!("Batch donation amount too low");
+                return Err(ErrorCode::InvalidDonationAmount.into());
+            }
+
+            check_hard_cap(&campaign, entry.amount)?;
+            check_min_donation_share(&campaign, entry.amount)?;
+            check_donation_increment(&campaign, entry.amount)?;
+
+            if campaign.usd_denominated {
+                msg!("Usd campaigns need a price feed");
+                return Err(ErrorCode::InvalidPriceFeed.into());
+            }
+
+            if campaign.whitelist_enabled {
+                msg!("Whitelisted campaigns need an allowed donor account");
+                return Err(ErrorCode::DonorNotWhitelisted.into());
+            }
+
+            if campaign.auto_forward {
+                msg!("Auto-forward campaigns need the beneficiary account");
+                return Err(ErrorCode::AutoForwardEnabled.into());
+            }
+
+            let donor_key = donor.key();
+            let cid_bytes = entry.cid.to_le_bytes();
+            let index_bytes = (campaign.donors + 1).to_le_bytes();
+            let (transaction_key, bump) = Pubkey::find_program_address(
+                &[b"donor", donor_key.as_ref(), &cid_bytes, &index_bytes],
+                ctx.program_id,
+            );
+
+            if transaction_info.key() != transaction_key {
+                msg!("Transaction record address mismatch");
+                return Err(anchor_lang::error::ErrorCode::ConstraintSeeds.into());
+            }
+
+            let (donor_record_key, donor_record_bump) = Pubkey::find_program_address(
+                &[b"donor_record", &cid_bytes, donor_key.as_ref()],
+                ctx.program_id,
+            );
+            let (leaderboard_key, leaderboard_bump) =
+                Pubkey::find_program_address(&[b"leaderboard", &cid_bytes], ctx.program_id);
+
+            if donor_record_info.key() != donor_record_key
+                || leaderboard_info.key() != leaderboard_key
+            {
+                msg!("Donor record or leaderboard address mismatch");
+                return Err(anchor_lang::error::ErrorCode::ConstraintSeeds.into());
+            }
+
+            let donor_record_created = init_pda_if_needed(
+                &donor.to_account_info(),
+                donor_record_info,
+                ANCHOR_DISCRIMINATOR_SIZE + DonorRecord::INIT_SPACE,
+                &[b"donor_record", &cid_bytes, donor_key.as_ref(), &[donor_record_bump]],
+                ctx.program_id,
+            )?;
+            let mut donor_record = if donor_record_created {
+                Account::<DonorRecord>::try_from_unchecked(donor_record_info)?
+            } else {
+                Account::<DonorRecord>::try_from(donor_record_info)?
+            };
+
+            let leaderboard_created = init_pda_if_needed(
+                &donor.to_account_info(),
+                leaderboard_info,
+                ANCHOR_DISCRIMINATOR_SIZE + Leaderboard::INIT_SPACE,
+                &[b"leaderboard", &cid_bytes, &[leaderboard_bump]],
+                ctx.program_id,
+            )?;
+            let mut leaderboard = if leaderboard_created {
+                Account::<Leaderboard>::try_from_unchecked(leaderboard_info)?
+            } else {
+                Account::<Leaderboard>::try_from(leaderboard_info)?
+            };
+
+            check_donor_limits(&campaign, &donor_record, entry.amount, now)?;
+
+            let donor_lamports_before = donor.lamports();
+            let campaign_lamports_before = campaign_info.lamports();
+
+            let tx_instruction = system_instruction::transfer(
+                &donor.key(),
+                &campaign.key(),
+                entry.amount,
+            );
+
+            let result = invoke(
+                &tx_instruction,
+                &[donor.to_account_info(), campaign_info.clone()],
+            );
+
+            if let Err(_e) = result {
+                msg!("Batch donation transfer failed");
+                return Err(ErrorCode::InsufficientFund.into());
+            }
+
+            check_transfer(
+                donor_lamports_before,
+                donor.lamports(),
+                campaign_lamports_before,
+                campaign_info.lamports(),
+                entry.amount,
+            )?;
+
+            let space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE;
+            invoke_signed(
+                &system_instruction::create_account(
+                    &donor.key(),
+                    &transaction_key,
+                    Rent::get()?.minimum_balance(space),
+                    space as u64,
+                    ctx.program_id,
+                ),
+                &[donor.to_account_info(), transaction_info.clone()],
+                &[&[b"donor", donor_key.as_ref(), &cid_bytes, &index_bytes, &[bump]]],
+            )?;
+
+            let previous_amount_raised = campaign.amount_raised;
+            campaign.amount_raised += entry.amount;
+            campaign.balance += entry.amount;
+            campaign.donors += 1;
+            record_goal_reached(&mut campaign, now);
+            record_donation(
+                &mut campaign,
+                &mut donor_record,
+                &mut leaderboard,
+                donor.key(),
+                entry.amount,
+                false,
+                now,
+            );
+
+            let mut transaction = Account::<Transaction>::try_from_unchecked(transaction_info)?;
+            transaction.amount = entry.amount;
+            transaction.cid = entry.cid;
+            transaction.owner = donor.key();
+            transaction.timestamp = now;
+            transaction.slot = Clock::get()?.slot;
+            #[cfg(feature = "receipt-hash")]
+            {
+                transaction.receipt_hash = receipt_hash(entry.cid, &donor.key(), entry.amount, now);
+            }
+            transaction.credited = true;
+            transaction.processed = true;
+
+            transaction.exit(ctx.program_id)?;
+            donor_record.exit(ctx.program_id)?;
+            leaderboard.exit(ctx.program_id)?;
+            campaign.exit(ctx.program_id)?;
+
+            emit!(ThresholdCrossed {
+                cid: entry.cid,
+                previous_amount_raised,
+                amount_raised: campaign.amount_raised,
+                timestamp: now,
+            });
+        }
+
+        msg!("Batch donation successful: {} campaigns", donations.len());
+        Ok(())
+    }
+
+    // emit a progress snapshot so every client renders the same numbers
+    pub fn compute_progress(ctx: Context<ComputeProgressCtx>, cid: u64) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for progress");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        let remaining = campaign.goal.saturating_sub(campaign.amount_raised);
+        let percent_bps = ((campaign.amount_raised as u128) * (BPS_DENOMINATOR as u128)
+            / (campaign.goal as u128))
+            .min(BPS_DENOMINATOR as u128) as u64;
+
+        emit!(ProgressSnapshot {
+            cid,
+            raised: campaign.amount_raised,
+            goal: campaign.goal,
+            remaining,
+            percent_bps,
+        });
+
+        msg!("Campaign progress: {} bps, {} remaining", percent_bps, remaining);
+        Ok(())
+    }
+
+    // move the bounds that platform and per-campaign fees are validated against
+    pub fn set_fee_bounds(
+        ctx: Context<SetFeeBoundsCtx>,
+        min_fee_bps: u64,
+        max_fee_bps: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized fee bounds update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if min_fee_bps > max_fee_bps || max_fee_bps > MAX_FEE_BOUND_BPS {
+            msg!("Invalid fee bounds");
+            return Err(ErrorCode::InvalidFeeBounds.into());
+        }
+
+        state.min_fee_bps = min_fee_bps;
+        state.max_fee_bps = max_fee_bps;
+
+        msg!("Fee bounds updated successfully");
+        Ok(())
+    }
+
+    // one-time conversion of whole-percent fees to basis points; campaigns with a fee
+    // override are passed in remaining accounts and converted alongside the state
+    pub fn migrate_fee_to_bps<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateFeeToBpsCtx<'info>>,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let fee_history = &mut ctx.accounts.fee_history;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized fee migration attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if state.fee_in_bps {
+            msg!("Platform fee already in basis points");
+            return Err(ErrorCode::FeeAlreadyMigrated.into());
+        }
+
+        state.platform_fee_bps = state.platform_fee_bps.saturating_mul(100);
+        for entry in fee_history.entries.iter_mut() {
+            entry.fee = entry.fee.saturating_mul(100);
+        }
+
+        let mut seen: Vec<Pubkey> = Vec::new();
+        for account_info in ctx.remaining_accounts.iter() {
+            if seen.contains(&account_info.key()) {
+                continue;
+            }
+            seen.push(account_info.key());
+
+            let mut campaign = Account::<Campaign>::try_from(account_info)?;
+            campaign.fee_override = campaign.fee_override.map(|fee| fee.saturating_mul(100));
+            campaign.exit(ctx.program_id)?;
+        }
+
+        state.fee_in_bps = true;
+
+        msg!("Platform fee migrated to {} bps", state.platform_fee_bps);
+        Ok(())
+    }
+
+    // close a settled campaign with nothing left to withdraw or refund and return its
+    // rent to the creator
+    pub fn close_campaign(ctx: Context<CloseCampaignCtx>, cid: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized close attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for close");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.status == CampaignStatus::Active {
+            msg!("Campaign not settled for close");
+            return Err(ErrorCode::CampaignNotSettled.into());
+        }
+
+        // once every donor has their principal back, what is left can only be sponsor
+        // match money, and it goes back to the sponsor rather than to the creator
+        let principal = campaign
+            .amount_raised
+            .saturating_sub(campaign.matched_total);
+        let donors_settled = campaign.refunds_open && campaign.refunded_total >= principal;
+        if campaign.balance != 0 && donors_settled && campaign.matched_total != 0 {
+            let (match_pool, sponsor) = match (
+                ctx.accounts.match_pool.as_ref(),
+                ctx.accounts.sponsor.as_ref(),
+            ) {
+                (Some(match_pool), Some(sponsor)) => (match_pool, sponsor),
+                _ => {
+                    msg!("Leftover match needs the match pool and sponsor");
+                    return Err(ErrorCode::CampaignBalanceNotEmpty.into());
+                }
+            };
+
+            if match_pool.sponsor != sponsor.key() {
+                msg!("Sponsor does not match the match pool");
+                return Err(ErrorCode::Unauthorized.into());
+            }
+
+            let leftover = campaign.balance;
+            **campaign.to_account_info().try_borrow_mut_lamports()? -= leftover;
+            **sponsor.try_borrow_mut_lamports()? += leftover;
+            campaign.balance = 0;
+        }
+
+        if campaign.balance != 0 {
+            msg!("Campaign balance not empty for close");
+            return Err(ErrorCode::CampaignBalanceNotEmpty.into());
+        }
+
+        ctx.accounts.creator_profile.campaign_ids.retain(|id| *id != cid);
+
+        msg!("Campaign closed successfully");
+        Ok(())
+    }
+
+    // turn the donor whitelist on or off
+    pub fn set_whitelist(ctx: Context<SetWhitelistCtx>, cid: u64, enabled: bool) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized whitelist update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for whitelist update");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        campaign.whitelist_enabled = enabled;
+
+        msg!("Campaign whitelist updated successfully");
+        Ok(())
+    }
+
+    // approve a wallet to donate while the whitelist is enabled
+    pub fn add_allowed_donor(
+        ctx: Context<AddAllowedDonorCtx>,
+        cid: u64,
+        donor: Pubkey,
+    ) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+        let allowed_donor = &mut ctx.accounts.allowed_donor;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized allowed donor attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for allowed donor");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        allowed_donor.cid = cid;
+        allowed_donor.donor = donor;
+
+        msg!("Allowed donor added successfully");
+        Ok(())
+    }
+
+    // sweep accrued fees from the treasury to the platform address
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasuryCtx>, amount: u64) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let state = &ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized treasury withdrawal attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        // lamports are written directly, so the platform must be a plain system-owned wallet
+        if *updater.owner != System::id() {
+            msg!("Invalid platform address for treasury withdrawal");
+            return Err(ErrorCode::InvalidPlatformAddress.into());
+        }
+
+        let rent_balance = Rent::get()?.minimum_balance(treasury.to_account_info().data_len());
+        if amount > treasury.to_account_info().lamports().saturating_sub(rent_balance) {
+            msg!("Treasury withdrawal exceeds usable balance");
+            return Err(ErrorCode::InsufficientFund.into());
+        }
+
+        **treasury.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **updater.to_account_info().try_borrow_mut_lamports()? += amount;
+        treasury.total_swept += amount;
+
+        msg!("Treasury withdrawal successful");
+        Ok(())
+    }
+
+    // moderation: the platform can cancel any campaign, opening refunds like a creator cancel
+    pub fn admin_deactivate_campaign(
+        ctx: Context<AdminDeactivateCampaignCtx>,
+        cid: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let state = &ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized admin deactivation attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for admin deactivation");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.active {
+            msg!("Campaign already inactive");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        campaign.active = false;
+        campaign.status = CampaignStatus::Cancelled;
+        campaign.refunds_open = true;
+
+        let timestamp = Clock::get()?.unix_timestamp as u64;
+        emit!(CampaignDeactivatedByAdmin {
+            cid,
+            creator: campaign.creator,
+            admin: updater.key(),
+            timestamp,
+        });
+        emit!(RefundsEnabled {
+            cid,
+            balance: campaign.balance,
+            timestamp,
+        });
+
+        msg!("Campaign deactivated by admin");
+        Ok(())
+    }
+
+    // bring a campaign created under an older layout up to the current one
+    pub fn migrate_campaign(ctx: Context<MigrateCampaignCtx>, cid: u64) -> Result<()> {
+        let info = ctx.accounts.campaign.to_account_info();
+        let (campaign, previous_version) = load_resized_campaign(
+            &info,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for migration");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if previous_version >= CAMPAIGN_VERSION {
+            msg!("Campaign already on the current layout");
+            return Err(ErrorCode::AlreadyMigrated.into());
+        }
+
+        campaign.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!("Campaign migrated successfully");
+        Ok(())
+    }
+
+    // mint a single commemorative token to the creator or a donor once the campaign completes
+    pub fn mint_completion_receipt(
+        ctx: Context<MintCompletionReceiptCtx>,
+        cid: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized receipt mint attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for receipt mint");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.status != CampaignStatus::Completed {
+            msg!("Receipt mint attempted before completion");
+            return Err(ErrorCode::CampaignNotFinished.into());
+        }
+
+        if campaign.receipt_minted {
+            msg!("Completion receipt already minted");
+            return Err(ErrorCode::ReceiptAlreadyMinted.into());
+        }
+
+        let cid_bytes = cid.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"campaign", cid_bytes.as_ref(), &[ctx.bumps.campaign]]];
+
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.receipt_mint.to_account_info(),
+                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                    authority: campaign.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+
+        // drop the mint authority so the supply stays at exactly one
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                SetAuthority {
+                    current_authority: campaign.to_account_info(),
+                    account_or_mint: ctx.accounts.receipt_mint.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            AuthorityType::MintTokens,
+            None,
+        )?;
+
+        campaign.receipt_minted = true;
+
+        msg!("Completion receipt minted successfully");
+        Ok(())
+    }
+
+    // emit the caller's perk tier from their on-chain donation total
+    pub fn check_perk_tier(ctx: Context<CheckPerkTierCtx>, cid: u64) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let donor_record = &ctx.accounts.donor_record;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for perk tier");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        // thresholds are ascending, so the tier is how many of them the total has reached
+        let tier = campaign
+            .perk_thresholds
+            .iter()
+            .filter(|threshold| donor_record.total_contributed >= **threshold)
+            .count() as u8;
+
+        emit!(PerkTier {
+            cid,
+            donor: ctx.accounts.donor.key(),
+            tier,
+        });
+
+        msg!("Donor perk tier: {}", tier);
+        Ok(())
+    }
+
+    // crank: pay what is left in a completed campaign to its beneficiary, once
+    pub fn finalize_campaign<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeCampaignCtx<'info>>,
+        cid: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let transaction = &mut ctx.accounts.transaction;
+        let state = &ctx.accounts.program_state;
+        let treasury = &mut ctx.accounts.treasury;
+        let beneficiary = &ctx.accounts.beneficiary;
+
+        if state.paused {
+            msg!("Program paused, finalize rejected");
+            return Err(ErrorCode::ProgramPaused.into());
+        }
+
+        if state.withdrawals_paused {
+            msg!("Withdrawals paused, finalize rejected");
+            return Err(ErrorCode::WithdrawalsPaused.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for finalize");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.frozen {
+            msg!("Campaign frozen, finalize rejected");
+            return Err(ErrorCode::CampaignFrozen.into());
+        }
+
+        if !campaign.sweep_on_complete {
+            msg!("Campaign has not opted into sweeping");
+            return Err(ErrorCode::SweepNotEnabled.into());
+        }
+
+        if campaign.finalized {
+            msg!("Campaign already finalized");
+            return Err(ErrorCode::AlreadyFinalized.into());
+        }
+
+        // expired campaigns missed their target and stay with the donors for refunds
+        if campaign.status != CampaignStatus::Completed {
+            msg!("Finalize attempted before completion");
+            return Err(ErrorCode::CampaignNotFinished.into());
+        }
+
+        if campaign.milestone_count > 0 {
+            msg!("Campaign funds are locked behind milestones");
+            return Err(ErrorCode::MilestoneReleaseRequired.into());
+        }
+
+        if beneficiary.key() != campaign.beneficiary {
+            msg!("Invalid beneficiary for finalize");
+            return Err(ErrorCode::InvalidBeneficiary.into());
+        }
+
+        let amount = campaign
+            .balance
+            .min(usable_balance(&campaign.to_account_info(), state)?);
+        if amount == 0 {
+            msg!("Nothing left to sweep");
+            return Err(ErrorCode::InsufficientFund.into());
+        }
+        check_net_raised(campaign, amount)?;
+
+        let creator_profile = &mut ctx.accounts.creator_profile;
+        record_success(campaign, creator_profile);
+
+        let (platform_fee, creator_amount) = withdrawal_fee(
+            campaign,
+            state,
+            creator_profile.successful_campaigns,
+            amount,
+        )?;
+
+        campaign.finalized = true;
+        campaign.withdrawals += 1;
+        campaign.balance -= amount;
+        campaign.withdrawn_total += amount;
+        treasury.total_fees_collected += platform_fee;
+
+        distribute_payout(
+            &campaign.to_account_info(),
+            &beneficiary.to_account_info(),
+            &campaign.splits,
+            ctx.remaining_accounts,
+            creator_amount,
+        )?;
+
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= platform_fee;
+        **treasury.to_account_info().try_borrow_mut_lamports()? += platform_fee;
+
+        transaction.amount = amount;
+        transaction.cid = cid;
+        transaction.owner = campaign.creator;
+        transaction.timestamp = Clock::get()?.unix_timestamp as u64;
+        transaction.slot = Clock::get()?.slot;
+        transaction.credited = false;
+        transaction.processed = true;
+
+        msg!("Campaign finalized, {} swept to beneficiary", amount);
+        Ok(())
+    }
+
+    // donate wrapped SOL; the vault is closed into the campaign in the same instruction,
+    // so the donation lands as native lamports and the usual SOL accounting applies
+    pub fn donate_wsol(ctx: Context<DonateWsolCtx>, cid: u64, amount: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let transaction = &mut ctx.accounts.transaction;
+        let donor = &ctx.accounts.donor;
+        let state = &ctx.accounts.program_state;
+        let now = Clock::get()?.unix_timestamp as u64;
+
+        if state.paused {
+            msg!("Program paused, wSOL donation rejected");
+            return Err(ErrorCode::ProgramPaused.into());
+        }
+
+        if ctx.accounts.wsol_mint.key() != native_mint::ID {
+            msg!("Mint is not the native wSOL mint");
+            return Err(ErrorCode::InvalidWsolMint.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for wSOL donation");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.frozen {
+            msg!("Campaign frozen, wSOL donation rejected");
+            return Err(ErrorCode::CampaignFrozen.into());
+        }
+
+        if !campaign.active {
+            msg!("Inactive campaign for wSOL donation");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        if now < campaign.start_time {
+            msg!("Campaign not started for wSOL donation");
+            return Err(ErrorCode::CampaignNotStarted.into());
+        }
+
+        if campaign.deadline != 0 && now > campaign.deadline {
+            msg!("Campaign expired for wSOL donation");
+            return Err(ErrorCode::CampaignExpired.into());
+        }
+
+        if amount < state.min_donation_floor {
+            msg!("wSOL donation amount too low");
+            return Err(ErrorCode::InvalidDonationAmount.into());
+        }
+
+        check_hard_cap(campaign, amount)?;
+        check_min_donation_share(campaign, amount)?;
+        check_donation_increment(campaign, amount)?;
+
+        let donor_record = &mut ctx.accounts.donor_record;
+        check_donor_limits(campaign, donor_record, amount, now)?;
+
+        // like donate_batch, this path carries no feed or allow list
+        if campaign.usd_denominated {
+            msg!("Usd campaigns need a price feed");
+            return Err(ErrorCode::InvalidPriceFeed.into());
+        }
+
+        if campaign.whitelist_enabled {
+            msg!("Whitelisted campaigns need an allowed donor account");
+            return Err(ErrorCode::DonorNotWhitelisted.into());
+        }
+
+        if campaign.auto_forward {
+            msg!("Auto-forward campaigns need the beneficiary account");
+            return Err(ErrorCode::AutoForwardEnabled.into());
+        }
+
+        token::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.donor_token_account.to_account_info(),
+                    mint: ctx.accounts.wsol_mint.to_account_info(),
+                    to: ctx.accounts.wsol_vault.to_account_info(),
+                    authority: donor.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.wsol_mint.decimals,
+        )?;
+
+        // closing the vault unwraps it: the campaign receives the donation plus the
+        // vault's rent, and the rent goes straight back to the donor who paid it
+        let vault_rent = ctx
+            .accounts
+            .wsol_vault
+            .to_account_info()
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(ErrorCode::TransferMismatch)?;
+        let campaign_lamports_before = campaign.to_account_info().lamports();
+
+        let cid_bytes = cid.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"campaign", cid_bytes.as_ref(), &[ctx.bumps.campaign]]];
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.wsol_vault.to_account_info(),
+                destination: campaign.to_account_info(),
+                authority: campaign.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= vault_rent;
+        **donor.to_account_info().try_borrow_mut_lamports()? += vault_rent;
+
+        if campaign.to_account_info().lamports() != campaign_lamports_before + amount {
+            msg!("Unwrapped amount does not match the donation");
+            return Err(ErrorCode::TransferMismatch.into());
+        }
+
+        let previous_amount_raised = campaign.amount_raised;
+        campaign.amount_raised += amount;
+        campaign.balance += amount;
+        campaign.donors += 1;
+        record_goal_reached(campaign, now);
+        record_donation(
+            campaign,
+            donor_record,
+            &mut ctx.accounts.leaderboard,
+            donor.key(),
+            amount,
+            false,
+            now,
+        );
+
+        transaction.amount = amount;
+        transaction.cid = cid;
+        transaction.owner = donor.key();
+        transaction.timestamp = now;
+        transaction.slot = Clock::get()?.slot;
+        #[cfg(feature = "receipt-hash")]
+        {
+            transaction.receipt_hash = receipt_hash(cid, &donor.key(), amount, now);
+        }
+        transaction.credited = true;
+        transaction.processed = true;
+
+        emit!(ThresholdCrossed {
+            cid,
+            previous_amount_raised,
+            amount_raised: campaign.amount_raised,
+            timestamp: now,
+        });
+
+        msg!("wSOL donation successful");
+        Ok(())
+    }
+
+    // release a campaign frozen on reaching its goal once the platform has reviewed it
+    pub fn unfreeze_campaign(ctx: Context<UnfreezeCampaignCtx>, cid: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let state = &ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized unfreeze attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for unfreeze");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.frozen {
+            msg!("Campaign is not frozen");
+            return Err(ErrorCode::CampaignNotFrozen.into());
+        }
+
+        campaign.frozen = false;
+
+        emit!(CampaignUnfrozen {
+            cid,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+
+        msg!("Campaign unfrozen successfully");
+        Ok(())
+    }
+
+    // recovery tool: reset the donation counter to the transaction PDA count verified off-chain
+    pub fn reconcile_donors(
+        ctx: Context<ReconcileDonorsCtx>,
+        cid: u64,
+        actual_count: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let state = &ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized donor reconcile attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for donor reconcile");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        let previous_count = campaign.donors;
+        campaign.donors = actual_count;
+
+        emit!(DonorsReconciled {
+            cid,
+            previous_count,
+            actual_count,
+            timestamp: Clock::get()?.unix_timestamp as u64,
+        });
+
+        msg!("Donor count reconciled successfully");
+        Ok(())
+    }
+
+    // commit to a recurring gift, the first one falls due straight away; the donor
+    // approves the pledge PDA as delegate on their wSOL account to fund it
+    pub fn create_pledge(
+        ctx: Context<CreatePledgeCtx>,
+        cid: u64,
+        amount_per_period: u64,
+        period_seconds: u64,
+    ) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let pledge = &mut ctx.accounts.pledge;
+        let donor = &ctx.accounts.donor;
+        let state = &ctx.accounts.program_state;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for pledge");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.active {
+            msg!("Inactive campaign for pledge");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        if pledge.active {
+            msg!("Pledge already active");
+            return Err(ErrorCode::PledgeAlreadyActive.into());
+        }
+
+        if amount_per_period < state.min_donation_floor || period_seconds == 0 {
+            msg!("Invalid pledge amount or period");
+            return Err(ErrorCode::InvalidPledge.into());
+        }
+
+        pledge.cid = cid;
+        pledge.donor = donor.key();
+        pledge.amount_per_period = amount_per_period;
+        pledge.period_seconds = period_seconds;
+        pledge.next_due_ts = Clock::get()?.unix_timestamp as u64;
+        pledge.active = true;
+        pledge.executed_count = 0;
+
+        msg!("Pledge created successfully");
+        Ok(())
+    }
+
+    // stop a recurring gift, the donor can also revoke the wSOL delegation directly
+    pub fn cancel_pledge(ctx: Context<CancelPledgeCtx>, cid: u64) -> Result<()> {
+        let pledge = &mut ctx.accounts.pledge;
+
+        if pledge.cid != cid {
+            msg!("Pledge not found for cancel");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !pledge.active {
+            msg!("Pledge already cancelled");
+            return Err(ErrorCode::PledgeInactive.into());
+        }
+
+        pledge.active = false;
+
+        msg!("Pledge cancelled successfully");
+        Ok(())
+    }
+
+    // permissionless crank: pull a due pledge from the donor's wSOL account and record it
+    pub fn execute_pledge(ctx: Context<ExecutePledgeCtx>, cid: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let pledge = &mut ctx.accounts.pledge;
+        let transaction = &mut ctx.accounts.transaction;
+        let donor = &ctx.accounts.donor;
+        let caller = &ctx.accounts.caller;
+        let state = &ctx.accounts.program_state;
+        let now = Clock::get()?.unix_timestamp as u64;
+        let amount = pledge.amount_per_period;
+
+        if state.paused {
+            msg!("Program paused, pledge execution rejected");
+            return Err(ErrorCode::ProgramPaused.into());
+        }
+
+        if ctx.accounts.wsol_mint.key() != native_mint::ID {
+            msg!("Mint is not the native wSOL mint");
+            return Err(ErrorCode::InvalidWsolMint.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for pledge execution");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.frozen {
+            msg!("Campaign frozen, pledge execution rejected");
+            return Err(ErrorCode::CampaignFrozen.into());
+        }
+
+        if !campaign.active {
+            msg!("Inactive campaign for pledge execution");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        if now < campaign.start_time {
+            msg!("Campaign not started for pledge execution");
+            return Err(ErrorCode::CampaignNotStarted.into());
+        }
+
+        if campaign.deadline != 0 && now > campaign.deadline {
+            msg!("Campaign expired for pledge execution");
+            return Err(ErrorCode::CampaignExpired.into());
+        }
+
+        if !pledge.active {
+            msg!("Pledge is not active");
+            return Err(ErrorCode::PledgeInactive.into());
+        }
+
+        if now < pledge.next_due_ts {
+            msg!("Pledge not due yet");
+            return Err(ErrorCode::PledgeNotDue.into());
+        }
+
+        check_hard_cap(campaign, amount)?;
+        check_min_donation_share(campaign, amount)?;
+        check_donation_increment(campaign, amount)?;
+
+        // a campaign cooldown longer than the pledge period just pushes the pull back
+        let donor_record = &mut ctx.accounts.donor_record;
+        if donor_record.donation_count > 0
+            && now.saturating_sub(donor_record.last_donation_ts) < campaign.donation_cooldown
+        {
+            msg!("Pledge not due under the campaign cooldown");
+            return Err(ErrorCode::PledgeNotDue.into());
+        }
+        check_donor_limits(campaign, donor_record, amount, now)?;
+
+        // same restrictions as donate_wsol, this path carries no feed or allow list
+        if campaign.usd_denominated {
+            msg!("Usd campaigns need a price feed");
+            return Err(ErrorCode::InvalidPriceFeed.into());
+        }
+
+        if campaign.whitelist_enabled {
+            msg!("Whitelisted campaigns need an allowed donor account");
+            return Err(ErrorCode::DonorNotWhitelisted.into());
+        }
+
+        if campaign.auto_forward {
+            msg!("Auto-forward campaigns need the beneficiary account");
+            return Err(ErrorCode::AutoForwardEnabled.into());
+        }
+
+        let cid_bytes = cid.to_le_bytes();
+        let donor_key = donor.key();
+        let pledge_seeds: &[&[&[u8]]] = &[&[
+            b"pledge",
+            cid_bytes.as_ref(),
+            donor_key.as_ref(),
+            &[ctx.bumps.pledge],
+        ]];
+        token::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.donor_token_account.to_account_info(),
+                    mint: ctx.accounts.wsol_mint.to_account_info(),
+                    to: ctx.accounts.wsol_vault.to_account_info(),
+                    authority: pledge.to_account_info(),
+                },
+                pledge_seeds,
+            ),
+            amount,
+            ctx.accounts.wsol_mint.decimals,
+        )?;
+
+        // unwrap into the campaign and hand the vault's rent back to the caller who paid it
+        let vault_rent = ctx
+            .accounts
+            .wsol_vault
+            .to_account_info()
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(ErrorCode::TransferMismatch)?;
+        let campaign_lamports_before = campaign.to_account_info().lamports();
+
+        let campaign_seeds: &[&[&[u8]]] =
+            &[&[b"campaign", cid_bytes.as_ref(), &[ctx.bumps.campaign]]];
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.wsol_vault.to_account_info(),
+                destination: campaign.to_account_info(),
+                authority: campaign.to_account_info(),
+            },
+            campaign_seeds,
+        ))?;
+
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= vault_rent;
+        **caller.to_account_info().try_borrow_mut_lamports()? += vault_rent;
+
+        if campaign.to_account_info().lamports() != campaign_lamports_before + amount {
+            msg!("Unwrapped amount does not match the pledge");
+            return Err(ErrorCode::TransferMismatch.into());
+        }
+
+        campaign.amount_raised += amount;
+        campaign.balance += amount;
+        campaign.donors += 1;
+        record_goal_reached(campaign, now);
+        record_donation(
+            campaign,
+            donor_record,
+            &mut ctx.accounts.leaderboard,
+            donor_key,
+            amount,
+            false,
+            now,
+        );
+
+        transaction.amount = amount;
+        transaction.cid = cid;
+        transaction.owner = donor_key;
+        transaction.timestamp = now;
+        transaction.slot = Clock::get()?.slot;
+        #[cfg(feature = "receipt-hash")]
+        {
+            transaction.receipt_hash = receipt_hash(cid, &donor_key, amount, now);
+        }
+        transaction.credited = true;
+        transaction.processed = true;
+
+        // stay on the original schedule, but skip missed periods rather than back-charging
+        pledge.next_due_ts = pledge.next_due_ts.saturating_add(pledge.period_seconds);
+        if pledge.next_due_ts <= now {
+            pledge.next_due_ts = now.saturating_add(pledge.period_seconds);
+        }
+        pledge.executed_count += 1;
+
+        emit!(PledgeExecuted {
+            cid,
+            donor: donor_key,
+            amount,
+            next_due_ts: pledge.next_due_ts,
+            timestamp: now,
+        });
+
+        msg!("Pledge executed successfully");
+        Ok(())
+    }
+
+    // let a donor report a campaign, once per wallet
+    pub fn flag_campaign(ctx: Context<FlagCampaignCtx>, cid: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let flag = &mut ctx.accounts.flag;
+        let reporter = &ctx.accounts.reporter;
+        let state = &ctx.accounts.program_state;
+        let now = Clock::get()?.unix_timestamp as u64;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for flag");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        flag.cid = cid;
+        flag.reporter = reporter.key();
+        flag.timestamp = now;
+
+        campaign.flags += 1;
+
+        if campaign.flags > state.flag_threshold {
+            emit!(CampaignFlagged {
+                cid,
+                flags: campaign.flags,
+                threshold: state.flag_threshold,
+                timestamp: now,
+            });
+        }
+
+        msg!("Campaign flagged successfully");
+        Ok(())
+    }
+
+    // dismiss the flags on a reviewed campaign; existing Flag accounts still block
+    // their reporters from flagging it again
+    pub fn clear_flags(ctx: Context<ClearFlagsCtx>, cid: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let state = &ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized flag clear attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for flag clear");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        campaign.flags = 0;
+
+        msg!("Campaign flags cleared successfully");
+        Ok(())
+    }
+
+    // dry run of withdraw: same checks and fee maths, reported as an event, nothing moves
+    pub fn preview_withdrawal(
+        ctx: Context<PreviewWithdrawalCtx>,
+        cid: u64,
+        amount: u64,
+    ) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let state = &ctx.accounts.program_state;
+        let creator_profile = &ctx.accounts.creator_profile;
+
+        check_withdrawal(
+            campaign,
+            state,
+            ctx.accounts.creator.key(),
+            ctx.accounts.beneficiary.key(),
+            cid,
+            amount,
+            false,
+        )?;
+        check_withdrawal_limit(campaign, amount, Clock::get()?.unix_timestamp as u64)?;
+
+        // withdraw would record the campaign's success first, and the discount with it
+        let pending_success = !campaign.success_recorded && campaign.amount_raised >= campaign.goal;
+        let successful_campaigns = creator_profile.successful_campaigns + pending_success as u64;
+        let (platform_fee, creator_amount) =
+            withdrawal_fee(campaign, state, successful_campaigns, amount)?;
+
+        emit!(WithdrawalPreview {
+            cid,
+            requested: amount,
+            platform_fee,
+            creator_amount,
+            usable_balance: usable_balance(&campaign.to_account_info(), state)?,
+        });
+
+        msg!("Withdrawal preview emitted");
+        Ok(())
+    }
+
+    // let the creator grow their campaign to the current layout and pay for the space
+    pub fn resize_campaign(ctx: Context<ResizeCampaignCtx>, cid: u64) -> Result<()> {
+        let info = ctx.accounts.campaign.to_account_info();
+        let (campaign, _) = load_resized_campaign(
+            &info,
+            &ctx.accounts.creator.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        if campaign.creator != ctx.accounts.creator.key() {
+            msg!("Unauthorized campaign resize attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for resize");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        campaign.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!("Campaign resized successfully");
+        Ok(())
+    }
+
+    // report whether the campaign takes donations right now, and why not if it doesn't
+    pub fn donatable_status(ctx: Context<DonatableStatusCtx>, cid: u64) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let state = &ctx.accounts.program_state;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for donatable status");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        let reason = donatable_reason(campaign, state, Clock::get()?.unix_timestamp as u64);
+
+        emit!(DonatableStatus {
+            cid,
+            donatable: reason == DonatableReason::Donatable,
+            reason: reason as u8,
+        });
+
+        msg!("Campaign donatable status: {}", reason as u8);
+        Ok(())
+    }
+}
+
+// contexts
+#[derive(Accounts)]
+pub struct InitializeCtx<'info> {
+    #[account(
+        init,
+        payer = deployer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ProgramState::INIT_SPACE,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        init,
+        payer = deployer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + FeeHistory::INIT_SPACE,
+        seeds = [b"fee_history"],
+        bump
+    )]
+    pub fee_history: Account<'info, FeeHistory>,
+
+    #[account(
+        init,
+        payer = deployer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Treasury::INIT_SPACE,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub deployer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeIfNeededCtx<'info> {
+    #[account(
+        init_if_needed,
+        payer = deployer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ProgramState::INIT_SPACE,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        init_if_needed,
+        payer = deployer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + FeeHistory::INIT_SPACE,
+        seeds = [b"fee_history"],
+        bump
+    )]
+    pub fee_history: Account<'info, FeeHistory>,
+
+    #[account(
+        init_if_needed,
+        payer = deployer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Treasury::INIT_SPACE,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub deployer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCampaignCtx<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Campaign::INIT_SPACE,
+        seeds = [
+            b"campaign",
+            (program_state.campaign_count + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    /// check: this is the platform's account which must match program_state.platform_address
+    #[account(mut)]
+    pub platform_address: AccountInfo<'info>,
+
+    pub category: Option<Account<'info, Category>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + CreatorProfile::INIT_SPACE,
+        seeds = [
+            b"creator_profile",
+            creator.key().as_ref()
+        ],
+        bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    // only needed when the creator seeds the campaign, it is the campaign's first donation
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [
+            b"donor",
+            creator.key().as_ref(),
+            (program_state.campaign_count + 1).to_le_bytes().as_ref(),
+            1u64.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub seed_transaction: Option<Account<'info, Transaction>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + DonorRecord::INIT_SPACE,
+        seeds = [
+            b"donor_record",
+            (program_state.campaign_count + 1).to_le_bytes().as_ref(),
+            creator.key().as_ref()
+        ],
+        bump
+    )]
+    pub seed_donor_record: Option<Account<'info, DonorRecord>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Leaderboard::INIT_SPACE,
+        seeds = [
+            b"leaderboard",
+            (program_state.campaign_count + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub seed_leaderboard: Option<Account<'info, Leaderboard>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct UpdateCampaignCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct DeleteCampaignCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct DonateCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    // the record index is the campaign's donation count, so two donations built
+    // against the same count race for one address: the first lands and the second
+    // fails with ConstraintSeeds, and the client should refetch the campaign and retry
+    #[account(
+        init,
+        payer = donor,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [
+            b"donor",
+            donor.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            (campaign.donors + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = ANCHOR_DISCRIMINATOR_SIZE + DonorRecord::INIT_SPACE,
+        seeds = [
+            b"donor_record",
+            cid.to_le_bytes().as_ref(),
+            donor.key().as_ref()
+        ],
+        bump
+    )]
+    pub donor_record: Account<'info, DonorRecord>,
+
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Leaderboard::INIT_SPACE,
+        seeds = [
+            b"leaderboard",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"match",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub match_pool: Option<Account<'info, MatchPool>>,
+
+    /// check: pyth price update account, must match program_state.sol_usd_feed
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [
+            b"allowed",
+            cid.to_le_bytes().as_ref(),
+            donor.key().as_ref()
+        ],
+        bump
+    )]
+    pub allowed_donor: Option<Account<'info, AllowedDonor>>,
+
+    /// check: only needed for auto-forward campaigns, must match campaign.beneficiary
+    #[account(mut)]
+    pub beneficiary: Option<UncheckedAccount<'info>>,
+
+    // only needed for auto-forward campaigns, for the creator's loyalty discount
+    #[account(
+        seeds = [
+            b"creator_profile",
+            campaign.creator.as_ref()
+        ],
+        bump
+    )]
+    pub creator_profile: Option<Account<'info, CreatorProfile>>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub referral_stats: Option<Account<'info, ReferralStats>>,
+
+    /// check: only needed for tips, must match program_state.platform_address
+    #[account(mut)]
+    pub platform_address: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct WithdrawCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    // indexed by the withdrawal count, with the same retry semantics as donations
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [
+            b"withdraw",
+            creator.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            (campaign.withdrawals + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// check: this is the payout account which must match campaign.beneficiary
+    #[account(mut)]
+    pub beneficiary: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + CreatorProfile::INIT_SPACE,
+        seeds = [
+            b"creator_profile",
+            creator.key().as_ref()
+        ],
+        bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct AddMilestoneCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Milestone::INIT_SPACE,
+        seeds = [
+            b"milestone",
+            cid.to_le_bytes().as_ref(),
+            (campaign.milestone_count + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub milestone: Account<'info, Milestone>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64, index: u64)]
+pub struct ReleaseMilestoneCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"milestone",
+            cid.to_le_bytes().as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub milestone: Account<'info, Milestone>,
+
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// check: this is the payout account which must match campaign.beneficiary
+    #[account(mut)]
+    pub beneficiary: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + CreatorProfile::INIT_SPACE,
+        seeds = [
+            b"creator_profile",
+            creator.key().as_ref()
+        ],
+        bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct RecordInKindCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = donor,
+        space = ANCHOR_DISCRIMINATOR_SIZE + InKindPledge::INIT_SPACE,
+        seeds = [
+            b"inkind",
+            cid.to_le_bytes().as_ref(),
+            donor.key().as_ref(),
+            (campaign.in_kind_count + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub pledge: Account<'info, InKindPledge>,
+
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64, donor: Pubkey, index: u64)]
+pub struct ConfirmInKindCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"inkind",
+            cid.to_le_bytes().as_ref(),
+            donor.as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub pledge: Account<'info, InKindPledge>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePlatformSettingsCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_history"],
+        bump
+    )]
+    pub fee_history: Account<'info, FeeHistory>,
+}
+
+#[derive(Accounts)]
+pub struct SetProgramPausedCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[derive(Accounts)]
+pub struct SetWithdrawalsPausedCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[derive(Accounts)]
+pub struct ForceSetPlatformAddressCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[derive(Accounts)]
+pub struct SetRentBufferCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[derive(Accounts)]
+pub struct SetAmountFloorsCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[derive(Accounts)]
+pub struct SetFlagThresholdCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetCampaignVerifiedCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(mut)]
+    pub updater: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SaveAsTemplateCtx<'info> {
+    #[account(
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + CampaignTemplate::INIT_SPACE,
+        seeds = [
+            b"template",
+            creator.key().as_ref(),
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub template: Account<'info, CampaignTemplate>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64, index: u64)]
+pub struct RefundCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"donor",
+            donor.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    /// check: the donation owner, bound to the transaction through its seeds
+    #[account(mut)]
+    pub donor: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(mut)]
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct RefundManyCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct RefundableAmountCtx<'info> {
+    #[account(
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    /// check: only used to match the owner of the passed donation records
+    pub donor: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct UpdateBeneficiaryCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetLeaderboardIncrementCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct ExpireCampaignCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64, index: u64)]
+pub struct CloseTransactionCtx<'info> {
+    #[account(
+        mut,
+        close = donor,
+        seeds = [
+            b"donor",
+            donor.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    #[account(mut)]
+    pub donor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FeeAtCtx<'info> {
+    #[account(
+        seeds = [b"fee_history"],
+        bump
+    )]
+    pub fee_history: Account<'info, FeeHistory>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetCampaignFeeCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(mut)]
+    pub updater: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct AddCategoryCtx<'info> {
+    #[account(
+        init,
+        payer = updater,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Category::INIT_SPACE,
+        seeds = [
+            b"category",
+            id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub category: Account<'info, Category>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(mut)]
+    pub updater: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetWithdrawalLimitCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetMaxPerDonorCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetPerkThresholdsCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetMinDonationBpsCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetDonationIncrementCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetSweepOnCompleteCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetFreezeOnGoalCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct WithdrawToVaultCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + VestingVault::INIT_SPACE,
+        seeds = [
+            b"vault",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub vault: Account<'info, VestingVault>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + CreatorProfile::INIT_SPACE,
+        seeds = [
+            b"creator_profile",
+            creator.key().as_ref()
+        ],
+        bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct ClaimVestedCtx<'info> {
+    #[account(
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"vault",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub vault: Account<'info, VestingVault>,
+
+    /// check: this is the payout account which must match campaign.beneficiary
+    #[account(mut)]
+    pub beneficiary: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct FundMatchPoolCtx<'info> {
+    #[account(
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        space = ANCHOR_DISCRIMINATOR_SIZE + MatchPool::INIT_SPACE,
+        seeds = [
+            b"match",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub match_pool: Account<'info, MatchPool>,
+
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetMatchTiersCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"match",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub match_pool: Account<'info, MatchPool>,
+
+    pub sponsor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct RespondToReportCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxGoalCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct AppendUpdateCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Update::INIT_SPACE,
+        seeds = [
+            b"update",
+            cid.to_le_bytes().as_ref(),
+            (campaign.update_count + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub update: Account<'info, Update>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetAuthorityCannotCreateCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct ReactivateCampaignCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64, threshold: u64)]
+pub struct SubscribeThresholdCtx<'info> {
+    #[account(
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = subscriber,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ThresholdSubscription::INIT_SPACE,
+        seeds = [
+            b"threshold",
+            cid.to_le_bytes().as_ref(),
+            subscriber.key().as_ref(),
+            threshold.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub subscription: Account<'info, ThresholdSubscription>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPriceFeedCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetUsdGoalCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct DistributeMatchCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"match",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub match_pool: Account<'info, MatchPool>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrerCtx<'info> {
+    #[account(
+        init,
+        payer = referrer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ReferralStats::INIT_SPACE,
+        seeds = [
+            b"referral",
+            referrer.key().as_ref()
+        ],
+        bump
+    )]
+    pub referral_stats: Account<'info, ReferralStats>,
+
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct ExtendDeadlineCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DonateBatchCtx<'info> {
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct ComputeProgressCtx<'info> {
+    #[account(
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeBoundsCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateFeeToBpsCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_history"],
+        bump
+    )]
+    pub fee_history: Account<'info, FeeHistory>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct CloseCampaignCtx<'info> {
+    #[account(
+        mut,
+        close = creator,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"creator_profile",
+            creator.key().as_ref()
+        ],
+        bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    // only needed to hand leftover match money back on a campaign that refunded its donors
+    #[account(
+        seeds = [
+            b"match",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub match_pool: Option<Account<'info, MatchPool>>,
+
+    /// check: must be the match pool's sponsor, checked in the handler
+    #[account(mut)]
+    pub sponsor: Option<AccountInfo<'info>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct SetWhitelistCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64, donor: Pubkey)]
+pub struct AddAllowedDonorCtx<'info> {
+    #[account(
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + AllowedDonor::INIT_SPACE,
+        seeds = [
+            b"allowed",
+            cid.to_le_bytes().as_ref(),
+            donor.as_ref()
+        ],
+        bump
+    )]
+    pub allowed_donor: Account<'info, AllowedDonor>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasuryCtx<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(mut)]
+    pub updater: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct AdminDeactivateCampaignCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(mut)]
+    pub updater: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct MigrateCampaignCtx<'info> {
+    /// check: old layouts cannot deserialize, so the handler grows and reads it itself
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct MintCompletionReceiptCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [
+            b"receipt_mint",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump,
+        mint::decimals = 0,
+        mint::authority = campaign
+    )]
+    pub receipt_mint: Account<'info, Mint>,
+
+    /// check: any wallet the creator picks to receive the receipt
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = recipient
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct CheckPerkTierCtx<'info> {
+    #[account(
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [
+            b"donor_record",
+            cid.to_le_bytes().as_ref(),
+            donor.key().as_ref()
+        ],
+        bump
+    )]
+    pub donor_record: Account<'info, DonorRecord>,
+
+    pub donor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct FinalizeCampaignCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    // recorded in the creator's withdrawal ledger, paid for by the caller
+    #[account(
+        init,
+        payer = caller,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [
+            b"withdraw",
+            campaign.creator.as_ref(),
+            cid.to_le_bytes().as_ref(),
+            (campaign.withdrawals + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// check: this is the payout account which must match campaign.beneficiary
+    #[account(mut)]
+    pub beneficiary: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"creator_profile",
+            campaign.creator.as_ref()
+        ],
+        bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct DonateWsolCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = donor,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [
+            b"donor",
+            donor.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            (campaign.donors + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, Transaction>,
 
-        if !(1..=15).contains(&new_platform_fee) {
-            msg!("Invalid platform fee update attempt");
-            return Err(ErrorCode::InvalidPlatformFee.into());
-        }
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = ANCHOR_DISCRIMINATOR_SIZE + DonorRecord::INIT_SPACE,
+        seeds = [
+            b"donor_record",
+            cid.to_le_bytes().as_ref(),
+            donor.key().as_ref()
+        ],
+        bump
+    )]
+    pub donor_record: Account<'info, DonorRecord>,
 
-        state.platform_fee = new_platform_fee;
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Leaderboard::INIT_SPACE,
+        seeds = [
+            b"leaderboard",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
 
-        msg!("Platform settings updated successfully");
-        Ok(())
-    }
-}
+    #[account(
+        mut,
+        token::mint = wsol_mint,
+        token::authority = donor
+    )]
+    pub donor_token_account: Account<'info, TokenAccount>,
 
-// contexts
-#[derive(Accounts)]
-pub struct InitializeCtx<'info> {
+    // lives only for the duration of the instruction
     #[account(
         init,
-        payer = deployer,
-        space = ANCHOR_DISCRIMINATOR_SIZE + ProgramState::INIT_SPACE,
+        payer = donor,
+        seeds = [
+            b"wsol_vault",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump,
+        token::mint = wsol_mint,
+        token::authority = campaign
+    )]
+    pub wsol_vault: Account<'info, TokenAccount>,
+
+    pub wsol_mint: Account<'info, Mint>,
+
+    #[account(
         seeds = [b"program_state"],
         bump
     )]
     pub program_state: Account<'info, ProgramState>,
 
     #[account(mut)]
-    pub deployer: Signer<'info>,
+    pub donor: Signer<'info>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CreateCampaignCtx<'info> {
-    #[account(mut)]
-    pub program_state: Account<'info, ProgramState>,
-
+#[instruction(cid: u64)]
+pub struct UnfreezeCampaignCtx<'info> {
     #[account(
-        init,
-        payer = creator,
-        space = ANCHOR_DISCRIMINATOR_SIZE + Campaign::INIT_SPACE,
+        mut,
         seeds = [
             b"campaign",
-            (program_state.campaign_count + 1).to_le_bytes().as_ref()
+            cid.to_le_bytes().as_ref()
         ],
         bump
     )]
     pub campaign: Account<'info, Campaign>,
 
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
     #[account(mut)]
-    pub creator: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    pub updater: Signer<'info>,
 }
 
 #[derive(Accounts)]
 #[instruction(cid: u64)]
-pub struct UpdateCampaignCtx<'info> {
+pub struct ReconcileDonorsCtx<'info> {
     #[account(
         mut,
         seeds = [
@@ -417,16 +7398,20 @@ pub struct UpdateCampaignCtx<'info> {
     )]
     pub campaign: Account<'info, Campaign>,
 
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
     #[account(mut)]
-    pub creator: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    pub updater: Signer<'info>,
 }
 
 #[derive(Accounts)]
 #[instruction(cid: u64)]
-pub struct DeleteCampaignCtx<'info> {
+pub struct CreatePledgeCtx<'info> {
     #[account(
-        mut,
         seeds = [
             b"campaign",
             cid.to_le_bytes().as_ref()
@@ -435,14 +7420,52 @@ pub struct DeleteCampaignCtx<'info> {
     )]
     pub campaign: Account<'info, Campaign>,
 
+    // reused after a cancel, so the same donor can pledge again
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Pledge::INIT_SPACE,
+        seeds = [
+            b"pledge",
+            cid.to_le_bytes().as_ref(),
+            donor.key().as_ref()
+        ],
+        bump
+    )]
+    pub pledge: Account<'info, Pledge>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub donor: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 #[instruction(cid: u64)]
-pub struct DonateCtx<'info> {
+pub struct CancelPledgeCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"pledge",
+            cid.to_le_bytes().as_ref(),
+            donor.key().as_ref()
+        ],
+        bump
+    )]
+    pub pledge: Account<'info, Pledge>,
+
+    #[account(mut)]
+    pub donor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct ExecutePledgeCtx<'info> {
     #[account(
         mut,
         seeds = [
@@ -453,9 +7476,20 @@ pub struct DonateCtx<'info> {
     )]
     pub campaign: Account<'info, Campaign>,
 
+    #[account(
+        mut,
+        seeds = [
+            b"pledge",
+            cid.to_le_bytes().as_ref(),
+            donor.key().as_ref()
+        ],
+        bump
+    )]
+    pub pledge: Account<'info, Pledge>,
+
     #[account(
         init,
-        payer = donor,
+        payer = caller,
         space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
         seeds = [
             b"donor",
@@ -467,14 +7501,72 @@ pub struct DonateCtx<'info> {
     )]
     pub transaction: Account<'info, Transaction>,
 
+    #[account(
+        init_if_needed,
+        payer = caller,
+        space = ANCHOR_DISCRIMINATOR_SIZE + DonorRecord::INIT_SPACE,
+        seeds = [
+            b"donor_record",
+            cid.to_le_bytes().as_ref(),
+            donor.key().as_ref()
+        ],
+        bump
+    )]
+    pub donor_record: Account<'info, DonorRecord>,
+
+    #[account(
+        init_if_needed,
+        payer = caller,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Leaderboard::INIT_SPACE,
+        seeds = [
+            b"leaderboard",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    /// check: only used to derive the pledge and transaction seeds, never signs
+    pub donor: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = wsol_mint,
+        token::authority = donor
+    )]
+    pub donor_token_account: Account<'info, TokenAccount>,
+
+    // lives only for the duration of the instruction
+    #[account(
+        init,
+        payer = caller,
+        seeds = [
+            b"wsol_vault",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump,
+        token::mint = wsol_mint,
+        token::authority = campaign
+    )]
+    pub wsol_vault: Account<'info, TokenAccount>,
+
+    pub wsol_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
     #[account(mut)]
-    pub donor: Signer<'info>,
+    pub caller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 #[instruction(cid: u64)]
-pub struct WithdrawCtx<'info> {
+pub struct FlagCampaignCtx<'info> {
     #[account(
         mut,
         seeds = [
@@ -485,26 +7577,111 @@ pub struct WithdrawCtx<'info> {
     )]
     pub campaign: Account<'info, Campaign>,
 
+    // only wallets that have donated can report
+    #[account(
+        seeds = [
+            b"donor_record",
+            cid.to_le_bytes().as_ref(),
+            reporter.key().as_ref()
+        ],
+        bump
+    )]
+    pub donor_record: Account<'info, DonorRecord>,
+
+    // init fails if this reporter already flagged the campaign
     #[account(
         init,
-        payer = creator,
-        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        payer = reporter,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Flag::INIT_SPACE,
         seeds = [
-            b"withdraw",
-            creator.key().as_ref(),
+            b"flag",
             cid.to_le_bytes().as_ref(),
-            (campaign.withdrawals + 1).to_le_bytes().as_ref()
+            reporter.key().as_ref()
         ],
         bump
     )]
-    pub transaction: Account<'info, Transaction>,
+    pub flag: Account<'info, Flag>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
 
     #[account(mut)]
+    pub reporter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct ClearFlagsCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
     pub program_state: Account<'info, ProgramState>,
 
-    /// check: this is the platform's account which must match program_state.platform_address
     #[account(mut)]
-    pub platform_address: AccountInfo<'info>,
+    pub updater: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct PreviewWithdrawalCtx<'info> {
+    #[account(
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// check: only compared against campaign.beneficiary, nothing is paid out
+    pub beneficiary: AccountInfo<'info>,
+
+    #[account(
+        seeds = [
+            b"creator_profile",
+            creator.key().as_ref()
+        ],
+        bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct ResizeCampaignCtx<'info> {
+    /// check: old layouts cannot deserialize, so the handler grows and reads it itself
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub creator: Signer<'info>,
@@ -512,12 +7689,18 @@ pub struct WithdrawCtx<'info> {
 }
 
 #[derive(Accounts)]
-pub struct UpdatePlatformSettingsCtx<'info> {
-    #[account(mut)]
-    pub updater: Signer<'info>,
+#[instruction(cid: u64)]
+pub struct DonatableStatusCtx<'info> {
+    #[account(
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
 
     #[account(
-        mut,
         seeds = [b"program_state"],
         bump
     )]
@@ -1,6 +1,7 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { BankrunProvider, startAnchor } from "anchor-bankrun";
+import { Clock } from "solana-bankrun";
 import {
   Keypair,
   PublicKey,
@@ -10,11 +11,24 @@ import {
 } from "@solana/web3.js";
 import { Crowdfunding } from "../target/types/crowdfunding";
 import { expect, it, beforeAll, describe } from "@jest/globals";
+import { keccak_256 } from "@noble/hashes/sha3";
+import {
+  AccountLayout,
+  NATIVE_MINT,
+  createApproveInstruction,
+  createAssociatedTokenAccountInstruction,
+  createSyncNativeInstruction,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
 
 const crowdfundingAddress = new PublicKey(
   "CeS7WEPrgnfvgLrVPw3BmTDkt9hz6Cu9oUb1ZPjCMymm"
 );
 const IDL = require("../target/idl/crowdfunding.json");
+// builds without the receipt-hash feature leave the field out of Transaction
+const receiptHashEnabled = IDL.types
+  .find((t: any) => t.name === "Transaction")
+  .type.fields.some((f: any) => f.name === "receipt_hash");
 
 describe("crowdfunding", () => {
   let context: any;
@@ -29,6 +43,7 @@ describe("crowdfunding", () => {
   let withdrawlPDAUnauthorized:PublicKey;
 
   let programStatePda: PublicKey;
+  let treasuryPda: PublicKey;
   let campaignPda: PublicKey;
 
   const campaignId = new anchor.BN(1);
@@ -37,6 +52,272 @@ describe("crowdfunding", () => {
     "A campaign to protect endangered whale species in our oceans.";
   const campaignImageUrl = "https://example.com/bimal-image.jpg";
   const campaignGoal = new anchor.BN(10 * LAMPORTS_PER_SOL); // 10 SOL goal
+  const campaignDuration = 24 * 60 * 60; // shortest deadline the program allows
+
+  // helpers shared by the feature suites below
+  const fundAccount = async (pubkey: PublicKey, sol: number) => {
+    const transferTransaction = new Transaction().add(
+      SystemProgram.transfer({
+        fromPubkey: provider.wallet.publicKey,
+        toPubkey: pubkey,
+        lamports: sol * LAMPORTS_PER_SOL,
+      })
+    );
+    await provider.sendAndConfirm!(transferTransaction);
+  };
+
+  const newFundedKeypair = async (sol = 50) => {
+    const keypair = Keypair.generate();
+    await fundAccount(keypair.publicKey, sol);
+    return keypair;
+  };
+
+  const findCampaignPda = (cid: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), cid.toArrayLike(Buffer, "le", 8)],
+      crowdfundingAddress
+    )[0];
+
+  const findCategoryPda = (id: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("category"), id.toArrayLike(Buffer, "le", 8)],
+      crowdfundingAddress
+    )[0];
+
+  const findDonationPda = (
+    donor: PublicKey,
+    cid: anchor.BN,
+    index: anchor.BN
+  ) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("donor"),
+        donor.toBuffer(),
+        cid.toArrayLike(Buffer, "le", 8),
+        index.toArrayLike(Buffer, "le", 8),
+      ],
+      crowdfundingAddress
+    )[0];
+
+  const findWithdrawalPda = (
+    creator: PublicKey,
+    cid: anchor.BN,
+    index: anchor.BN
+  ) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("withdraw"),
+        creator.toBuffer(),
+        cid.toArrayLike(Buffer, "le", 8),
+        index.toArrayLike(Buffer, "le", 8),
+      ],
+      crowdfundingAddress
+    )[0];
+
+  const findSeedDonorRecordPda = (cid: anchor.BN, creator: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("donor_record"),
+        cid.toArrayLike(Buffer, "le", 8),
+        creator.toBuffer(),
+      ],
+      crowdfundingAddress
+    )[0];
+
+  const findSeedLeaderboardPda = (cid: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("leaderboard"), cid.toArrayLike(Buffer, "le", 8)],
+      crowdfundingAddress
+    )[0];
+
+  const createCampaign = async (
+    owner: Keypair,
+    {
+      goal = campaignGoal,
+      startTime = new anchor.BN(0),
+      deadline = new anchor.BN(0),
+      beneficiary = null as PublicKey | null,
+      splits = [] as { recipient: PublicKey; bps: number }[],
+      categoryId = new anchor.BN(0),
+      tags = [] as string[],
+      donationCooldown = new anchor.BN(0),
+      lockUntilGoal = false,
+      softCap = new anchor.BN(0),
+      hardCap = new anchor.BN(0),
+      requireSoftCap = false,
+      displayDecimals = null as number | null,
+      currencySymbol = null as string | null,
+      autoForward = false,
+      mediaUrls = [] as string[],
+      creatorSeedAmount = new anchor.BN(0),
+    } = {}
+  ) => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const cid = programState.campaignCount.add(new anchor.BN(1));
+
+    await crowdfundingProgram.methods
+      .createCampaign(
+        campaignTitle,
+        campaignDescription,
+        campaignImageUrl,
+        goal,
+        startTime,
+        deadline,
+        beneficiary,
+        splits,
+        categoryId,
+        tags,
+        donationCooldown,
+        lockUntilGoal,
+        softCap,
+        hardCap,
+        requireSoftCap,
+        displayDecimals,
+        currencySymbol,
+        autoForward,
+        mediaUrls,
+        creatorSeedAmount
+      )
+      .accounts({
+        creator: owner.publicKey,
+        campaign: findCampaignPda(cid),
+        programState: programStatePda,
+        platformAddress: deployer.publicKey,
+        category: categoryId.isZero() ? null : findCategoryPda(categoryId),
+        seedTransaction: creatorSeedAmount.isZero()
+          ? null
+          : findDonationPda(owner.publicKey, cid, new anchor.BN(1)),
+        seedDonorRecord: creatorSeedAmount.isZero()
+          ? null
+          : findSeedDonorRecordPda(cid, owner.publicKey),
+        seedLeaderboard: creatorSeedAmount.isZero()
+          ? null
+          : findSeedLeaderboardPda(cid),
+      })
+      .signers([owner])
+      .rpc();
+
+    return cid;
+  };
+
+  const donate = async (
+    donor: Keypair,
+    cid: anchor.BN,
+    amount: anchor.BN,
+    {
+      matchPool = null as PublicKey | null,
+      priceFeed = null as PublicKey | null,
+      referrer = null as PublicKey | null,
+      tip = new anchor.BN(0),
+      allowedDonor = null as PublicKey | null,
+      anonymous = false,
+      beneficiary = null as PublicKey | null,
+    } = {}
+  ) => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    const transaction = findDonationPda(
+      donor.publicKey,
+      cid,
+      campaign.donors.add(new anchor.BN(1))
+    );
+
+    await crowdfundingProgram.methods
+      .donate(cid, amount, referrer, tip, anonymous)
+      .accounts({
+        donor: donor.publicKey,
+        transaction,
+        matchPool,
+        priceFeed,
+        referralStats: referrer
+          ? PublicKey.findProgramAddressSync(
+              [Buffer.from("referral"), referrer.toBuffer()],
+              crowdfundingAddress
+            )[0]
+          : null,
+        platformAddress: tip.isZero() ? null : deployer.publicKey,
+        allowedDonor,
+        beneficiary,
+        creatorProfile: beneficiary
+          ? PublicKey.findProgramAddressSync(
+              [Buffer.from("creator_profile"), campaign.creator.toBuffer()],
+              crowdfundingAddress
+            )[0]
+          : null,
+      })
+      .signers([donor])
+      .rpc();
+
+    return transaction;
+  };
+
+  const withdraw = async (
+    owner: Keypair,
+    cid: anchor.BN,
+    amount: anchor.BN
+  ) => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    const transaction = findWithdrawalPda(
+      owner.publicKey,
+      cid,
+      campaign.withdrawals.add(new anchor.BN(1))
+    );
+
+    await crowdfundingProgram.methods
+      .withdraw(cid, amount)
+      .accounts({
+        creator: owner.publicKey,
+        transaction,
+        programState: programStatePda,
+        beneficiary: campaign.beneficiary,
+      })
+      .remainingAccounts(
+        campaign.splits.map((split) => ({
+          pubkey: split.recipient,
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
+      .signers([owner])
+      .rpc();
+
+    return transaction;
+  };
+
+  const currentTime = async () => {
+    const clock = await context.banksClient.getClock();
+    return Number(clock.unixTimestamp);
+  };
+
+  // advance a slot as well so retried transactions get a fresh blockhash
+  const warpTo = async (unixTimestamp: number) => {
+    const { slot } = await context.banksClient.getClock();
+    context.warpToSlot(slot + BigInt(1));
+    const clock = await context.banksClient.getClock();
+    context.setClock(
+      new Clock(
+        clock.slot,
+        clock.epochStartTimestamp,
+        clock.epoch,
+        clock.leaderScheduleEpoch,
+        BigInt(unixTimestamp)
+      )
+    );
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+      expect(true).toBe(false); // Should not reach here
+    } catch (error: any) {
+      expect(error.error.errorCode.code).toBe(code);
+    }
+  };
 
   beforeAll(async () => {
     context = await startAnchor(
@@ -79,6 +360,11 @@ describe("crowdfunding", () => {
       crowdfundingAddress
     );
 
+    [treasuryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury")],
+      crowdfundingAddress
+    );
+
     [campaignPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("campaign"), campaignId.toArrayLike(Buffer, "le", 8)],
       crowdfundingAddress
@@ -108,7 +394,10 @@ describe("crowdfunding", () => {
 
       expect(programState.initialized).toBe(true);
       expect(programState.campaignCount.toNumber()).toBe(0);
-      expect(programState.platformFee.toNumber()).toBe(5);
+      expect(programState.platformFeeBps.toNumber()).toBe(500);
+      expect(programState.createFee.toNumber()).toBe(0);
+      expect(programState.maxGoal.toNumber()).toBe(0);
+      expect(programState.authorityCannotCreate).toBe(false);
       expect(programState.platformAddress.toBase58()).toEqual(
         deployer.publicKey.toBase58()
       );
@@ -119,6 +408,27 @@ describe("crowdfunding", () => {
       console.log("Deployer final balance:", finalDeployerBalance);
       expect(finalDeployerBalance).toBeLessThan(initialDeployerBalance);
     });
+
+    it("should treat initialize_if_needed as a no-op once initialized", async () => {
+      // distinct signers keep bankrun from deduplicating the two transactions
+      for (const caller of [deployer, await newFundedKeypair()]) {
+        await crowdfundingProgram.methods
+          .initializeIfNeeded()
+          .accounts({
+            deployer: caller.publicKey,
+          })
+          .signers([caller])
+          .rpc();
+      }
+
+      const programState = await crowdfundingProgram.account.programState.fetch(
+        programStatePda
+      );
+      expect(programState.initialized).toBe(true);
+      expect(programState.platformAddress.toBase58()).toEqual(
+        deployer.publicKey.toBase58()
+      );
+    });
   });
 
   describe("Create Campaign", () => {
@@ -132,12 +442,33 @@ describe("crowdfunding", () => {
           campaignTitle,
           campaignDescription,
           campaignImageUrl,
-          campaignGoal
+          campaignGoal,
+          new anchor.BN(0),
+          new anchor.BN(0),
+          null,
+          [],
+          new anchor.BN(0),
+          [],
+          new anchor.BN(0),
+          false,
+          new anchor.BN(0),
+          new anchor.BN(0),
+          false,
+          null,
+          null,
+          false,
+          [],
+          new anchor.BN(0)
         )
         .accounts({
           creator: creator.publicKey,
           campaign: campaignPda,
           programState: programStatePda,
+          platformAddress: deployer.publicKey,
+          category: null,
+          seedTransaction: null,
+          seedDonorRecord: null,
+          seedLeaderboard: null,
         })
         .signers([creator])
         .rpc();
@@ -186,12 +517,33 @@ describe("crowdfunding", () => {
             "Valid Title",
             campaignDescription,
             campaignImageUrl,
+            new anchor.BN(0),
+            new anchor.BN(0),
+            new anchor.BN(0),
+            null,
+            [],
+            new anchor.BN(0),
+            [],
+            new anchor.BN(0),
+            false,
+            new anchor.BN(0),
+            new anchor.BN(0),
+            false,
+            null,
+            null,
+            false,
+            [],
             new anchor.BN(0)
           )
           .accounts({
             creator: creator.publicKey,
             campaign: invalidCampaignPda,
             programState: programStatePda,
+            platformAddress: deployer.publicKey,
+            category: null,
+            seedTransaction: null,
+            seedDonorRecord: null,
+            seedLeaderboard: null,
           })
           .signers([creator])
           .rpc();
@@ -217,12 +569,33 @@ describe("crowdfunding", () => {
             longTitle,
             campaignDescription,
             campaignImageUrl,
-            campaignGoal
+            campaignGoal,
+            new anchor.BN(0),
+            new anchor.BN(0),
+            null,
+            [],
+            new anchor.BN(0),
+            [],
+            new anchor.BN(0),
+            false,
+            new anchor.BN(0),
+            new anchor.BN(0),
+            false,
+            null,
+            null,
+            false,
+            [],
+            new anchor.BN(0)
           )
           .accounts({
             creator: creator.publicKey,
             campaign: campaignPdaforlongtitle,
             programState: programStatePda,
+            platformAddress: deployer.publicKey,
+            category: null,
+            seedTransaction: null,
+            seedDonorRecord: null,
+            seedLeaderboard: null,
           })
           .signers([creator])
           .rpc();
@@ -250,7 +623,9 @@ describe("crowdfunding", () => {
           newTitle,
           newDescription,
           newImageUrl,
-          newGoal
+          newGoal,
+          new anchor.BN(0),
+          []
         )
         .accounts({
           creator: creator.publicKey,
@@ -289,7 +664,9 @@ describe("crowdfunding", () => {
             "Unauthorized Update",
             campaignDescription,
             campaignImageUrl,
-            campaignGoal
+            campaignGoal,
+            new anchor.BN(0),
+            []
           )
           .accounts({
             creator: donor1.publicKey, // Wrong creator
@@ -333,10 +710,17 @@ describe("crowdfunding", () => {
 
       //sending the create pda on chain
       await crowdfundingProgram.methods
-        .donate(campaignId, donationAmount1)
+        .donate(campaignId, donationAmount1, null, new anchor.BN(0), false)
         .accounts({
           donor: donor1.publicKey,
           transaction: transactionPda,
+          matchPool: null,
+          priceFeed: null,
+          referralStats: null,
+          platformAddress: null,
+          allowedDonor: null,
+          beneficiary: null,
+          creatorProfile: null,
         })
         .signers([donor1])
         .rpc();
@@ -387,10 +771,17 @@ describe("crowdfunding", () => {
         crowdfundingAddress
       );
       await crowdfundingProgram.methods
-        .donate(campaignId, donationAmount2)
+        .donate(campaignId, donationAmount2, null, new anchor.BN(0), false)
         .accounts({
           donor: donor2.publicKey,
           transaction: transactionPda1,
+          matchPool: null,
+          priceFeed: null,
+          referralStats: null,
+          platformAddress: null,
+          allowedDonor: null,
+          beneficiary: null,
+          creatorProfile: null,
         })
         .signers([donor2])
         .rpc();
@@ -425,10 +816,17 @@ describe("crowdfunding", () => {
       try {
         await crowdfundingProgram.methods
           //less than 1 sol cant be donated
-          .donate(campaignId, new anchor.BN(0.5 * LAMPORTS_PER_SOL))
+          .donate(campaignId, new anchor.BN(0.5 * LAMPORTS_PER_SOL), null, new anchor.BN(0), false)
           .accounts({
             donor: donor1.publicKey,
             transaction: invalidTransactionPda,
+            matchPool: null,
+            priceFeed: null,
+            referralStats: null,
+            platformAddress: null,
+            allowedDonor: null,
+            beneficiary: null,
+            creatorProfile: null,
           })
           .signers([donor1])
           .rpc();
@@ -450,7 +848,7 @@ describe("crowdfunding", () => {
 
         const initialCreatorBalance = await context.banksClient.getBalance(creator.publicKey);
         const initialCampaignBalance = await context.banksClient.getBalance(campaignPda);
-        const initialPlatformBalance = await context.banksClient.getBalance(deployer.publicKey);
+        const initialPlatformBalance = await context.banksClient.getBalance(treasuryPda);
 
         const [withdrawlPDA] = PublicKey.findProgramAddressSync(
             [
@@ -468,7 +866,7 @@ describe("crowdfunding", () => {
                 creator: creator.publicKey,
                 transaction: withdrawlPDA,
                 programState: programStatePda,
-                platformAddress: deployer.publicKey,
+                beneficiary: creator.publicKey,
             })
             .signers([creator])
             .rpc();
@@ -483,7 +881,7 @@ describe("crowdfunding", () => {
         // Verify balances changed
         const finalCreatorBalance = await context.banksClient.getBalance(creator.publicKey);
         const finalCampaignBalance = await context.banksClient.getBalance(campaignPda);
-        const finalPlatformBalance = await context.banksClient.getBalance(deployer.publicKey);
+        const finalPlatformBalance = await context.banksClient.getBalance(treasuryPda);
 
         expect(finalCreatorBalance).toBeGreaterThan(initialCreatorBalance);
         expect(finalCampaignBalance).toBeLessThan(initialCampaignBalance);
@@ -510,7 +908,7 @@ describe("crowdfunding", () => {
                   creator: donor1.publicKey, // Wrong creator
                   transaction: withdrawlPDAUnauthorized,
                   programState: programStatePda,
-                  platformAddress: deployer.publicKey,
+                  beneficiary: creator.publicKey,
               })
               .signers([donor1])
               .rpc();
@@ -540,7 +938,7 @@ describe("crowdfunding", () => {
                   creator: creator.publicKey,
                   transaction: withdrawlInsufficientAmountPDA,
                   programState: programStatePda,
-                  platformAddress: deployer.publicKey,
+                  beneficiary: creator.publicKey,
               })
               .signers([creator])
               .rpc();
@@ -550,13 +948,20 @@ describe("crowdfunding", () => {
           expect(error.error.errorCode.code).toBe("InvalidWithdrawalAmount");
       }
   });
+
+  it('should fail withdrawal exceeding the campaign balance', async () => {
+    await expectError(
+      withdraw(creator, campaignId, new anchor.BN(10 * LAMPORTS_PER_SOL)),
+      "WithdrawalExceedsBalance"
+    );
+  });
 });
 describe("Update Platform Settings", () => {
   it('should allow deployer to update platform fee', async () => {
-      const newPlatformFee = new anchor.BN(10); // 10 basis points (0.1%)
+      const newPlatformFee = new anchor.BN(1_000); // 1_000 basis points (10%)
 
       await crowdfundingProgram.methods
-          .updatePlatformSettings(newPlatformFee)
+          .updatePlatformSettings(newPlatformFee, new anchor.BN(0))
           .accounts({
               updater: deployer.publicKey,
           })
@@ -564,13 +969,13 @@ describe("Update Platform Settings", () => {
           .rpc();
 
       const programState = await crowdfundingProgram.account.programState.fetch(programStatePda);
-      expect(programState.platformFee.toNumber()).toBe(newPlatformFee.toNumber());
+      expect(programState.platformFeeBps.toNumber()).toBe(newPlatformFee.toNumber());
   });
 
   it('should fail to update platform settings with unauthorized user', async () => {
     try {
         await crowdfundingProgram.methods
-            .updatePlatformSettings(new anchor.BN(15))
+            .updatePlatformSettings(new anchor.BN(1_500), new anchor.BN(0))
             .accounts({
                 updater: creator.publicKey, // Wrong updater
             })
@@ -627,4 +1032,7072 @@ describe("Delete Campaign", () => {
       }
   });
 });
+
+describe("Milestones", () => {
+  const findMilestonePda = (cid: anchor.BN, index: number) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("milestone"),
+        cid.toArrayLike(Buffer, "le", 8),
+        new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+      ],
+      crowdfundingAddress
+    )[0];
+
+  const releaseMilestone = (owner: Keypair, cid: anchor.BN, index: number) =>
+    crowdfundingProgram.methods
+      .releaseMilestone(cid, new anchor.BN(index))
+      .accounts({
+        creator: owner.publicKey,
+        milestone: findMilestonePda(cid, index),
+        programState: programStatePda,
+        beneficiary: owner.publicKey,
+      })
+      .signers([owner])
+      .rpc();
+
+  let milestoneCreator: Keypair;
+  let milestoneDonor: Keypair;
+  let cid: anchor.BN;
+  const milestoneAmounts = [3, 3, 4].map(
+    (sol) => new anchor.BN(sol * LAMPORTS_PER_SOL)
+  );
+
+  beforeAll(async () => {
+    milestoneCreator = await newFundedKeypair();
+    milestoneDonor = await newFundedKeypair();
+    cid = await createCampaign(milestoneCreator);
+  });
+
+  it("should add three milestones before donations", async () => {
+    for (const [i, amount] of milestoneAmounts.entries()) {
+      await crowdfundingProgram.methods
+        .addMilestone(cid, amount, `Milestone ${i + 1}`)
+        .accounts({
+          creator: milestoneCreator.publicKey,
+          milestone: findMilestonePda(cid, i + 1),
+        })
+        .signers([milestoneCreator])
+        .rpc();
+    }
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.milestoneCount.toNumber()).toBe(3);
+    expect(campaign.milestoneTotal.toNumber()).toBe(campaignGoal.toNumber());
+
+    const milestone = await crowdfundingProgram.account.milestone.fetch(
+      findMilestonePda(cid, 2)
+    );
+    expect(milestone.index.toNumber()).toBe(2);
+    expect(milestone.description).toBe("Milestone 2");
+    expect(milestone.released).toBe(false);
+  });
+
+  it("should reject milestones beyond the goal", async () => {
+    await expectError(
+      crowdfundingProgram.methods
+        .addMilestone(cid, new anchor.BN(LAMPORTS_PER_SOL), "Too much")
+        .accounts({
+          creator: milestoneCreator.publicKey,
+          milestone: findMilestonePda(cid, 4),
+        })
+        .signers([milestoneCreator])
+        .rpc(),
+      "MilestoneExceedsGoal"
+    );
+  });
+
+  it("should reject plain withdrawals on a milestone campaign", async () => {
+    await donate(milestoneDonor, cid, campaignGoal);
+
+    await expectError(
+      withdraw(milestoneCreator, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "MilestoneReleaseRequired"
+    );
+  });
+
+  it("should reject out-of-order milestone release", async () => {
+    await expectError(
+      releaseMilestone(milestoneCreator, cid, 2),
+      "MilestoneOutOfOrder"
+    );
+  });
+
+  it("should release milestones sequentially", async () => {
+    let expectedBalance = campaignGoal;
+
+    for (const [i, amount] of milestoneAmounts.entries()) {
+      const initialCreatorBalance = await context.banksClient.getBalance(
+        milestoneCreator.publicKey
+      );
+
+      await releaseMilestone(milestoneCreator, cid, i + 1);
+
+      const milestone = await crowdfundingProgram.account.milestone.fetch(
+        findMilestonePda(cid, i + 1)
+      );
+      expect(milestone.released).toBe(true);
+
+      expectedBalance = expectedBalance.sub(amount);
+      const campaign = await crowdfundingProgram.account.campaign.fetch(
+        findCampaignPda(cid)
+      );
+      expect(campaign.milestonesReleased.toNumber()).toBe(i + 1);
+      expect(campaign.balance.toNumber()).toBe(expectedBalance.toNumber());
+
+      const finalCreatorBalance = await context.banksClient.getBalance(
+        milestoneCreator.publicKey
+      );
+      expect(finalCreatorBalance).toBeGreaterThan(initialCreatorBalance);
+    }
+  });
+
+  it("should not release a milestone twice", async () => {
+    await expectError(
+      releaseMilestone(milestoneCreator, cid, 1),
+      "MilestoneAlreadyReleased"
+    );
+  });
+
+  it("should hold milestone releases to the withdrawal limit", async () => {
+    const limitedCid = await createCampaign(milestoneCreator);
+    const amount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+    for (const index of [1, 2]) {
+      await crowdfundingProgram.methods
+        .addMilestone(limitedCid, amount, `Milestone ${index}`)
+        .accounts({
+          creator: milestoneCreator.publicKey,
+          milestone: findMilestonePda(limitedCid, index),
+        })
+        .signers([milestoneCreator])
+        .rpc();
+    }
+    await crowdfundingProgram.methods
+      .setWithdrawalLimit(
+        limitedCid,
+        new anchor.BN(24 * 60 * 60),
+        new anchor.BN(3 * LAMPORTS_PER_SOL)
+      )
+      .accounts({
+        creator: milestoneCreator.publicKey,
+      })
+      .signers([milestoneCreator])
+      .rpc();
+    await donate(milestoneDonor, limitedCid, amount.muln(2));
+
+    await releaseMilestone(milestoneCreator, limitedCid, 1);
+    await expectError(
+      releaseMilestone(milestoneCreator, limitedCid, 2),
+      "WithdrawalLimitExceeded"
+    );
+  });
+});
+
+describe("Program Pause", () => {
+  let pauseCreator: Keypair;
+  let pauseDonor: Keypair;
+  let cid: anchor.BN;
+  const donationAmount = new anchor.BN(LAMPORTS_PER_SOL);
+
+  const setProgramPaused = (updater: Keypair, paused: boolean) =>
+    crowdfundingProgram.methods
+      .setProgramPaused(paused)
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  beforeAll(async () => {
+    pauseCreator = await newFundedKeypair();
+    pauseDonor = await newFundedKeypair();
+    cid = await createCampaign(pauseCreator);
+  });
+
+  it("should reject pausing by a non-platform signer", async () => {
+    await expectError(setProgramPaused(pauseCreator, true), "Unauthorized");
+  });
+
+  it("should reject donations and withdrawals while paused", async () => {
+    await setProgramPaused(deployer, true);
+
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    expect(programState.paused).toBe(true);
+
+    await expectError(donate(pauseDonor, cid, donationAmount), "ProgramPaused");
+    await expectError(
+      withdraw(pauseCreator, cid, donationAmount),
+      "ProgramPaused"
+    );
+    await expectError(createCampaign(pauseCreator), "ProgramPaused");
+  });
+
+  it("should accept donations again after unpausing", async () => {
+    await setProgramPaused(deployer, false);
+
+    await donate(pauseDonor, cid, donationAmount);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(donationAmount.toNumber());
+  });
+});
+
+describe("In-Kind Pledges", () => {
+  let inKindCreator: Keypair;
+  let inKindDonor: Keypair;
+  let cid: anchor.BN;
+  const estimatedValue = new anchor.BN(3 * LAMPORTS_PER_SOL);
+
+  const findInKindPda = (cid: anchor.BN, donor: PublicKey, index: number) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("inkind"),
+        cid.toArrayLike(Buffer, "le", 8),
+        donor.toBuffer(),
+        new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+      ],
+      crowdfundingAddress
+    )[0];
+
+  beforeAll(async () => {
+    inKindCreator = await newFundedKeypair();
+    inKindDonor = await newFundedKeypair();
+    cid = await createCampaign(inKindCreator);
+  });
+
+  it("should record an in-kind pledge", async () => {
+    await crowdfundingProgram.methods
+      .recordInKind(cid, "Twenty boxes of medical supplies", estimatedValue)
+      .accounts({
+        donor: inKindDonor.publicKey,
+        pledge: findInKindPda(cid, inKindDonor.publicKey, 1),
+      })
+      .signers([inKindDonor])
+      .rpc();
+
+    const pledge = await crowdfundingProgram.account.inKindPledge.fetch(
+      findInKindPda(cid, inKindDonor.publicKey, 1)
+    );
+    expect(pledge.donor.toBase58()).toBe(inKindDonor.publicKey.toBase58());
+    expect(pledge.estimatedValue.toNumber()).toBe(estimatedValue.toNumber());
+    expect(pledge.confirmed).toBe(false);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.inKindCount.toNumber()).toBe(1);
+    expect(campaign.inKindValue.toNumber()).toBe(0);
+    expect(campaign.amountRaised.toNumber()).toBe(0);
+  });
+
+  it("should reject confirmation by a non-creator", async () => {
+    await expectError(
+      crowdfundingProgram.methods
+        .confirmInKind(cid, inKindDonor.publicKey, new anchor.BN(1))
+        .accounts({
+          creator: inKindDonor.publicKey,
+        })
+        .signers([inKindDonor])
+        .rpc(),
+      "Unauthorized"
+    );
+  });
+
+  it("should confirm an in-kind pledge without touching the balance", async () => {
+    await crowdfundingProgram.methods
+      .confirmInKind(cid, inKindDonor.publicKey, new anchor.BN(1))
+      .accounts({
+        creator: inKindCreator.publicKey,
+      })
+      .signers([inKindCreator])
+      .rpc();
+
+    const pledge = await crowdfundingProgram.account.inKindPledge.fetch(
+      findInKindPda(cid, inKindDonor.publicKey, 1)
+    );
+    expect(pledge.confirmed).toBe(true);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.inKindValue.toNumber()).toBe(estimatedValue.toNumber());
+    expect(campaign.amountRaised.toNumber()).toBe(0);
+    expect(campaign.balance.toNumber()).toBe(0);
+  });
+});
+
+describe("Campaign Verification", () => {
+  let verifyCreator: Keypair;
+  let cid: anchor.BN;
+
+  const setCampaignVerified = (updater: Keypair, verified: boolean) =>
+    crowdfundingProgram.methods
+      .setCampaignVerified(cid, verified)
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  beforeAll(async () => {
+    verifyCreator = await newFundedKeypair();
+    cid = await createCampaign(verifyCreator);
+  });
+
+  it("should create campaigns unverified", async () => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.verified).toBe(false);
+  });
+
+  it("should reject verification by a random signer", async () => {
+    await expectError(setCampaignVerified(verifyCreator, true), "Unauthorized");
+  });
+
+  it("should allow the platform address to verify a campaign", async () => {
+    const { events } = await crowdfundingProgram.methods
+      .setCampaignVerified(cid, true)
+      .accounts({
+        updater: deployer.publicKey,
+      })
+      .signers([deployer])
+      .simulate();
+    const event = events.find(
+      (e) => e.name === "campaignVerificationChanged"
+    );
+    expect(event?.data.cid.toNumber()).toBe(cid.toNumber());
+    expect(event?.data.verified).toBe(true);
+
+    await setCampaignVerified(deployer, true);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.verified).toBe(true);
+  });
+
+  it("should allow the platform address to revoke verification", async () => {
+    await setCampaignVerified(deployer, false);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.verified).toBe(false);
+  });
+});
+
+describe("Campaign Templates", () => {
+  let templateCreator: Keypair;
+  let templateDonor: Keypair;
+  let cid: anchor.BN;
+  const templateGoal = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+  const findTemplatePda = (creator: PublicKey, cid: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("template"),
+        creator.toBuffer(),
+        cid.toArrayLike(Buffer, "le", 8),
+      ],
+      crowdfundingAddress
+    )[0];
+
+  const saveAsTemplate = (owner: Keypair) =>
+    crowdfundingProgram.methods
+      .saveAsTemplate(cid)
+      .accounts({
+        creator: owner.publicKey,
+      })
+      .signers([owner])
+      .rpc();
+
+  beforeAll(async () => {
+    templateCreator = await newFundedKeypair();
+    templateDonor = await newFundedKeypair();
+    cid = await createCampaign(templateCreator, { goal: templateGoal });
+  });
+
+  it("should reject saving an incomplete campaign", async () => {
+    await expectError(saveAsTemplate(templateCreator), "CampaignNotCompleted");
+  });
+
+  it("should reject saving by a non-creator", async () => {
+    await donate(templateDonor, cid, templateGoal);
+
+    await expectError(saveAsTemplate(templateDonor), "Unauthorized");
+  });
+
+  it("should save a completed campaign and instantiate a new one from it", async () => {
+    await saveAsTemplate(templateCreator);
+
+    const template = await crowdfundingProgram.account.campaignTemplate.fetch(
+      findTemplatePda(templateCreator.publicKey, cid)
+    );
+    expect(template.creator.toBase58()).toBe(
+      templateCreator.publicKey.toBase58()
+    );
+    expect(template.sourceCid.toNumber()).toBe(cid.toNumber());
+    expect(template.title).toBe(campaignTitle);
+    expect(template.goal.toNumber()).toBe(templateGoal.toNumber());
+
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const nextCid = programState.campaignCount.add(new anchor.BN(1));
+
+    await crowdfundingProgram.methods
+      .createCampaign(
+        template.title,
+        template.description,
+        template.imageUrl,
+        template.goal,
+        new anchor.BN(0),
+        new anchor.BN(0),
+        null,
+        [],
+        new anchor.BN(0),
+        [],
+        new anchor.BN(0),
+        false,
+        new anchor.BN(0),
+        new anchor.BN(0),
+        false,
+        null,
+        null,
+        false,
+        [],
+        new anchor.BN(0)
+      )
+      .accounts({
+        creator: templateCreator.publicKey,
+        campaign: findCampaignPda(nextCid),
+        programState: programStatePda,
+        platformAddress: deployer.publicKey,
+        category: null,
+        seedTransaction: null,
+        seedDonorRecord: null,
+        seedLeaderboard: null,
+      })
+      .signers([templateCreator])
+      .rpc();
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(nextCid)
+    );
+    expect(campaign.title).toBe(template.title);
+    expect(campaign.description).toBe(template.description);
+    expect(campaign.imageUrl).toBe(template.imageUrl);
+    expect(campaign.goal.toNumber()).toBe(template.goal.toNumber());
+    expect(campaign.amountRaised.toNumber()).toBe(0);
+  });
+});
+
+describe("Refund Queue", () => {
+  let refundCreator: Keypair;
+  let refundDonors: Keypair[];
+  let cid: anchor.BN;
+  const donationAmount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+  const refund = (donor: Keypair, index: number) =>
+    crowdfundingProgram.methods
+      .refund(cid, new anchor.BN(index))
+      .accounts({
+        donor: donor.publicKey,
+        caller: refundCreator.publicKey,
+      })
+      .signers([refundCreator])
+      .rpc();
+
+  beforeAll(async () => {
+    refundCreator = await newFundedKeypair();
+    refundDonors = [
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+    ];
+    cid = await createCampaign(refundCreator);
+
+    for (const donor of refundDonors) {
+      await donate(donor, cid, donationAmount);
+    }
+  });
+
+  it("should reject refunds while the campaign is active", async () => {
+    await expectError(refund(refundDonors[0], 1), "RefundNotAvailable");
+  });
+
+  it("should refund the earliest donor first", async () => {
+    // leave only 3 SOL behind so not every donor can be refunded
+    await withdraw(refundCreator, cid, new anchor.BN(3 * LAMPORTS_PER_SOL));
+    await crowdfundingProgram.methods
+      .deleteCampaign(cid)
+      .accounts({
+        creator: refundCreator.publicKey,
+      })
+      .signers([refundCreator])
+      .rpc();
+
+    await expectError(refund(refundDonors[1], 2), "RefundOutOfOrder");
+
+    const initialDonorBalance = await context.banksClient.getBalance(
+      refundDonors[0].publicKey
+    );
+
+    await refund(refundDonors[0], 1);
+
+    const finalDonorBalance = await context.banksClient.getBalance(
+      refundDonors[0].publicKey
+    );
+    expect(Number(finalDonorBalance) - Number(initialDonorBalance)).toBe(
+      donationAmount.toNumber()
+    );
+
+    const transaction = await crowdfundingProgram.account.transaction.fetch(
+      findDonationPda(refundDonors[0].publicKey, cid, new anchor.BN(1))
+    );
+    expect(transaction.credited).toBe(false);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.refundCursor.toNumber()).toBe(1);
+    expect(campaign.balance.toNumber()).toBe(LAMPORTS_PER_SOL);
+  });
+
+  it("should stop refunding once funds run out", async () => {
+    await expectError(refund(refundDonors[1], 2), "InsufficientFund");
+    await expectError(refund(refundDonors[2], 3), "RefundOutOfOrder");
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.refundCursor.toNumber()).toBe(1);
+  });
+});
+
+describe("Campaign Creation Fee", () => {
+  let feeCreator: Keypair;
+  const createFee = new anchor.BN(LAMPORTS_PER_SOL / 2);
+
+  const setCreateFee = (fee: anchor.BN) =>
+    crowdfundingProgram.methods
+      .updatePlatformSettings(new anchor.BN(1_000), fee)
+      .accounts({
+        updater: deployer.publicKey,
+      })
+      .signers([deployer])
+      .rpc();
+
+  beforeAll(async () => {
+    feeCreator = await newFundedKeypair();
+  });
+
+  it("should charge the creation fee to the platform address", async () => {
+    await setCreateFee(createFee);
+
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    expect(programState.createFee.toNumber()).toBe(createFee.toNumber());
+
+    const initialPlatformBalance = await context.banksClient.getBalance(
+      deployer.publicKey
+    );
+    const initialCreatorBalance = await context.banksClient.getBalance(
+      feeCreator.publicKey
+    );
+
+    const cid = await createCampaign(feeCreator);
+
+    const finalPlatformBalance = await context.banksClient.getBalance(
+      deployer.publicKey
+    );
+    const finalCreatorBalance = await context.banksClient.getBalance(
+      feeCreator.publicKey
+    );
+    const rent = await context.banksClient.getBalance(findCampaignPda(cid));
+
+    expect(Number(finalPlatformBalance) - Number(initialPlatformBalance)).toBe(
+      createFee.toNumber()
+    );
+    expect(Number(initialCreatorBalance) - Number(finalCreatorBalance)).toBe(
+      createFee.toNumber() + Number(rent)
+    );
+  });
+
+  it("should charge nothing extra with a zero fee", async () => {
+    await setCreateFee(new anchor.BN(0));
+
+    const initialPlatformBalance = await context.banksClient.getBalance(
+      deployer.publicKey
+    );
+
+    await createCampaign(feeCreator);
+
+    const finalPlatformBalance = await context.banksClient.getBalance(
+      deployer.publicKey
+    );
+    expect(finalPlatformBalance).toBe(initialPlatformBalance);
+  });
+});
+
+describe("Scheduled Start", () => {
+  let scheduledCreator: Keypair;
+  let scheduledDonor: Keypair;
+  let cid: anchor.BN;
+  let startTime: number;
+  const donationAmount = new anchor.BN(LAMPORTS_PER_SOL);
+
+  const updateStartTime = (newStartTime: number) =>
+    crowdfundingProgram.methods
+      .updateCampaign(
+        cid,
+        campaignTitle,
+        campaignDescription,
+        campaignImageUrl,
+        campaignGoal,
+        new anchor.BN(newStartTime),
+        []
+      )
+      .accounts({
+        creator: scheduledCreator.publicKey,
+      })
+      .signers([scheduledCreator])
+      .rpc();
+
+  beforeAll(async () => {
+    scheduledCreator = await newFundedKeypair();
+    scheduledDonor = await newFundedKeypair();
+    startTime = (await currentTime()) + 1_000;
+    cid = await createCampaign(scheduledCreator, {
+      startTime: new anchor.BN(startTime),
+    });
+  });
+
+  it("should reject donations before the start time", async () => {
+    await expectError(
+      donate(scheduledDonor, cid, donationAmount),
+      "CampaignNotStarted"
+    );
+  });
+
+  it("should allow moving the start time before the campaign starts", async () => {
+    startTime += 500;
+    await updateStartTime(startTime);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.startTime.toNumber()).toBe(startTime);
+  });
+
+  it("should accept donations after the start time", async () => {
+    await warpTo(startTime + 1);
+
+    await donate(scheduledDonor, cid, donationAmount);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(donationAmount.toNumber());
+  });
+
+  it("should reject changing the start time once started", async () => {
+    await expectError(
+      updateStartTime(startTime + 10_000),
+      "CampaignAlreadyStarted"
+    );
+  });
+});
+
+describe("Refundable Amount", () => {
+  let refundableCreator: Keypair;
+  let refundableDonor: Keypair;
+  let cid: anchor.BN;
+  let donationPdas: PublicKey[];
+  const donationAmounts = [2, 3].map(
+    (sol) => new anchor.BN(sol * LAMPORTS_PER_SOL)
+  );
+
+  const refundableAmount = () =>
+    crowdfundingProgram.methods
+      .refundableAmount(
+        cid,
+        donationPdas.map((_, i) => new anchor.BN(i + 1))
+      )
+      .accounts({
+        donor: refundableDonor.publicKey,
+      })
+      .remainingAccounts(
+        donationPdas.map((pubkey) => ({
+          pubkey,
+          isWritable: false,
+          isSigner: false,
+        }))
+      )
+      .view();
+
+  beforeAll(async () => {
+    refundableCreator = await newFundedKeypair();
+    refundableDonor = await newFundedKeypair();
+    cid = await createCampaign(refundableCreator);
+
+    donationPdas = [];
+    for (const amount of donationAmounts) {
+      donationPdas.push(await donate(refundableDonor, cid, amount));
+    }
+  });
+
+  it("should report nothing refundable while the campaign is active", async () => {
+    const refundable = await refundableAmount();
+    expect(refundable.toNumber()).toBe(0);
+  });
+
+  it("should match the amount actually refunded", async () => {
+    await crowdfundingProgram.methods
+      .deleteCampaign(cid)
+      .accounts({
+        creator: refundableCreator.publicKey,
+      })
+      .signers([refundableCreator])
+      .rpc();
+
+    const refundable = await refundableAmount();
+    expect(refundable.toNumber()).toBe(
+      donationAmounts[0].add(donationAmounts[1]).toNumber()
+    );
+
+    const initialDonorBalance = await context.banksClient.getBalance(
+      refundableDonor.publicKey
+    );
+
+    for (const index of [1, 2]) {
+      await crowdfundingProgram.methods
+        .refund(cid, new anchor.BN(index))
+        .accounts({
+          donor: refundableDonor.publicKey,
+          caller: refundableCreator.publicKey,
+        })
+        .signers([refundableCreator])
+        .rpc();
+
+      // donations behind the refund cursor no longer count
+      if (index === 1) {
+        const left = await refundableAmount();
+        expect(left.toNumber()).toBe(donationAmounts[1].toNumber());
+      }
+    }
+
+    const finalDonorBalance = await context.banksClient.getBalance(
+      refundableDonor.publicKey
+    );
+    expect(Number(finalDonorBalance) - Number(initialDonorBalance)).toBe(
+      refundable.toNumber()
+    );
+
+    const remaining = await refundableAmount();
+    expect(remaining.toNumber()).toBe(0);
+  });
+});
+
+describe("Beneficiary", () => {
+  let beneficiaryCreator: Keypair;
+  let beneficiaryDonor: Keypair;
+  let beneficiary: Keypair;
+  let cid: anchor.BN;
+  const withdrawAmount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+  beforeAll(async () => {
+    beneficiaryCreator = await newFundedKeypair();
+    beneficiaryDonor = await newFundedKeypair();
+    beneficiary = await newFundedKeypair(1);
+    cid = await createCampaign(beneficiaryCreator, {
+      beneficiary: beneficiary.publicKey,
+    });
+    await donate(beneficiaryDonor, cid, new anchor.BN(5 * LAMPORTS_PER_SOL));
+  });
+
+  it("should default the beneficiary to the creator", async () => {
+    const defaultCid = await createCampaign(beneficiaryCreator);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(defaultCid)
+    );
+    expect(campaign.beneficiary.toBase58()).toBe(
+      beneficiaryCreator.publicKey.toBase58()
+    );
+  });
+
+  it("should pay the net amount to the beneficiary", async () => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const initialBeneficiaryBalance = await context.banksClient.getBalance(
+      beneficiary.publicKey
+    );
+    const initialCreatorBalance = await context.banksClient.getBalance(
+      beneficiaryCreator.publicKey
+    );
+
+    const transaction = await withdraw(beneficiaryCreator, cid, withdrawAmount);
+
+    const platformFee = withdrawAmount
+      .mul(programState.platformFeeBps)
+      .div(new anchor.BN(10_000));
+    const finalBeneficiaryBalance = await context.banksClient.getBalance(
+      beneficiary.publicKey
+    );
+    expect(
+      Number(finalBeneficiaryBalance) - Number(initialBeneficiaryBalance)
+    ).toBe(withdrawAmount.sub(platformFee).toNumber());
+
+    // the creator only pays rent for the withdrawal record
+    const recordRent = await context.banksClient.getBalance(transaction);
+    const finalCreatorBalance = await context.banksClient.getBalance(
+      beneficiaryCreator.publicKey
+    );
+    expect(Number(initialCreatorBalance) - Number(finalCreatorBalance)).toBe(
+      Number(recordRent)
+    );
+  });
+
+  it("should reject a withdrawal to the wrong beneficiary", async () => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+
+    await expectError(
+      crowdfundingProgram.methods
+        .withdraw(cid, withdrawAmount)
+        .accounts({
+          creator: beneficiaryCreator.publicKey,
+          transaction: findWithdrawalPda(
+            beneficiaryCreator.publicKey,
+            cid,
+            campaign.withdrawals.add(new anchor.BN(1))
+          ),
+          programState: programStatePda,
+          beneficiary: beneficiaryCreator.publicKey,
+        })
+        .signers([beneficiaryCreator])
+        .rpc(),
+      "InvalidBeneficiary"
+    );
+  });
+
+  it("should only let the creator update the beneficiary", async () => {
+    await expectError(
+      crowdfundingProgram.methods
+        .updateBeneficiary(cid, beneficiaryDonor.publicKey)
+        .accounts({
+          creator: beneficiaryDonor.publicKey,
+        })
+        .signers([beneficiaryDonor])
+        .rpc(),
+      "Unauthorized"
+    );
+
+    await crowdfundingProgram.methods
+      .updateBeneficiary(cid, beneficiaryCreator.publicKey)
+      .accounts({
+        creator: beneficiaryCreator.publicKey,
+      })
+      .signers([beneficiaryCreator])
+      .rpc();
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.beneficiary.toBase58()).toBe(
+      beneficiaryCreator.publicKey.toBase58()
+    );
+  });
+});
+
+describe("Leaderboard Increment", () => {
+  let leaderboardCreator: Keypair;
+  let leaderboardDonors: Keypair[];
+  let cid: anchor.BN;
+
+  const fetchCampaign = () =>
+    crowdfundingProgram.account.campaign.fetch(findCampaignPda(cid));
+
+  beforeAll(async () => {
+    leaderboardCreator = await newFundedKeypair();
+    leaderboardDonors = [
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+    ];
+    cid = await createCampaign(leaderboardCreator);
+
+    await crowdfundingProgram.methods
+      .setLeaderboardIncrement(cid, new anchor.BN(LAMPORTS_PER_SOL))
+      .accounts({
+        creator: leaderboardCreator.publicKey,
+      })
+      .signers([leaderboardCreator])
+      .rpc();
+  });
+
+  it("should give the first donation the lead", async () => {
+    await donate(leaderboardDonors[0], cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+
+    const campaign = await fetchCampaign();
+    expect(campaign.leaderboardMinIncrement.toNumber()).toBe(LAMPORTS_PER_SOL);
+    expect(campaign.topDonor.toBase58()).toBe(
+      leaderboardDonors[0].publicKey.toBase58()
+    );
+    expect(campaign.largestDonation.toNumber()).toBe(2 * LAMPORTS_PER_SOL);
+  });
+
+  it("should not hand the lead to a donation below the increment", async () => {
+    await donate(
+      leaderboardDonors[1],
+      cid,
+      new anchor.BN(2.5 * LAMPORTS_PER_SOL)
+    );
+
+    const campaign = await fetchCampaign();
+    expect(campaign.topDonor.toBase58()).toBe(
+      leaderboardDonors[0].publicKey.toBase58()
+    );
+    expect(campaign.largestDonation.toNumber()).toBe(2 * LAMPORTS_PER_SOL);
+  });
+
+  it("should hand the lead to a donation beyond the increment", async () => {
+    await donate(
+      leaderboardDonors[2],
+      cid,
+      new anchor.BN(3.5 * LAMPORTS_PER_SOL)
+    );
+
+    const campaign = await fetchCampaign();
+    expect(campaign.topDonor.toBase58()).toBe(
+      leaderboardDonors[2].publicKey.toBase58()
+    );
+    expect(campaign.largestDonation.toNumber()).toBe(3.5 * LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Beneficiary Splits", () => {
+  let splitCreator: Keypair;
+  let splitDonor: Keypair;
+  let partnerA: Keypair;
+  let partnerB: Keypair;
+  let cid: anchor.BN;
+  const withdrawAmount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+  beforeAll(async () => {
+    splitCreator = await newFundedKeypair();
+    splitDonor = await newFundedKeypair();
+    partnerA = await newFundedKeypair(1);
+    partnerB = await newFundedKeypair(1);
+  });
+
+  it("should reject splits that do not sum to 10000 bps", async () => {
+    await expectError(
+      createCampaign(splitCreator, {
+        splits: [
+          { recipient: partnerA.publicKey, bps: 5_000 },
+          { recipient: partnerB.publicKey, bps: 4_000 },
+        ],
+      }),
+      "InvalidSplitConfig"
+    );
+  });
+
+  it("should distribute a withdrawal 60/40 across partners", async () => {
+    cid = await createCampaign(splitCreator, {
+      splits: [
+        { recipient: partnerA.publicKey, bps: 6_000 },
+        { recipient: partnerB.publicKey, bps: 4_000 },
+      ],
+    });
+    await donate(splitDonor, cid, new anchor.BN(5 * LAMPORTS_PER_SOL));
+
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const initialBalanceA = await context.banksClient.getBalance(
+      partnerA.publicKey
+    );
+    const initialBalanceB = await context.banksClient.getBalance(
+      partnerB.publicKey
+    );
+
+    await withdraw(splitCreator, cid, withdrawAmount);
+
+    const netAmount = withdrawAmount.sub(
+      withdrawAmount.mul(programState.platformFeeBps).div(new anchor.BN(10_000))
+    );
+    const shareA = netAmount.mul(new anchor.BN(6_000)).div(new anchor.BN(10_000));
+    const shareB = netAmount.sub(shareA);
+
+    const finalBalanceA = await context.banksClient.getBalance(
+      partnerA.publicKey
+    );
+    const finalBalanceB = await context.banksClient.getBalance(
+      partnerB.publicKey
+    );
+    expect(Number(finalBalanceA) - Number(initialBalanceA)).toBe(
+      shareA.toNumber()
+    );
+    expect(Number(finalBalanceB) - Number(initialBalanceB)).toBe(
+      shareB.toNumber()
+    );
+  });
+
+  it("should reject a withdrawal missing the split recipients", async () => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+
+    await expectError(
+      crowdfundingProgram.methods
+        .withdraw(cid, withdrawAmount)
+        .accounts({
+          creator: splitCreator.publicKey,
+          transaction: findWithdrawalPda(
+            splitCreator.publicKey,
+            cid,
+            campaign.withdrawals.add(new anchor.BN(1))
+          ),
+          programState: programStatePda,
+          beneficiary: campaign.beneficiary,
+        })
+        .signers([splitCreator])
+        .rpc(),
+      "InvalidSplitConfig"
+    );
+  });
+});
+
+describe("Campaign Expiry", () => {
+  let expiryCreator: Keypair;
+  let expiryDonor: Keypair;
+  let thirdParty: Keypair;
+  let cid: anchor.BN;
+  let completedCid: anchor.BN;
+  let deadline: number;
+
+  const expireCampaign = (cid: anchor.BN) =>
+    crowdfundingProgram.methods
+      .expireCampaign(cid)
+      .accounts({
+        caller: thirdParty.publicKey,
+      })
+      .signers([thirdParty])
+      .rpc();
+
+  beforeAll(async () => {
+    expiryCreator = await newFundedKeypair();
+    expiryDonor = await newFundedKeypair();
+    thirdParty = await newFundedKeypair(1);
+    deadline = (await currentTime()) + campaignDuration;
+    cid = await createCampaign(expiryCreator, {
+      deadline: new anchor.BN(deadline),
+    });
+    completedCid = await createCampaign(expiryCreator, {
+      goal: new anchor.BN(LAMPORTS_PER_SOL),
+      deadline: new anchor.BN(deadline),
+    });
+    await donate(expiryDonor, completedCid, new anchor.BN(LAMPORTS_PER_SOL));
+  });
+
+  it("should reject a deadline that is not after the start time", async () => {
+    const now = await currentTime();
+    await expectError(
+      createCampaign(expiryCreator, {
+        startTime: new anchor.BN(now + 500),
+        deadline: new anchor.BN(now + 100),
+      }),
+      "InvalidDeadline"
+    );
+  });
+
+  it("should not let anyone expire a campaign before its deadline", async () => {
+    await expectError(expireCampaign(cid), "CampaignNotExpired");
+  });
+
+  it("should let a third party expire a campaign after its deadline", async () => {
+    await warpTo(deadline + 1);
+
+    await expectError(
+      donate(expiryDonor, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "CampaignExpired"
+    );
+
+    await expireCampaign(cid);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.active).toBe(false);
+    expect(campaign.status).toEqual({ expired: {} });
+    expect(campaign.refundsOpen).toBe(true);
+  });
+
+  it("should mark a funded campaign as completed on expiry", async () => {
+    await expireCampaign(completedCid);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(completedCid)
+    );
+    expect(campaign.active).toBe(false);
+    expect(campaign.status).toEqual({ completed: {} });
+    expect(campaign.refundsOpen).toBe(false);
+  });
+
+  it("should not refund donors of a completed campaign", async () => {
+    await expectError(
+      crowdfundingProgram.methods
+        .refund(completedCid, new anchor.BN(1))
+        .accounts({
+          donor: expiryDonor.publicKey,
+          caller: thirdParty.publicKey,
+        })
+        .signers([thirdParty])
+        .rpc(),
+      "RefundNotAvailable"
+    );
+  });
+
+  it("should not expire a campaign twice", async () => {
+    await expectError(expireCampaign(cid), "InactiveCampaign");
+  });
+});
+
+describe("Fixed Beneficiary Shares", () => {
+  let coFounderCreator: Keypair;
+  let coFounderDonor: Keypair;
+  let coFounders: Keypair[];
+  let cid: anchor.BN;
+  const withdrawAmount = new anchor.BN(3 * LAMPORTS_PER_SOL);
+
+  beforeAll(async () => {
+    coFounderCreator = await newFundedKeypair();
+    coFounderDonor = await newFundedKeypair();
+    coFounders = [await newFundedKeypair(1), await newFundedKeypair(1)];
+    cid = await createCampaign(coFounderCreator, {
+      splits: [
+        { recipient: coFounders[0].publicKey, bps: 7_000 },
+        { recipient: coFounders[1].publicKey, bps: 3_000 },
+      ],
+    });
+    await donate(coFounderDonor, cid, new anchor.BN(5 * LAMPORTS_PER_SOL));
+  });
+
+  it("should lock the shares in at creation", async () => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.splits.map((split) => split.bps)).toEqual([7_000, 3_000]);
+  });
+
+  it("should pay two beneficiaries 70/30 on withdrawal", async () => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const initialBalances = await Promise.all(
+      coFounders.map((keypair) =>
+        context.banksClient.getBalance(keypair.publicKey)
+      )
+    );
+
+    await withdraw(coFounderCreator, cid, withdrawAmount);
+
+    const netAmount = withdrawAmount.sub(
+      withdrawAmount.mul(programState.platformFeeBps).div(new anchor.BN(10_000))
+    );
+    const firstShare = netAmount
+      .mul(new anchor.BN(7_000))
+      .div(new anchor.BN(10_000));
+    const expectedShares = [firstShare, netAmount.sub(firstShare)];
+
+    for (const [i, keypair] of coFounders.entries()) {
+      const finalBalance = await context.banksClient.getBalance(
+        keypair.publicKey
+      );
+      expect(Number(finalBalance) - Number(initialBalances[i])).toBe(
+        expectedShares[i].toNumber()
+      );
+    }
+  });
+});
+
+describe("Close Transaction", () => {
+  let closeCreator: Keypair;
+  let refundedDonor: Keypair;
+  let creditedDonor: Keypair;
+  let cid: anchor.BN;
+
+  const closeTransaction = (donor: Keypair, index: number) =>
+    crowdfundingProgram.methods
+      .closeTransaction(cid, new anchor.BN(index))
+      .accounts({
+        donor: donor.publicKey,
+      })
+      .signers([donor])
+      .rpc();
+
+  beforeAll(async () => {
+    closeCreator = await newFundedKeypair();
+    refundedDonor = await newFundedKeypair();
+    creditedDonor = await newFundedKeypair();
+    cid = await createCampaign(closeCreator);
+
+    await donate(refundedDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+    await donate(creditedDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    await crowdfundingProgram.methods
+      .deleteCampaign(cid)
+      .accounts({
+        creator: closeCreator.publicKey,
+      })
+      .signers([closeCreator])
+      .rpc();
+
+    await crowdfundingProgram.methods
+      .refund(cid, new anchor.BN(1))
+      .accounts({
+        donor: refundedDonor.publicKey,
+        caller: closeCreator.publicKey,
+      })
+      .signers([closeCreator])
+      .rpc();
+  });
+
+  it("should not close a donation that is still credited", async () => {
+    await expectError(
+      closeTransaction(creditedDonor, 2),
+      "DonationStillCredited"
+    );
+  });
+
+  it("should return the rent to the donor after a refund", async () => {
+    const transactionPda = findDonationPda(
+      refundedDonor.publicKey,
+      cid,
+      new anchor.BN(1)
+    );
+    const rent = await context.banksClient.getBalance(transactionPda);
+    const initialDonorBalance = await context.banksClient.getBalance(
+      refundedDonor.publicKey
+    );
+
+    await closeTransaction(refundedDonor, 1);
+
+    const finalDonorBalance = await context.banksClient.getBalance(
+      refundedDonor.publicKey
+    );
+    expect(Number(finalDonorBalance) - Number(initialDonorBalance)).toBe(
+      Number(rent)
+    );
+
+    const closedAccount = await context.banksClient.getAccount(transactionPda);
+    expect(closedAccount).toBeNull();
+  });
+});
+
+describe("Fee History", () => {
+  const setPlatformFee = (fee: number) =>
+    crowdfundingProgram.methods
+      .updatePlatformSettings(new anchor.BN(fee), new anchor.BN(0))
+      .accounts({
+        updater: deployer.publicKey,
+      })
+      .signers([deployer])
+      .rpc();
+
+  const feeAt = (ts: number) =>
+    crowdfundingProgram.methods.feeAt(new anchor.BN(ts)).view();
+
+  it("should report the fee in effect at intermediate timestamps", async () => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const originalFee = programState.platformFeeBps.toNumber();
+
+    const firstChange = (await currentTime()) + 100;
+    await warpTo(firstChange);
+    await setPlatformFee(700);
+
+    const secondChange = firstChange + 100;
+    await warpTo(secondChange);
+    await setPlatformFee(1_200);
+
+    expect((await feeAt(firstChange - 1)).toNumber()).toBe(originalFee);
+    expect((await feeAt(firstChange)).toNumber()).toBe(700);
+    expect((await feeAt(firstChange + 50)).toNumber()).toBe(700);
+    expect((await feeAt(secondChange + 1)).toNumber()).toBe(1_200);
+
+    await warpTo(secondChange + 100);
+    await setPlatformFee(originalFee);
+  });
+
+  it("should reject lookups before the recorded history", async () => {
+    await expectError(feeAt(0), "FeeHistoryUnavailable");
+  });
+});
+
+describe("Platform Fee Computation", () => {
+  let whaleCreator: Keypair;
+  let whaleDonor: Keypair;
+  let cid: anchor.BN;
+  // large enough that amount * fee overflows u64
+  const whaleAmount = new anchor.BN("1500000000000000000");
+  const whaleFee = 1_500;
+
+  const setPlatformFee = (fee: number) =>
+    crowdfundingProgram.methods
+      .updatePlatformSettings(new anchor.BN(fee), new anchor.BN(0))
+      .accounts({
+        updater: deployer.publicKey,
+      })
+      .signers([deployer])
+      .rpc();
+
+  beforeAll(async () => {
+    whaleCreator = await newFundedKeypair();
+    whaleDonor = Keypair.generate();
+    context.setAccount(whaleDonor.publicKey, {
+      lamports: 2e18,
+      data: Buffer.alloc(0),
+      owner: SystemProgram.programId,
+      executable: false,
+    });
+    cid = await createCampaign(whaleCreator, {
+      goal: new anchor.BN("2000000000000000000"),
+    });
+    await donate(whaleDonor, cid, whaleAmount);
+  });
+
+  it("should compute the fee without overflowing u64", async () => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const originalFee = programState.platformFeeBps.toNumber();
+    await setPlatformFee(whaleFee);
+
+    const initialPlatformBalance = await context.banksClient.getBalance(
+      treasuryPda
+    );
+    const initialCreatorBalance = await context.banksClient.getBalance(
+      whaleCreator.publicKey
+    );
+
+    const transaction = await withdraw(whaleCreator, cid, whaleAmount);
+
+    const expectedFee =
+      (BigInt(whaleAmount.toString()) * BigInt(whaleFee)) / BigInt(10_000);
+    const finalPlatformBalance = await context.banksClient.getBalance(
+      treasuryPda
+    );
+    expect(finalPlatformBalance - initialPlatformBalance).toBe(expectedFee);
+
+    const recordRent = await context.banksClient.getBalance(transaction);
+    const finalCreatorBalance = await context.banksClient.getBalance(
+      whaleCreator.publicKey
+    );
+    expect(finalCreatorBalance - initialCreatorBalance + recordRent).toBe(
+      BigInt(whaleAmount.toString()) - expectedFee
+    );
+
+    await setPlatformFee(originalFee);
+  });
+});
+
+describe("Campaign Fee Override", () => {
+  let partnerCreator: Keypair;
+  let partnerDonor: Keypair;
+  let partnerCid: anchor.BN;
+  let defaultCid: anchor.BN;
+  const withdrawAmount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+  const setCampaignFee = (
+    updater: Keypair,
+    cid: anchor.BN,
+    fee: anchor.BN | null
+  ) =>
+    crowdfundingProgram.methods
+      .setCampaignFee(cid, fee)
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  const platformCut = async (cid: anchor.BN) => {
+    const initialPlatformBalance = await context.banksClient.getBalance(
+      treasuryPda
+    );
+    await withdraw(partnerCreator, cid, withdrawAmount);
+    const finalPlatformBalance = await context.banksClient.getBalance(
+      treasuryPda
+    );
+    return Number(finalPlatformBalance - initialPlatformBalance);
+  };
+
+  beforeAll(async () => {
+    partnerCreator = await newFundedKeypair();
+    partnerDonor = await newFundedKeypair();
+    partnerCid = await createCampaign(partnerCreator);
+    defaultCid = await createCampaign(partnerCreator);
+    await donate(partnerDonor, partnerCid, new anchor.BN(5 * LAMPORTS_PER_SOL));
+    await donate(partnerDonor, defaultCid, new anchor.BN(5 * LAMPORTS_PER_SOL));
+  });
+
+  it("should only let the platform address override a campaign fee", async () => {
+    await expectError(
+      setCampaignFee(partnerCreator, partnerCid, new anchor.BN(200)),
+      "Unauthorized"
+    );
+    await expectError(
+      setCampaignFee(deployer, partnerCid, new anchor.BN(1_600)),
+      "InvalidPlatformFee"
+    );
+  });
+
+  it("should charge the overridden fee on withdrawal", async () => {
+    await setCampaignFee(deployer, partnerCid, new anchor.BN(200));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(partnerCid)
+    );
+    expect(campaign.feeOverride?.toNumber()).toBe(200);
+
+    expect(await platformCut(partnerCid)).toBe(
+      withdrawAmount.muln(200).divn(10_000).toNumber()
+    );
+  });
+
+  it("should charge the default fee without an override", async () => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+
+    expect(await platformCut(defaultCid)).toBe(
+      withdrawAmount.mul(programState.platformFeeBps).divn(10_000).toNumber()
+    );
+  });
+});
+
+describe("Category Registry", () => {
+  let categoryCreator: Keypair;
+  const categoryId = new anchor.BN(7);
+
+  beforeAll(async () => {
+    categoryCreator = await newFundedKeypair();
+  });
+
+  it("should only let the platform address register categories", async () => {
+    await expectError(
+      crowdfundingProgram.methods
+        .addCategory(categoryId, "Environment")
+        .accounts({
+          updater: categoryCreator.publicKey,
+        })
+        .signers([categoryCreator])
+        .rpc(),
+      "Unauthorized"
+    );
+  });
+
+  it("should create a campaign in a registered category", async () => {
+    await crowdfundingProgram.methods
+      .addCategory(categoryId, "Environment")
+      .accounts({
+        updater: deployer.publicKey,
+      })
+      .signers([deployer])
+      .rpc();
+
+    const category = await crowdfundingProgram.account.category.fetch(
+      findCategoryPda(categoryId)
+    );
+    expect(category.name).toBe("Environment");
+
+    const cid = await createCampaign(categoryCreator, { categoryId });
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.categoryId.toNumber()).toBe(categoryId.toNumber());
+  });
+
+  it("should reject an unregistered category", async () => {
+    await expectError(
+      createCampaign(categoryCreator, { categoryId: new anchor.BN(99) }),
+      "AccountNotInitialized"
+    );
+  });
+
+  it("should reject a category account that does not match the id", async () => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const cid = programState.campaignCount.add(new anchor.BN(1));
+
+    await expectError(
+      crowdfundingProgram.methods
+        .createCampaign(
+          campaignTitle,
+          campaignDescription,
+          campaignImageUrl,
+          campaignGoal,
+          new anchor.BN(0),
+          new anchor.BN(0),
+          null,
+          [],
+          new anchor.BN(99),
+          [],
+          new anchor.BN(0),
+          false,
+          new anchor.BN(0),
+          new anchor.BN(0),
+          false,
+          null,
+          null,
+          false,
+          [],
+          new anchor.BN(0)
+        )
+        .accounts({
+          creator: categoryCreator.publicKey,
+          campaign: findCampaignPda(cid),
+          programState: programStatePda,
+          platformAddress: deployer.publicKey,
+          category: findCategoryPda(categoryId),
+          seedTransaction: null,
+          seedDonorRecord: null,
+          seedLeaderboard: null,
+        })
+        .signers([categoryCreator])
+        .rpc(),
+      "InvalidCategory"
+    );
+  });
+});
+
+describe("Donor Rank", () => {
+  let rankCreator: Keypair;
+  let firstDonor: Keypair;
+  let secondDonor: Keypair;
+  let cid: anchor.BN;
+  const donationAmount = new anchor.BN(LAMPORTS_PER_SOL);
+
+  const findDonorRecordPda = (cid: anchor.BN, donor: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("donor_record"),
+        cid.toArrayLike(Buffer, "le", 8),
+        donor.toBuffer(),
+      ],
+      crowdfundingAddress
+    )[0];
+
+  // simulate first to capture the emitted event, then land the donation
+  const donateWithEvent = async (donor: Keypair) => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    const { events } = await crowdfundingProgram.methods
+      .donate(cid, donationAmount, null, new anchor.BN(0), false)
+      .accounts({
+        donor: donor.publicKey,
+        transaction: findDonationPda(
+          donor.publicKey,
+          cid,
+          campaign.donors.add(new anchor.BN(1))
+        ),
+        matchPool: null,
+        priceFeed: null,
+        referralStats: null,
+        platformAddress: null,
+        allowedDonor: null,
+        beneficiary: null,
+        creatorProfile: null,
+      })
+      .signers([donor])
+      .simulate();
+    await donate(donor, cid, donationAmount);
+
+    return events.find((e) => e.name === "donationReceived")!.data;
+  };
+
+  beforeAll(async () => {
+    rankCreator = await newFundedKeypair();
+    firstDonor = await newFundedKeypair();
+    secondDonor = await newFundedKeypair();
+    cid = await createCampaign(rankCreator);
+  });
+
+  it("should rank the first unique donor as #1", async () => {
+    const event = await donateWithEvent(firstDonor);
+    expect(event.donor.toBase58()).toBe(firstDonor.publicKey.toBase58());
+    expect(event.donorRank.toNumber()).toBe(1);
+    expect(event.firstTimeDonor).toBe(true);
+  });
+
+  it("should rank the next unique donor as #2", async () => {
+    const event = await donateWithEvent(secondDonor);
+    expect(event.donorRank.toNumber()).toBe(2);
+    expect(event.firstTimeDonor).toBe(true);
+  });
+
+  it("should keep a returning donor's rank", async () => {
+    const event = await donateWithEvent(firstDonor);
+    expect(event.donorRank.toNumber()).toBe(1);
+    expect(event.firstTimeDonor).toBe(false);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.uniqueDonors.toNumber()).toBe(2);
+    expect(campaign.donors.toNumber()).toBe(3);
+
+    const record = await crowdfundingProgram.account.donorRecord.fetch(
+      findDonorRecordPda(cid, firstDonor.publicKey)
+    );
+    expect(record.donationCount.toNumber()).toBe(2);
+    expect(record.totalContributed.toNumber()).toBe(
+      donationAmount.muln(2).toNumber()
+    );
+  });
+});
+
+describe("Campaign Tags", () => {
+  let tagCreator: Keypair;
+
+  beforeAll(async () => {
+    tagCreator = await newFundedKeypair();
+  });
+
+  it("should create a campaign with no tags", async () => {
+    const cid = await createCampaign(tagCreator);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.tags).toEqual([]);
+  });
+
+  it("should create a campaign with a single tag", async () => {
+    const cid = await createCampaign(tagCreator, { tags: ["urgent"] });
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.tags).toEqual(["urgent"]);
+  });
+
+  it("should replace tags on update", async () => {
+    const cid = await createCampaign(tagCreator, { tags: ["urgent"] });
+
+    await crowdfundingProgram.methods
+      .updateCampaign(
+        cid,
+        campaignTitle,
+        campaignDescription,
+        campaignImageUrl,
+        campaignGoal,
+        new anchor.BN(0),
+        ["verified-org", "matched"]
+      )
+      .accounts({
+        creator: tagCreator.publicKey,
+      })
+      .signers([tagCreator])
+      .rpc();
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.tags).toEqual(["verified-org", "matched"]);
+  });
+
+  it("should reject more than five tags", async () => {
+    await expectError(
+      createCampaign(tagCreator, {
+        tags: ["a", "b", "c", "d", "e", "f"],
+      }),
+      "TooManyTags"
+    );
+  });
+
+  it("should reject a tag longer than 24 characters", async () => {
+    await expectError(
+      createCampaign(tagCreator, { tags: ["x".repeat(25)] }),
+      "InvalidTagLength"
+    );
+  });
+});
+
+describe("Withdrawal Rate Limit", () => {
+  let limitCreator: Keypair;
+  let limitDonor: Keypair;
+  let cid: anchor.BN;
+  const window = 24 * 60 * 60;
+  const limit = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+  const setWithdrawalLimit = (newWindow: number, newLimit: anchor.BN) =>
+    crowdfundingProgram.methods
+      .setWithdrawalLimit(cid, new anchor.BN(newWindow), newLimit)
+      .accounts({
+        creator: limitCreator.publicKey,
+      })
+      .signers([limitCreator])
+      .rpc();
+
+  beforeAll(async () => {
+    limitCreator = await newFundedKeypair();
+    limitDonor = await newFundedKeypair();
+    cid = await createCampaign(limitCreator);
+    await donate(limitDonor, cid, new anchor.BN(5 * LAMPORTS_PER_SOL));
+
+    await setWithdrawalLimit(window, limit);
+  });
+
+  it("should allow withdrawals up to the window limit", async () => {
+    await withdraw(limitCreator, cid, limit);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.withdrawnInWindow.toNumber()).toBe(limit.toNumber());
+  });
+
+  it("should reject withdrawals beyond the window limit", async () => {
+    await expectError(
+      withdraw(limitCreator, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "WithdrawalLimitExceeded"
+    );
+  });
+
+  it("should only let the creator tighten the limit", async () => {
+    await expectError(
+      setWithdrawalLimit(window, new anchor.BN(0)),
+      "WithdrawalLimitLoosened"
+    );
+    await expectError(
+      setWithdrawalLimit(window, limit.muln(2)),
+      "WithdrawalLimitLoosened"
+    );
+    await expectError(
+      setWithdrawalLimit(window - 1, limit),
+      "WithdrawalLimitLoosened"
+    );
+
+    // re-applying the same limit keeps what was already withdrawn this window
+    await setWithdrawalLimit(window, limit);
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.withdrawnInWindow.toNumber()).toBe(limit.toNumber());
+    await expectError(
+      withdraw(limitCreator, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "WithdrawalLimitExceeded"
+    );
+  });
+
+  it("should reset the limit once the window has passed", async () => {
+    await warpTo((await currentTime()) + window);
+    await withdraw(limitCreator, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.withdrawnInWindow.toNumber()).toBe(LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Vesting Vault", () => {
+  let vaultCreator: Keypair;
+  let vaultDonor: Keypair;
+  let cid: anchor.BN;
+  const duration = 30 * 24 * 60 * 60;
+  const vaultWithdrawal = new anchor.BN(4 * LAMPORTS_PER_SOL);
+
+  const findVaultPda = (cid: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), cid.toArrayLike(Buffer, "le", 8)],
+      crowdfundingAddress
+    )[0];
+
+  const fetchVault = () =>
+    crowdfundingProgram.account.vestingVault.fetch(findVaultPda(cid));
+
+  const claimVested = () =>
+    crowdfundingProgram.methods
+      .claimVested(cid)
+      .accounts({
+        creator: vaultCreator.publicKey,
+        beneficiary: vaultCreator.publicKey,
+      })
+      .signers([vaultCreator])
+      .rpc();
+
+  beforeAll(async () => {
+    vaultCreator = await newFundedKeypair();
+    vaultDonor = await newFundedKeypair();
+    cid = await createCampaign(vaultCreator);
+    await donate(vaultDonor, cid, new anchor.BN(5 * LAMPORTS_PER_SOL));
+  });
+
+  it("should move a withdrawal into the vault minus the platform fee", async () => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+
+    await crowdfundingProgram.methods
+      .withdrawToVault(cid, vaultWithdrawal, new anchor.BN(duration))
+      .accounts({
+        creator: vaultCreator.publicKey,
+      })
+      .signers([vaultCreator])
+      .rpc();
+
+    const vault = await fetchVault();
+    const platformFee = vaultWithdrawal
+      .mul(programState.platformFeeBps)
+      .div(new anchor.BN(10_000));
+    expect(vault.totalDeposited.toNumber()).toBe(
+      vaultWithdrawal.sub(platformFee).toNumber()
+    );
+    expect(vault.duration.toNumber()).toBe(duration);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.balance.toNumber()).toBe(LAMPORTS_PER_SOL);
+  });
+
+  it("should not release anything before time has passed", async () => {
+    await expectError(claimVested(), "NothingToClaim");
+  });
+
+  it("should release half of the vault halfway through the schedule", async () => {
+    const vault = await fetchVault();
+    await warpTo(vault.startTs.toNumber() + duration / 2);
+
+    const balanceBefore = await context.banksClient.getBalance(
+      vaultCreator.publicKey
+    );
+    await claimVested();
+    const balanceAfter = await context.banksClient.getBalance(
+      vaultCreator.publicKey
+    );
+
+    const half = vault.totalDeposited.divn(2).toNumber();
+    expect((await fetchVault()).claimed.toNumber()).toBe(half);
+    expect(Number(balanceAfter - balanceBefore)).toBe(half);
+  });
+
+  it("should release the remainder once the schedule ends", async () => {
+    const vault = await fetchVault();
+    await warpTo(vault.startTs.toNumber() + duration);
+
+    await claimVested();
+
+    const claimed = await fetchVault();
+    expect(claimed.claimed.toNumber()).toBe(vault.totalDeposited.toNumber());
+  });
+
+  it("should reject shortening the vesting schedule", async () => {
+    await expectError(
+      crowdfundingProgram.methods
+        .withdrawToVault(
+          cid,
+          new anchor.BN(LAMPORTS_PER_SOL),
+          new anchor.BN(duration - 1)
+        )
+        .accounts({
+          creator: vaultCreator.publicKey,
+        })
+        .signers([vaultCreator])
+        .rpc(),
+      "InvalidVestingSchedule"
+    );
+  });
+});
+
+describe("Transaction Account Size", () => {
+  // mirrors MAX_TRANSACTION_SPACE in the program
+  const maxTransactionSpace = 160;
+
+  it("should keep donation and withdrawal records under the size ceiling", async () => {
+    const sizeCreator = await newFundedKeypair();
+    const sizeDonor = await newFundedKeypair();
+    const cid = await createCampaign(sizeCreator);
+
+    const donation = await donate(sizeDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+    const withdrawal = await withdraw(
+      sizeCreator,
+      cid,
+      new anchor.BN(LAMPORTS_PER_SOL)
+    );
+
+    for (const address of [donation, withdrawal]) {
+      const account = await context.banksClient.getAccount(address);
+      expect(account).not.toBeNull();
+      expect(account!.data.length).toBeLessThanOrEqual(maxTransactionSpace);
+    }
+  });
+
+  it("should drop the receipt hash from lean builds", async () => {
+    const sizeCreator = await newFundedKeypair();
+    const sizeDonor = await newFundedKeypair();
+    const cid = await createCampaign(sizeCreator);
+    const donation = await donate(
+      sizeDonor,
+      cid,
+      new anchor.BN(LAMPORTS_PER_SOL)
+    );
+
+    // 157 bytes with the receipt-hash feature, 32 fewer without it
+    const expectedSpace = receiptHashEnabled ? 157 : 125;
+    const account = await context.banksClient.getAccount(donation);
+    expect(account!.data.length).toBe(expectedSpace);
+  });
+});
+
+describe("Match Pool", () => {
+  let matchCreator: Keypair;
+  let sponsor: Keypair;
+  let matchDonor: Keypair;
+  let cid: anchor.BN;
+  let matchPool: PublicKey;
+  const poolFunding = new anchor.BN(3 * LAMPORTS_PER_SOL);
+
+  const fetchCampaign = () =>
+    crowdfundingProgram.account.campaign.fetch(findCampaignPda(cid));
+
+  beforeAll(async () => {
+    matchCreator = await newFundedKeypair();
+    sponsor = await newFundedKeypair();
+    matchDonor = await newFundedKeypair();
+    cid = await createCampaign(matchCreator);
+    matchPool = PublicKey.findProgramAddressSync(
+      [Buffer.from("match"), cid.toArrayLike(Buffer, "le", 8)],
+      crowdfundingAddress
+    )[0];
+
+    // 1:1 match
+    await crowdfundingProgram.methods
+      .fundMatchPool(cid, poolFunding, new anchor.BN(10_000))
+      .accounts({
+        sponsor: sponsor.publicKey,
+      })
+      .signers([sponsor])
+      .rpc();
+  });
+
+  it("should record the sponsor's pool", async () => {
+    const pool = await crowdfundingProgram.account.matchPool.fetch(matchPool);
+    expect(pool.sponsor.toBase58()).toBe(sponsor.publicKey.toBase58());
+    expect(pool.matchedRemaining.toNumber()).toBe(poolFunding.toNumber());
+    expect(pool.matchRatioBps.toNumber()).toBe(10_000);
+  });
+
+  it("should double a donation with a 1:1 match", async () => {
+    const donation = await donate(
+      matchDonor,
+      cid,
+      new anchor.BN(2 * LAMPORTS_PER_SOL),
+      { matchPool }
+    );
+
+    const campaign = await fetchCampaign();
+    expect(campaign.amountRaised.toNumber()).toBe(4 * LAMPORTS_PER_SOL);
+    expect(campaign.balance.toNumber()).toBe(4 * LAMPORTS_PER_SOL);
+
+    const transaction = await crowdfundingProgram.account.transaction.fetch(
+      donation
+    );
+    expect(transaction.matchedAmount.toNumber()).toBe(2 * LAMPORTS_PER_SOL);
+  });
+
+  it("should only match what is left in the pool", async () => {
+    await donate(matchDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL), {
+      matchPool,
+    });
+
+    const campaign = await fetchCampaign();
+    expect(campaign.amountRaised.toNumber()).toBe(7 * LAMPORTS_PER_SOL);
+
+    const pool = await crowdfundingProgram.account.matchPool.fetch(matchPool);
+    expect(pool.matchedRemaining.toNumber()).toBe(0);
+    expect(pool.totalMatched.toNumber()).toBe(poolFunding.toNumber());
+  });
+
+  it("should stop matching once the pool is exhausted", async () => {
+    const donation = await donate(
+      matchDonor,
+      cid,
+      new anchor.BN(LAMPORTS_PER_SOL),
+      { matchPool }
+    );
+
+    const campaign = await fetchCampaign();
+    expect(campaign.amountRaised.toNumber()).toBe(8 * LAMPORTS_PER_SOL);
+
+    const transaction = await crowdfundingProgram.account.transaction.fetch(
+      donation
+    );
+    expect(transaction.matchedAmount.toNumber()).toBe(0);
+  });
+
+  it("should reject top-ups from another sponsor", async () => {
+    await expectError(
+      crowdfundingProgram.methods
+        .fundMatchPool(cid, poolFunding, new anchor.BN(10_000))
+        .accounts({
+          sponsor: matchDonor.publicKey,
+        })
+        .signers([matchDonor])
+        .rpc(),
+      "Unauthorized"
+    );
+  });
+});
+
+describe("Report Responses", () => {
+  let responseCreator: Keypair;
+  let cid: anchor.BN;
+
+  const respond = (signer: Keypair, text: string) =>
+    crowdfundingProgram.methods
+      .respondToReport(cid, text)
+      .accounts({
+        creator: signer.publicKey,
+      })
+      .signers([signer])
+      .rpc();
+
+  beforeAll(async () => {
+    responseCreator = await newFundedKeypair();
+    cid = await createCampaign(responseCreator);
+  });
+
+  it("should persist the creator's response", async () => {
+    const text = "Receipts for every purchase are linked on our site.";
+    await respond(responseCreator, text);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.creatorResponse).toBe(text);
+  });
+
+  it("should reject responses from anyone but the creator", async () => {
+    const stranger = await newFundedKeypair();
+    await expectError(respond(stranger, "Not my campaign"), "Unauthorized");
+  });
+
+  it("should reject responses longer than 280 characters", async () => {
+    await expectError(respond(responseCreator, "x".repeat(281)), "ResponseTooLong");
+  });
+});
+
+describe("Max Goal", () => {
+  let maxGoalCreator: Keypair;
+  const maxGoal = new anchor.BN(20 * LAMPORTS_PER_SOL);
+
+  const setMaxGoal = (updater: Keypair, value: anchor.BN) =>
+    crowdfundingProgram.methods
+      .setMaxGoal(value)
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  beforeAll(async () => {
+    maxGoalCreator = await newFundedKeypair();
+    await setMaxGoal(deployer, maxGoal);
+  });
+
+  afterAll(async () => {
+    await setMaxGoal(deployer, new anchor.BN(0));
+  });
+
+  it("should reject setting the max goal by a non-platform signer", async () => {
+    await expectError(setMaxGoal(maxGoalCreator, maxGoal), "Unauthorized");
+  });
+
+  it("should accept a goal exactly at the max", async () => {
+    const cid = await createCampaign(maxGoalCreator, { goal: maxGoal });
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.goal.toNumber()).toBe(maxGoal.toNumber());
+  });
+
+  it("should reject a goal just above the max", async () => {
+    await expectError(
+      createCampaign(maxGoalCreator, { goal: maxGoal.addn(1) }),
+      "GoalTooLarge"
+    );
+  });
+
+  it("should reject raising a goal above the max on update", async () => {
+    const cid = await createCampaign(maxGoalCreator);
+
+    await expectError(
+      crowdfundingProgram.methods
+        .updateCampaign(
+          cid,
+          campaignTitle,
+          campaignDescription,
+          campaignImageUrl,
+          maxGoal.addn(1),
+          new anchor.BN(0),
+          []
+        )
+        .accounts({
+          creator: maxGoalCreator.publicKey,
+        })
+        .signers([maxGoalCreator])
+        .rpc(),
+      "GoalTooLarge"
+    );
+  });
+});
+
+describe("Campaign Updates", () => {
+  let updateCreator: Keypair;
+  let cid: anchor.BN;
+
+  const findUpdatePda = (cid: anchor.BN, index: number) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("update"),
+        cid.toArrayLike(Buffer, "le", 8),
+        new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+      ],
+      crowdfundingAddress
+    )[0];
+
+  const appendUpdate = async (signer: Keypair, text: string) => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    const update = findUpdatePda(cid, campaign.updateCount.toNumber() + 1);
+
+    await crowdfundingProgram.methods
+      .appendUpdate(cid, text)
+      .accounts({
+        creator: signer.publicKey,
+        update,
+      })
+      .signers([signer])
+      .rpc();
+
+    return update;
+  };
+
+  beforeAll(async () => {
+    updateCreator = await newFundedKeypair();
+    cid = await createCampaign(updateCreator);
+  });
+
+  it("should append updates with increasing indexes", async () => {
+    const first = await appendUpdate(updateCreator, "We bought the supplies");
+    const second = await appendUpdate(updateCreator, "Supplies delivered");
+
+    const firstUpdate = await crowdfundingProgram.account.update.fetch(first);
+    expect(firstUpdate.index.toNumber()).toBe(1);
+    expect(firstUpdate.text).toBe("We bought the supplies");
+
+    const secondUpdate = await crowdfundingProgram.account.update.fetch(second);
+    expect(secondUpdate.index.toNumber()).toBe(2);
+    expect(secondUpdate.text).toBe("Supplies delivered");
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.updateCount.toNumber()).toBe(2);
+  });
+
+  it("should emit an UpdatePosted event", async () => {
+    const { events } = await crowdfundingProgram.methods
+      .appendUpdate(cid, "Thank you all")
+      .accounts({
+        creator: updateCreator.publicKey,
+        update: findUpdatePda(cid, 3),
+      })
+      .signers([updateCreator])
+      .simulate();
+
+    const event = events.find((e) => e.name === "updatePosted");
+    expect(event).toBeDefined();
+    expect(event!.data.index.toNumber()).toBe(3);
+  });
+
+  it("should reject updates from anyone but the creator", async () => {
+    const stranger = await newFundedKeypair();
+    await expectError(appendUpdate(stranger, "Fake update"), "Unauthorized");
+  });
+});
+
+describe("Authority Role Separation", () => {
+  const setAuthorityCannotCreate = (updater: Keypair, flag: boolean) =>
+    crowdfundingProgram.methods
+      .setAuthorityCannotCreate(flag)
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  afterAll(async () => {
+    await setAuthorityCannotCreate(deployer, false);
+  });
+
+  it("should let the authority create campaigns while the flag is off", async () => {
+    const cid = await createCampaign(deployer);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.creator.toBase58()).toBe(deployer.publicKey.toBase58());
+  });
+
+  it("should reject setting the flag by a non-platform signer", async () => {
+    const stranger = await newFundedKeypair();
+    await expectError(setAuthorityCannotCreate(stranger, true), "Unauthorized");
+  });
+
+  it("should reject campaigns from the authority while the flag is on", async () => {
+    await setAuthorityCannotCreate(deployer, true);
+
+    await expectError(createCampaign(deployer), "AuthorityCannotCreate");
+
+    const otherCreator = await newFundedKeypair();
+    await createCampaign(otherCreator);
+  });
+});
+
+describe("Reactivate Campaign", () => {
+  let reactivateCreator: Keypair;
+  let reactivateDonor: Keypair;
+  let cid: anchor.BN;
+
+  const deleteCampaign = () =>
+    crowdfundingProgram.methods
+      .deleteCampaign(cid)
+      .accounts({
+        creator: reactivateCreator.publicKey,
+      })
+      .signers([reactivateCreator])
+      .rpc();
+
+  const reactivateCampaign = (campaignId: anchor.BN) =>
+    crowdfundingProgram.methods
+      .reactivateCampaign(campaignId)
+      .accounts({
+        creator: reactivateCreator.publicKey,
+      })
+      .signers([reactivateCreator])
+      .rpc();
+
+  beforeAll(async () => {
+    reactivateCreator = await newFundedKeypair();
+    reactivateDonor = await newFundedKeypair();
+    cid = await createCampaign(reactivateCreator);
+  });
+
+  it("should reject reactivating an active campaign", async () => {
+    await expectError(reactivateCampaign(cid), "CampaignAlreadyActive");
+  });
+
+  it("should reactivate a cancelled campaign", async () => {
+    await deleteCampaign();
+    await expectError(
+      donate(reactivateDonor, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "InactiveCampaign"
+    );
+
+    await reactivateCampaign(cid);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.active).toBe(true);
+    expect(campaign.status).toEqual({ active: {} });
+  });
+
+  it("should accept donations again after reactivation", async () => {
+    await donate(reactivateDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(LAMPORTS_PER_SOL);
+  });
+
+  it("should reject reactivating a campaign past its deadline", async () => {
+    const deadline = (await currentTime()) + campaignDuration;
+    const expiringCid = await createCampaign(reactivateCreator, {
+      deadline: new anchor.BN(deadline),
+    });
+    await crowdfundingProgram.methods
+      .deleteCampaign(expiringCid)
+      .accounts({
+        creator: reactivateCreator.publicKey,
+      })
+      .signers([reactivateCreator])
+      .rpc();
+
+    await warpTo(deadline + 1);
+
+    await expectError(reactivateCampaign(expiringCid), "CampaignExpired");
+  });
+});
+
+describe("Threshold Subscriptions", () => {
+  let thresholdCreator: Keypair;
+  let subscriber: Keypair;
+  let cid: anchor.BN;
+  const threshold = new anchor.BN(3 * LAMPORTS_PER_SOL);
+  const donationAmount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+  // simulate first to capture the emitted event, then land the donation
+  const donateWithEvent = async (donor: Keypair) => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    const { events } = await crowdfundingProgram.methods
+      .donate(cid, donationAmount, null, new anchor.BN(0), false)
+      .accounts({
+        donor: donor.publicKey,
+        transaction: findDonationPda(
+          donor.publicKey,
+          cid,
+          campaign.donors.add(new anchor.BN(1))
+        ),
+        matchPool: null,
+        priceFeed: null,
+        referralStats: null,
+        platformAddress: null,
+        allowedDonor: null,
+        beneficiary: null,
+        creatorProfile: null,
+      })
+      .signers([donor])
+      .simulate();
+    await donate(donor, cid, donationAmount);
+
+    return events.find((e) => e.name === "thresholdCrossed")!.data;
+  };
+
+  const crosses = (event: any) =>
+    event.previousAmountRaised.lt(threshold) && event.amountRaised.gte(threshold);
+
+  beforeAll(async () => {
+    thresholdCreator = await newFundedKeypair();
+    subscriber = await newFundedKeypair();
+    cid = await createCampaign(thresholdCreator);
+  });
+
+  it("should store the subscription for discovery", async () => {
+    await crowdfundingProgram.methods
+      .subscribeThreshold(cid, threshold)
+      .accounts({
+        subscriber: subscriber.publicKey,
+      })
+      .signers([subscriber])
+      .rpc();
+
+    const [subscriptionPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("threshold"),
+        cid.toArrayLike(Buffer, "le", 8),
+        subscriber.publicKey.toBuffer(),
+        threshold.toArrayLike(Buffer, "le", 8),
+      ],
+      crowdfundingAddress
+    );
+    const subscription =
+      await crowdfundingProgram.account.thresholdSubscription.fetch(
+        subscriptionPda
+      );
+    expect(subscription.subscriber.toBase58()).toBe(
+      subscriber.publicKey.toBase58()
+    );
+    expect(subscription.threshold.toNumber()).toBe(threshold.toNumber());
+  });
+
+  it("should not report a crossing below the threshold", async () => {
+    const event = await donateWithEvent(subscriber);
+    expect(event.amountRaised.toNumber()).toBe(donationAmount.toNumber());
+    expect(crosses(event)).toBe(false);
+  });
+
+  it("should carry the new total when the threshold is crossed", async () => {
+    const event = await donateWithEvent(subscriber);
+    expect(event.previousAmountRaised.toNumber()).toBe(donationAmount.toNumber());
+    expect(event.amountRaised.toNumber()).toBe(donationAmount.muln(2).toNumber());
+    expect(crosses(event)).toBe(true);
+  });
+
+  it("should reject a zero threshold", async () => {
+    await expectError(
+      crowdfundingProgram.methods
+        .subscribeThreshold(cid, new anchor.BN(0))
+        .accounts({
+          subscriber: subscriber.publicKey,
+        })
+        .signers([subscriber])
+        .rpc(),
+      "InvalidThreshold"
+    );
+  });
+});
+
+describe("Creator Loyalty Discount", () => {
+  let loyalCreator: Keypair;
+  let loyalDonor: Keypair;
+  const smallGoal = new anchor.BN(LAMPORTS_PER_SOL);
+
+  // create, fully fund and withdraw a campaign, returning the fee paid
+  const runSuccessfulCampaign = async () => {
+    const cid = await createCampaign(loyalCreator, { goal: smallGoal });
+    await donate(loyalDonor, cid, smallGoal);
+
+    const platformBefore = await context.banksClient.getBalance(treasuryPda);
+    await withdraw(loyalCreator, cid, smallGoal);
+    const platformAfter = await context.banksClient.getBalance(treasuryPda);
+
+    return Number(platformAfter - platformBefore);
+  };
+
+  const findCreatorProfilePda = (creator: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("creator_profile"), creator.toBuffer()],
+      crowdfundingAddress
+    )[0];
+
+  beforeAll(async () => {
+    loyalCreator = await newFundedKeypair();
+    loyalDonor = await newFundedKeypair();
+  });
+
+  it("should charge the full fee until the creator has a track record", async () => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const fullFee = smallGoal
+      .mul(programState.platformFeeBps)
+      .div(new anchor.BN(10_000))
+      .toNumber();
+
+    for (let i = 0; i < 5; i++) {
+      expect(await runSuccessfulCampaign()).toBe(fullFee);
+    }
+
+    const profile = await crowdfundingProgram.account.creatorProfile.fetch(
+      findCreatorProfilePda(loyalCreator.publicKey)
+    );
+    expect(profile.campaignsCreated.toNumber()).toBe(5);
+    expect(profile.successfulCampaigns.toNumber()).toBe(5);
+  });
+
+  it("should reduce the fee by 100 bps after five successful campaigns", async () => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const discountedFee = smallGoal
+      .mul(programState.platformFeeBps.subn(100))
+      .div(new anchor.BN(10_000))
+      .toNumber();
+
+    expect(await runSuccessfulCampaign()).toBe(discountedFee);
+  });
+
+  it("should apply the discount to auto-forwarded donations", async () => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const discountedFee = smallGoal
+      .mul(programState.platformFeeBps.subn(100))
+      .div(new anchor.BN(10_000))
+      .toNumber();
+    const cid = await createCampaign(loyalCreator, {
+      goal: smallGoal,
+      autoForward: true,
+    });
+
+    const platformBefore = await context.banksClient.getBalance(treasuryPda);
+    await donate(loyalDonor, cid, smallGoal, {
+      beneficiary: loyalCreator.publicKey,
+    });
+    const platformAfter = await context.banksClient.getBalance(treasuryPda);
+
+    expect(Number(platformAfter - platformBefore)).toBe(discountedFee);
+  });
+});
+
+describe("USD Goals", () => {
+  let usdCreator: Keypair;
+  let usdDonor: Keypair;
+  let cid: anchor.BN;
+  const priceFeed = Keypair.generate().publicKey;
+  const pythReceiver = new PublicKey(
+    "rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ"
+  );
+  // $150.00 per SOL with pyth's usual -8 exponent
+  const solPrice = new anchor.BN(150).mul(new anchor.BN(100_000_000));
+  const donationAmount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+  // lay out a fully verified PriceUpdateV2 account the way the pyth receiver does
+  const mockPriceUpdate = (
+    address: PublicKey,
+    price: anchor.BN,
+    publishTime: number
+  ) => {
+    const data = Buffer.alloc(134);
+    Buffer.from([34, 241, 35, 99, 157, 126, 244, 205]).copy(data, 0);
+    data.writeUInt8(1, 40); // VerificationLevel::Full
+    price.toArrayLike(Buffer, "le", 8).copy(data, 73);
+    data.writeInt32LE(-8, 89);
+    data.writeBigInt64LE(BigInt(publishTime), 93);
+
+    context.setAccount(address, {
+      lamports: LAMPORTS_PER_SOL,
+      data,
+      owner: pythReceiver,
+      executable: false,
+    });
+  };
+
+  const donateUsd = (feed: PublicKey | null) =>
+    donate(usdDonor, cid, donationAmount, { priceFeed: feed });
+
+  beforeAll(async () => {
+    usdCreator = await newFundedKeypair();
+    usdDonor = await newFundedKeypair();
+    cid = await createCampaign(usdCreator);
+
+    await crowdfundingProgram.methods
+      .setPriceFeed(priceFeed)
+      .accounts({
+        updater: deployer.publicKey,
+      })
+      .signers([deployer])
+      .rpc();
+
+    await crowdfundingProgram.methods
+      .setUsdGoal(cid, new anchor.BN(100_000)) // $1,000.00
+      .accounts({
+        creator: usdCreator.publicKey,
+      })
+      .signers([usdCreator])
+      .rpc();
+  });
+
+  it("should require the trusted price feed for usd campaigns", async () => {
+    mockPriceUpdate(priceFeed, solPrice, await currentTime());
+
+    await expectError(donateUsd(null), "InvalidPriceFeed");
+
+    const untrustedFeed = Keypair.generate().publicKey;
+    mockPriceUpdate(untrustedFeed, solPrice, await currentTime());
+    await expectError(donateUsd(untrustedFeed), "InvalidPriceFeed");
+  });
+
+  it("should reject a stale price", async () => {
+    mockPriceUpdate(priceFeed, solPrice, (await currentTime()) - 3_600);
+
+    await expectError(donateUsd(priceFeed), "StalePrice");
+  });
+
+  it("should record usd progress at the oracle price", async () => {
+    // move to a new slot so the retried donation gets a fresh blockhash
+    await warpTo((await currentTime()) + 1);
+    const publishTime = await currentTime();
+    mockPriceUpdate(priceFeed, solPrice, publishTime);
+
+    await donateUsd(priceFeed);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.usdDenominated).toBe(true);
+    expect(campaign.goalUsd.toNumber()).toBe(100_000);
+    expect(campaign.raisedUsd.toNumber()).toBe(30_000); // 2 SOL at $150
+    expect(campaign.referencePrice.toString()).toBe(solPrice.toString());
+    expect(campaign.referenceExpo).toBe(-8);
+    expect(campaign.priceTimestamp.toNumber()).toBe(publishTime);
+  });
+
+  it("should leave sol campaigns unaffected", async () => {
+    const solCid = await createCampaign(usdCreator);
+    await donate(usdDonor, solCid, donationAmount);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(solCid)
+    );
+    expect(campaign.usdDenominated).toBe(false);
+    expect(campaign.raisedUsd.toNumber()).toBe(0);
+  });
+});
+
+describe("Concurrent Transactions", () => {
+  let raceCreator: Keypair;
+  let raceDonor: Keypair;
+  let cid: anchor.BN;
+
+  const donateAt = (transaction: PublicKey, amount: anchor.BN) =>
+    crowdfundingProgram.methods
+      .donate(cid, amount, null, new anchor.BN(0), false)
+      .accounts({
+        donor: raceDonor.publicKey,
+        transaction,
+        matchPool: null,
+        priceFeed: null,
+        referralStats: null,
+        platformAddress: null,
+        allowedDonor: null,
+        beneficiary: null,
+        creatorProfile: null,
+      })
+      .signers([raceDonor])
+      .rpc();
+
+  beforeAll(async () => {
+    raceCreator = await newFundedKeypair();
+    raceDonor = await newFundedKeypair();
+    cid = await createCampaign(raceCreator);
+  });
+
+  it("should reject the second of two donations built against the same index", async () => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    const staleIndex = campaign.donors.add(new anchor.BN(1));
+    const transaction = findDonationPda(raceDonor.publicKey, cid, staleIndex);
+
+    await donateAt(transaction, new anchor.BN(LAMPORTS_PER_SOL));
+    await expectError(
+      donateAt(transaction, new anchor.BN(2 * LAMPORTS_PER_SOL)),
+      "ConstraintSeeds"
+    );
+
+    const record = await crowdfundingProgram.account.transaction.fetch(
+      transaction
+    );
+    expect(record.amount.toNumber()).toBe(LAMPORTS_PER_SOL);
+    expect(record.processed).toBe(true);
+  });
+
+  it("should accept the retry once the client refetches the index", async () => {
+    await donate(raceDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.donors.toNumber()).toBe(2);
+    expect(campaign.amountRaised.toNumber()).toBe(3 * LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Match Distribution", () => {
+  let qfCreator: Keypair;
+  let qfSponsor: Keypair;
+  let qfDonors: Keypair[];
+  let cid: anchor.BN;
+  let deadline: number;
+  const poolFunding = new anchor.BN(10 * LAMPORTS_PER_SOL);
+
+  const findDonorRecordPda = (donor: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("donor_record"),
+        cid.toArrayLike(Buffer, "le", 8),
+        donor.toBuffer(),
+      ],
+      crowdfundingAddress
+    )[0];
+
+  const distributeMatch = (donors: Keypair[]) =>
+    crowdfundingProgram.methods
+      .distributeMatch(cid)
+      .accounts({
+        caller: qfSponsor.publicKey,
+      })
+      .remainingAccounts(
+        donors.map((donor) => ({
+          pubkey: findDonorRecordPda(donor.publicKey),
+          isWritable: false,
+          isSigner: false,
+        }))
+      )
+      .signers([qfSponsor])
+      .rpc();
+
+  beforeAll(async () => {
+    qfCreator = await newFundedKeypair();
+    qfSponsor = await newFundedKeypair();
+    qfDonors = [await newFundedKeypair(), await newFundedKeypair()];
+    deadline = (await currentTime()) + campaignDuration;
+    // the 5 SOL raised clears the soft cap, so the campaign ends completed
+    cid = await createCampaign(qfCreator, {
+      goal: new anchor.BN(20 * LAMPORTS_PER_SOL),
+      softCap: new anchor.BN(5 * LAMPORTS_PER_SOL),
+      deadline: new anchor.BN(deadline),
+    });
+
+    await crowdfundingProgram.methods
+      .fundMatchPool(cid, poolFunding, new anchor.BN(10_000))
+      .accounts({
+        sponsor: qfSponsor.publicKey,
+      })
+      .signers([qfSponsor])
+      .rpc();
+
+    // donations skip the live match so the whole pool is left for distribution
+    await donate(qfDonors[0], cid, new anchor.BN(LAMPORTS_PER_SOL));
+    await donate(qfDonors[1], cid, new anchor.BN(4 * LAMPORTS_PER_SOL));
+  });
+
+  it("should reject distribution while the campaign is running", async () => {
+    await expectError(distributeMatch(qfDonors), "CampaignNotCompleted");
+  });
+
+  it("should require a record for every donor", async () => {
+    await warpTo(deadline + 1);
+    await crowdfundingProgram.methods
+      .expireCampaign(cid)
+      .accounts({
+        caller: qfSponsor.publicKey,
+      })
+      .signers([qfSponsor])
+      .rpc();
+
+    await expectError(
+      distributeMatch([qfDonors[0]]),
+      "InvalidDonorRecords"
+    );
+  });
+
+  it("should not match a campaign that is paying donors back", async () => {
+    const donor = await newFundedKeypair();
+    const failedDeadline = (await currentTime()) + campaignDuration;
+    const failedCid = await createCampaign(qfCreator, {
+      deadline: new anchor.BN(failedDeadline),
+    });
+
+    await crowdfundingProgram.methods
+      .fundMatchPool(failedCid, poolFunding, new anchor.BN(10_000))
+      .accounts({
+        sponsor: qfSponsor.publicKey,
+      })
+      .signers([qfSponsor])
+      .rpc();
+    await donate(donor, failedCid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    await warpTo(failedDeadline + 1);
+    await crowdfundingProgram.methods
+      .expireCampaign(failedCid)
+      .accounts({
+        caller: qfSponsor.publicKey,
+      })
+      .signers([qfSponsor])
+      .rpc();
+
+    await expectError(
+      crowdfundingProgram.methods
+        .distributeMatch(failedCid)
+        .accounts({
+          caller: qfSponsor.publicKey,
+        })
+        .remainingAccounts([
+          {
+            pubkey: PublicKey.findProgramAddressSync(
+              [
+                Buffer.from("donor_record"),
+                failedCid.toArrayLike(Buffer, "le", 8),
+                donor.publicKey.toBuffer(),
+              ],
+              crowdfundingAddress
+            )[0],
+            isWritable: false,
+            isSigner: false,
+          },
+        ])
+        .signers([qfSponsor])
+        .rpc(),
+      "CampaignNotCompleted"
+    );
+  });
+
+  it("should add the quadratic match to the campaign", async () => {
+    await distributeMatch(qfDonors);
+
+    // (sqrt(1) + sqrt(4))^2 - 5 = 4 SOL, give or take integer sqrt rounding
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    const matched = campaign.amountRaised.toNumber() - 5 * LAMPORTS_PER_SOL;
+    expect(Math.abs(matched - 4 * LAMPORTS_PER_SOL)).toBeLessThan(
+      LAMPORTS_PER_SOL / 1_000
+    );
+
+    const pool = await crowdfundingProgram.account.matchPool.fetch(
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("match"), cid.toArrayLike(Buffer, "le", 8)],
+        crowdfundingAddress
+      )[0]
+    );
+    expect(pool.distributed).toBe(true);
+    expect(pool.totalMatched.toNumber()).toBe(matched);
+  });
+
+  it("should only distribute once", async () => {
+    await warpTo((await currentTime()) + 1);
+    await expectError(distributeMatch(qfDonors), "MatchAlreadyDistributed");
+  });
+});
+
+describe("Donation Cooldown", () => {
+  let cooldownCreator: Keypair;
+  let cooldownDonor: Keypair;
+  let cid: anchor.BN;
+  const cooldown = 60 * 60;
+  const donationAmount = new anchor.BN(LAMPORTS_PER_SOL);
+
+  beforeAll(async () => {
+    cooldownCreator = await newFundedKeypair();
+    cooldownDonor = await newFundedKeypair();
+    cid = await createCampaign(cooldownCreator, {
+      donationCooldown: new anchor.BN(cooldown),
+    });
+  });
+
+  it("should reject a second donation inside the cooldown", async () => {
+    await donate(cooldownDonor, cid, donationAmount);
+
+    await expectError(
+      donate(cooldownDonor, cid, donationAmount),
+      "DonationTooFrequent"
+    );
+  });
+
+  it("should not hold other wallets to the cooldown", async () => {
+    const otherDonor = await newFundedKeypair();
+    await donate(otherDonor, cid, donationAmount);
+  });
+
+  it("should accept the donation once the cooldown has passed", async () => {
+    await warpTo((await currentTime()) + cooldown);
+
+    await donate(cooldownDonor, cid, donationAmount);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.donationCooldown.toNumber()).toBe(cooldown);
+    expect(campaign.donors.toNumber()).toBe(3);
+  });
+});
+
+describe("Net Raised After Refunds", () => {
+  let netCreator: Keypair;
+  let netDonors: Keypair[];
+  let cid: anchor.BN;
+  const donationAmount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+  const fetchCampaign = () =>
+    crowdfundingProgram.account.campaign.fetch(findCampaignPda(cid));
+
+  beforeAll(async () => {
+    netCreator = await newFundedKeypair();
+    netDonors = [await newFundedKeypair(), await newFundedKeypair()];
+    cid = await createCampaign(netCreator);
+
+    for (const donor of netDonors) {
+      await donate(donor, cid, donationAmount);
+    }
+
+    await crowdfundingProgram.methods
+      .deleteCampaign(cid)
+      .accounts({
+        creator: netCreator.publicKey,
+      })
+      .signers([netCreator])
+      .rpc();
+
+    await crowdfundingProgram.methods
+      .refund(cid, new anchor.BN(1))
+      .accounts({
+        donor: netDonors[0].publicKey,
+        caller: netCreator.publicKey,
+      })
+      .signers([netCreator])
+      .rpc();
+  });
+
+  it("should track refunds separately from the amount raised", async () => {
+    const campaign = await fetchCampaign();
+    expect(campaign.amountRaised.toNumber()).toBe(donationAmount.muln(2).toNumber());
+    expect(campaign.refundedTotal.toNumber()).toBe(donationAmount.toNumber());
+    expect(campaign.balance.toNumber()).toBe(donationAmount.toNumber());
+  });
+
+  it("should reject withdrawing once the campaign is cancelled", async () => {
+    await expectError(
+      withdraw(netCreator, cid, donationAmount),
+      "RefundsOpen"
+    );
+  });
+
+  it("should refund the rest of the amount raised to donors", async () => {
+    await crowdfundingProgram.methods
+      .refund(cid, new anchor.BN(2))
+      .accounts({
+        donor: netDonors[1].publicKey,
+        caller: netCreator.publicKey,
+      })
+      .signers([netCreator])
+      .rpc();
+
+    const campaign = await fetchCampaign();
+    expect(campaign.withdrawnTotal.toNumber()).toBe(0);
+    expect(campaign.refundedTotal.toNumber()).toBe(
+      campaign.amountRaised.toNumber()
+    );
+    expect(campaign.balance.toNumber()).toBe(0);
+  });
+});
+
+describe("Referrals", () => {
+  let referralCreator: Keypair;
+  let referrer: Keypair;
+  let referredDonor: Keypair;
+  let cid: anchor.BN;
+  let referralStatsPda: PublicKey;
+  const donationAmount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+  beforeAll(async () => {
+    referralCreator = await newFundedKeypair();
+    referrer = await newFundedKeypair();
+    referredDonor = await newFundedKeypair();
+    cid = await createCampaign(referralCreator);
+    [referralStatsPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("referral"), referrer.publicKey.toBuffer()],
+      crowdfundingAddress
+    );
+
+    await crowdfundingProgram.methods
+      .registerReferrer()
+      .accounts({
+        referrer: referrer.publicKey,
+      })
+      .signers([referrer])
+      .rpc();
+  });
+
+  it("should credit the referrer for a referred donation", async () => {
+    const donation = await donate(referredDonor, cid, donationAmount, {
+      referrer: referrer.publicKey,
+    });
+
+    const transaction = await crowdfundingProgram.account.transaction.fetch(
+      donation
+    );
+    expect(transaction.referrer!.toBase58()).toBe(referrer.publicKey.toBase58());
+
+    const stats = await crowdfundingProgram.account.referralStats.fetch(
+      referralStatsPda
+    );
+    expect(stats.referredTotal.toNumber()).toBe(donationAmount.toNumber());
+    expect(stats.referredCount.toNumber()).toBe(1);
+  });
+
+  it("should accumulate across referred donations", async () => {
+    const secondDonor = await newFundedKeypair();
+    await donate(secondDonor, cid, donationAmount, {
+      referrer: referrer.publicKey,
+    });
+
+    const stats = await crowdfundingProgram.account.referralStats.fetch(
+      referralStatsPda
+    );
+    expect(stats.referredTotal.toNumber()).toBe(donationAmount.muln(2).toNumber());
+    expect(stats.referredCount.toNumber()).toBe(2);
+  });
+
+  it("should reject a donor referring themselves", async () => {
+    await expectError(
+      donate(referrer, cid, donationAmount, { referrer: referrer.publicKey }),
+      "SelfReferral"
+    );
+  });
+
+  it("should reject a referrer without registered stats", async () => {
+    const unregistered = await newFundedKeypair();
+    await expectError(
+      donate(referredDonor, cid, donationAmount, {
+        referrer: unregistered.publicKey,
+      }),
+      "AccountNotInitialized"
+    );
+  });
+});
+
+describe("Platform Tips", () => {
+  let tipCreator: Keypair;
+  let tipDonor: Keypair;
+  let cid: anchor.BN;
+  const donationAmount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+  const tip = new anchor.BN(LAMPORTS_PER_SOL / 2);
+
+  const fetchCampaign = () =>
+    crowdfundingProgram.account.campaign.fetch(findCampaignPda(cid));
+
+  beforeAll(async () => {
+    tipCreator = await newFundedKeypair();
+    tipDonor = await newFundedKeypair();
+    cid = await createCampaign(tipCreator);
+  });
+
+  it("should send the tip to the platform and the donation to the campaign", async () => {
+    const platformBefore = await context.banksClient.getBalance(
+      deployer.publicKey
+    );
+
+    const donation = await donate(tipDonor, cid, donationAmount, { tip });
+
+    const platformAfter = await context.banksClient.getBalance(
+      deployer.publicKey
+    );
+    expect(Number(platformAfter - platformBefore)).toBe(tip.toNumber());
+
+    const campaign = await fetchCampaign();
+    expect(campaign.amountRaised.toNumber()).toBe(donationAmount.toNumber());
+    expect(campaign.balance.toNumber()).toBe(donationAmount.toNumber());
+
+    const transaction = await crowdfundingProgram.account.transaction.fetch(
+      donation
+    );
+    expect(transaction.tip.toNumber()).toBe(tip.toNumber());
+  });
+
+  it("should roll back the donation when the tip cannot be paid", async () => {
+    const shortDonor = await newFundedKeypair(3);
+
+    // the failing system transfer aborts the whole transaction
+    await expect(
+      donate(shortDonor, cid, donationAmount, {
+        tip: new anchor.BN(5 * LAMPORTS_PER_SOL),
+      })
+    ).rejects.toThrow();
+
+    const campaign = await fetchCampaign();
+    expect(campaign.amountRaised.toNumber()).toBe(donationAmount.toNumber());
+  });
+});
+
+describe("Balance Invariant", () => {
+  let ledgerCreator: Keypair;
+  let ledgerDonors: Keypair[];
+  let cid: anchor.BN;
+
+  // balance is never stored independently of the ledger totals
+  const expectLedgerConsistent = async () => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(
+      campaign.amountRaised
+        .sub(campaign.withdrawnTotal)
+        .sub(campaign.refundedTotal)
+        .toString()
+    ).toBe(campaign.balance.toString());
+    return campaign;
+  };
+
+  beforeAll(async () => {
+    ledgerCreator = await newFundedKeypair();
+    ledgerDonors = [
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+    ];
+    cid = await createCampaign(ledgerCreator, {
+      goal: new anchor.BN(20 * LAMPORTS_PER_SOL),
+    });
+  });
+
+  it("should hold after each donation", async () => {
+    await expectLedgerConsistent();
+    await donate(ledgerDonors[0], cid, new anchor.BN(3 * LAMPORTS_PER_SOL));
+    await expectLedgerConsistent();
+    await donate(ledgerDonors[1], cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+    await expectLedgerConsistent();
+  });
+
+  it("should hold across interleaved withdrawals and donations", async () => {
+    await withdraw(ledgerCreator, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+    await expectLedgerConsistent();
+    await donate(ledgerDonors[2], cid, new anchor.BN(4 * LAMPORTS_PER_SOL));
+    await expectLedgerConsistent();
+    await withdraw(ledgerCreator, cid, new anchor.BN(LAMPORTS_PER_SOL));
+    await withdraw(ledgerCreator, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    const campaign = await expectLedgerConsistent();
+    expect(campaign.withdrawnTotal.toNumber()).toBe(4 * LAMPORTS_PER_SOL);
+    expect(campaign.balance.toNumber()).toBe(5 * LAMPORTS_PER_SOL);
+  });
+
+  it("should hold through cancellation and refunds", async () => {
+    await crowdfundingProgram.methods
+      .deleteCampaign(cid)
+      .accounts({
+        creator: ledgerCreator.publicKey,
+      })
+      .signers([ledgerCreator])
+      .rpc();
+
+    await crowdfundingProgram.methods
+      .refund(cid, new anchor.BN(1))
+      .accounts({
+        donor: ledgerDonors[0].publicKey,
+        caller: ledgerCreator.publicKey,
+      })
+      .signers([ledgerCreator])
+      .rpc();
+
+    const campaign = await expectLedgerConsistent();
+    expect(campaign.refundedTotal.toNumber()).toBe(3 * LAMPORTS_PER_SOL);
+    expect(campaign.balance.toNumber()).toBe(2 * LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Campaign Duration Limits", () => {
+  let durationCreator: Keypair;
+  let cid: anchor.BN;
+  let deadline: number;
+  const day = 24 * 60 * 60;
+
+  const extendDeadline = (newDeadline: number) =>
+    crowdfundingProgram.methods
+      .extendDeadline(cid, new anchor.BN(newDeadline))
+      .accounts({
+        creator: durationCreator.publicKey,
+      })
+      .signers([durationCreator])
+      .rpc();
+
+  beforeAll(async () => {
+    durationCreator = await newFundedKeypair();
+    deadline = (await currentTime()) + 7 * day;
+    cid = await createCampaign(durationCreator, {
+      deadline: new anchor.BN(deadline),
+    });
+  });
+
+  it("should reject a campaign shorter than a day", async () => {
+    await expectError(
+      createCampaign(durationCreator, {
+        deadline: new anchor.BN((await currentTime()) + day - 1),
+      }),
+      "CampaignTooShort"
+    );
+  });
+
+  it("should extend the deadline within the cap", async () => {
+    deadline += 20 * day;
+    await extendDeadline(deadline);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.deadline.toNumber()).toBe(deadline);
+    expect(campaign.totalExtension.toNumber()).toBe(20 * day);
+  });
+
+  it("should reject extensions beyond the lifetime cap", async () => {
+    await expectError(extendDeadline(deadline + 10 * day + 1), "ExtensionCapExceeded");
+  });
+
+  it("should reject shortening the deadline", async () => {
+    await expectError(extendDeadline(deadline - day), "InvalidDeadline");
+  });
+});
+
+describe("Lock Until Goal", () => {
+  let lockedCreator: Keypair;
+  let lockedDonor: Keypair;
+  let cid: anchor.BN;
+  const lockedGoal = new anchor.BN(3 * LAMPORTS_PER_SOL);
+
+  beforeAll(async () => {
+    lockedCreator = await newFundedKeypair();
+    lockedDonor = await newFundedKeypair();
+    cid = await createCampaign(lockedCreator, {
+      goal: lockedGoal,
+      lockUntilGoal: true,
+    });
+  });
+
+  it("should reject withdrawals before the goal is reached", async () => {
+    await donate(lockedDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+
+    await expectError(
+      withdraw(lockedCreator, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "GoalNotReached"
+    );
+  });
+
+  it("should allow withdrawals once the goal is crossed", async () => {
+    await donate(lockedDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+    await withdraw(lockedCreator, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.lockUntilGoal).toBe(true);
+    expect(campaign.withdrawnTotal.toNumber()).toBe(LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Batch Donations", () => {
+  let batchCreator: Keypair;
+  let batchDonor: Keypair;
+  let cids: anchor.BN[];
+
+  const findBatchDonorRecordPda = (cid: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("donor_record"),
+        cid.toArrayLike(Buffer, "le", 8),
+        batchDonor.publicKey.toBuffer(),
+      ],
+      crowdfundingAddress
+    )[0];
+
+  const findBatchLeaderboardPda = (cid: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("leaderboard"), cid.toArrayLike(Buffer, "le", 8)],
+      crowdfundingAddress
+    )[0];
+
+  const donateBatch = async (
+    entries: { cid: anchor.BN; amount: anchor.BN }[]
+  ) => {
+    const remainingAccounts = [];
+    for (const { cid } of entries) {
+      const campaignPda = findCampaignPda(cid);
+      const campaign = await crowdfundingProgram.account.campaign.fetch(
+        campaignPda
+      );
+      remainingAccounts.push(
+        { pubkey: campaignPda, isWritable: true, isSigner: false },
+        {
+          pubkey: findDonationPda(
+            batchDonor.publicKey,
+            cid,
+            campaign.donors.add(new anchor.BN(1))
+          ),
+          isWritable: true,
+          isSigner: false,
+        },
+        {
+          pubkey: findBatchDonorRecordPda(cid),
+          isWritable: true,
+          isSigner: false,
+        },
+        {
+          pubkey: findBatchLeaderboardPda(cid),
+          isWritable: true,
+          isSigner: false,
+        }
+      );
+    }
+
+    await crowdfundingProgram.methods
+      .donateBatch(entries)
+      .accounts({
+        donor: batchDonor.publicKey,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([batchDonor])
+      .rpc();
+  };
+
+  beforeAll(async () => {
+    batchCreator = await newFundedKeypair();
+    batchDonor = await newFundedKeypair();
+    cids = [];
+    for (let i = 0; i < 3; i++) {
+      cids.push(await createCampaign(batchCreator));
+    }
+  });
+
+  it("should donate to three campaigns in one instruction", async () => {
+    const amounts = [1, 2, 3].map(
+      (sol) => new anchor.BN(sol * LAMPORTS_PER_SOL)
+    );
+
+    await donateBatch(cids.map((cid, i) => ({ cid, amount: amounts[i] })));
+
+    for (let i = 0; i < cids.length; i++) {
+      const campaign = await crowdfundingProgram.account.campaign.fetch(
+        findCampaignPda(cids[i])
+      );
+      expect(campaign.amountRaised.toString()).toBe(amounts[i].toString());
+      expect(campaign.balance.toString()).toBe(amounts[i].toString());
+
+      const transaction = await crowdfundingProgram.account.transaction.fetch(
+        findDonationPda(batchDonor.publicKey, cids[i], new anchor.BN(1))
+      );
+      expect(transaction.amount.toString()).toBe(amounts[i].toString());
+      expect(transaction.credited).toBe(true);
+    }
+  });
+
+  it("should record the donor like a single donation would", async () => {
+    for (const cid of cids) {
+      const campaign = await crowdfundingProgram.account.campaign.fetch(
+        findCampaignPda(cid)
+      );
+      expect(campaign.uniqueDonors.toNumber()).toBe(1);
+      expect(campaign.topDonor.toBase58()).toBe(
+        batchDonor.publicKey.toBase58()
+      );
+
+      const record = await crowdfundingProgram.account.donorRecord.fetch(
+        findBatchDonorRecordPda(cid)
+      );
+      expect(record.donorRank.toNumber()).toBe(1);
+      expect(record.donationCount.toNumber()).toBe(1);
+      expect(record.totalContributed.toString()).toBe(
+        campaign.amountRaised.toString()
+      );
+
+      const leaderboard = await crowdfundingProgram.account.leaderboard.fetch(
+        findBatchLeaderboardPda(cid)
+      );
+      expect(leaderboard.entries[0].donor.toBase58()).toBe(
+        batchDonor.publicKey.toBase58()
+      );
+    }
+  });
+
+  it("should not be blocked by lamports sent to a new donor record", async () => {
+    const cid = await createCampaign(batchCreator);
+    const donorRecord = findBatchDonorRecordPda(cid);
+    const leaderboard = findBatchLeaderboardPda(cid);
+
+    // anyone can fund the addresses before the batch creates them
+    for (const address of [donorRecord, leaderboard]) {
+      context.setAccount(address, {
+        lamports: 1_000_000,
+        data: Buffer.alloc(0),
+        owner: SystemProgram.programId,
+        executable: false,
+      });
+    }
+
+    await donateBatch([{ cid, amount: new anchor.BN(LAMPORTS_PER_SOL) }]);
+
+    const record = await crowdfundingProgram.account.donorRecord.fetch(
+      donorRecord
+    );
+    expect(record.donationCount.toNumber()).toBe(1);
+  });
+
+  it("should reject a batch missing its donor accounts", async () => {
+    await expectError(
+      crowdfundingProgram.methods
+        .donateBatch([
+          { cid: cids[0], amount: new anchor.BN(LAMPORTS_PER_SOL) },
+        ])
+        .accounts({
+          donor: batchDonor.publicKey,
+        })
+        .remainingAccounts([
+          {
+            pubkey: findCampaignPda(cids[0]),
+            isWritable: true,
+            isSigner: false,
+          },
+        ])
+        .signers([batchDonor])
+        .rpc(),
+      "BatchAccountsMismatch"
+    );
+  });
+
+  it("should roll back the whole batch when one entry fails", async () => {
+    const before = await Promise.all(
+      cids.map((cid) =>
+        crowdfundingProgram.account.campaign.fetch(findCampaignPda(cid))
+      )
+    );
+
+    await expectError(
+      donateBatch([
+        { cid: cids[0], amount: new anchor.BN(LAMPORTS_PER_SOL) },
+        { cid: cids[1], amount: new anchor.BN(LAMPORTS_PER_SOL) },
+        { cid: cids[2], amount: new anchor.BN(LAMPORTS_PER_SOL / 2) },
+      ]),
+      "InvalidDonationAmount"
+    );
+
+    for (let i = 0; i < cids.length; i++) {
+      const campaign = await crowdfundingProgram.account.campaign.fetch(
+        findCampaignPda(cids[i])
+      );
+      expect(campaign.amountRaised.toString()).toBe(
+        before[i].amountRaised.toString()
+      );
+      expect(campaign.donors.toString()).toBe(before[i].donors.toString());
+    }
+  });
+
+  it("should reject batches larger than the cap", async () => {
+    const entries = [];
+    for (let i = 0; i < 6; i++) {
+      entries.push({
+        cid: cids[i % cids.length],
+        amount: new anchor.BN(LAMPORTS_PER_SOL),
+      });
+    }
+
+    await expectError(
+      crowdfundingProgram.methods
+        .donateBatch(entries)
+        .accounts({
+          donor: batchDonor.publicKey,
+        })
+        .signers([batchDonor])
+        .rpc(),
+      "BatchTooLarge"
+    );
+  });
+});
+
+describe("Soft and Hard Caps", () => {
+  let cappedCreator: Keypair;
+  let cappedDonor: Keypair;
+
+  beforeAll(async () => {
+    cappedCreator = await newFundedKeypair();
+    cappedDonor = await newFundedKeypair();
+  });
+
+  it("should reject caps that do not bracket the goal", async () => {
+    await expectError(
+      createCampaign(cappedCreator, {
+        goal: new anchor.BN(5 * LAMPORTS_PER_SOL),
+        hardCap: new anchor.BN(4 * LAMPORTS_PER_SOL),
+      }),
+      "InvalidCapConfig"
+    );
+
+    await expectError(
+      createCampaign(cappedCreator, {
+        goal: new anchor.BN(5 * LAMPORTS_PER_SOL),
+        softCap: new anchor.BN(6 * LAMPORTS_PER_SOL),
+      }),
+      "InvalidCapConfig"
+    );
+  });
+
+  it("should accept donations past the goal up to the hard cap", async () => {
+    const cid = await createCampaign(cappedCreator, {
+      goal: new anchor.BN(2 * LAMPORTS_PER_SOL),
+      softCap: new anchor.BN(LAMPORTS_PER_SOL),
+      hardCap: new anchor.BN(4 * LAMPORTS_PER_SOL),
+    });
+
+    await donate(cappedDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+    await donate(cappedDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    await expectError(
+      donate(cappedDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL)),
+      "HardCapReached"
+    );
+
+    await donate(cappedDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    await expectError(
+      donate(cappedDonor, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "HardCapReached"
+    );
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(4 * LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Campaign Progress", () => {
+  let progressCreator: Keypair;
+  let progressDonor: Keypair;
+  let cid: anchor.BN;
+
+  const computeProgress = async () => {
+    const { events } = await crowdfundingProgram.methods
+      .computeProgress(cid)
+      .simulate();
+
+    return events.find((e) => e.name === "progressSnapshot")!.data;
+  };
+
+  beforeAll(async () => {
+    progressCreator = await newFundedKeypair();
+    progressDonor = await newFundedKeypair();
+    cid = await createCampaign(progressCreator, {
+      goal: new anchor.BN(4 * LAMPORTS_PER_SOL),
+    });
+  });
+
+  it("should snapshot a partially funded campaign", async () => {
+    await donate(progressDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    const snapshot = await computeProgress();
+    expect(snapshot.cid.toNumber()).toBe(cid.toNumber());
+    expect(snapshot.raised.toNumber()).toBe(LAMPORTS_PER_SOL);
+    expect(snapshot.goal.toNumber()).toBe(4 * LAMPORTS_PER_SOL);
+    expect(snapshot.remaining.toNumber()).toBe(3 * LAMPORTS_PER_SOL);
+    expect(snapshot.percentBps.toNumber()).toBe(2_500);
+  });
+
+  it("should saturate once the goal is overshot", async () => {
+    await donate(progressDonor, cid, new anchor.BN(5 * LAMPORTS_PER_SOL));
+
+    const snapshot = await computeProgress();
+    expect(snapshot.remaining.toNumber()).toBe(0);
+    expect(snapshot.percentBps.toNumber()).toBe(10_000);
+  });
+});
+
+describe("Donation Transfer Accounting", () => {
+  it("should move exactly the donated lamports into the campaign", async () => {
+    const accountingCreator = await newFundedKeypair();
+    const accountingDonor = await newFundedKeypair();
+    const cid = await createCampaign(accountingCreator);
+    const campaignPda = findCampaignPda(cid);
+    const amount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+    const lamportsBefore = await context.banksClient.getBalance(campaignPda);
+    await donate(accountingDonor, cid, amount);
+    const lamportsAfter = await context.banksClient.getBalance(campaignPda);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      campaignPda
+    );
+    expect((lamportsAfter - lamportsBefore).toString()).toBe(
+      campaign.amountRaised.toString()
+    );
+    expect(campaign.balance.toString()).toBe(amount.toString());
+  });
+});
+
+describe("Fee Bounds", () => {
+  let boundsUpdater: Keypair;
+  let originalFee: anchor.BN;
+
+  const setFeeBounds = (updater: Keypair, min: number, max: number) =>
+    crowdfundingProgram.methods
+      .setFeeBounds(new anchor.BN(min), new anchor.BN(max))
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  const setPlatformFee = (fee: number) =>
+    crowdfundingProgram.methods
+      .updatePlatformSettings(new anchor.BN(fee), new anchor.BN(0))
+      .accounts({
+        updater: deployer.publicKey,
+      })
+      .signers([deployer])
+      .rpc();
+
+  beforeAll(async () => {
+    boundsUpdater = await newFundedKeypair();
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    originalFee = programState.platformFeeBps;
+  });
+
+  afterAll(async () => {
+    await setFeeBounds(deployer, 100, 1_500);
+    await setPlatformFee(originalFee.toNumber());
+  });
+
+  it("should reject bound updates from a non-platform signer", async () => {
+    await expectError(setFeeBounds(boundsUpdater, 200, 800), "Unauthorized");
+  });
+
+  it("should reject bounds above 50% or inverted", async () => {
+    await expectError(setFeeBounds(deployer, 100, 5_100), "InvalidFeeBounds");
+    await expectError(setFeeBounds(deployer, 800, 200), "InvalidFeeBounds");
+  });
+
+  it("should validate the platform fee against the stored bounds", async () => {
+    await setFeeBounds(deployer, 200, 800);
+
+    await setPlatformFee(800);
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    expect(programState.minFeeBps.toNumber()).toBe(200);
+    expect(programState.maxFeeBps.toNumber()).toBe(800);
+    expect(programState.platformFeeBps.toNumber()).toBe(800);
+
+    await expectError(setPlatformFee(900), "InvalidPlatformFee");
+    await expectError(setPlatformFee(100), "InvalidPlatformFee");
+  });
+
+  it("should allow loosening the bounds beyond the old 15% limit", async () => {
+    await setFeeBounds(deployer, 100, 2_000);
+    await setPlatformFee(2_000);
+
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    expect(programState.platformFeeBps.toNumber()).toBe(2_000);
+  });
+});
+
+describe("Basis Point Fees", () => {
+  let bpsCreator: Keypair;
+  let bpsDonor: Keypair;
+  let originalFee: anchor.BN;
+
+  const setPlatformFee = (fee: number) =>
+    crowdfundingProgram.methods
+      .updatePlatformSettings(new anchor.BN(fee), new anchor.BN(0))
+      .accounts({
+        updater: deployer.publicKey,
+      })
+      .signers([deployer])
+      .rpc();
+
+  const migrateFeeToBps = (campaigns: PublicKey[] = []) =>
+    crowdfundingProgram.methods
+      .migrateFeeToBps()
+      .accounts({
+        updater: deployer.publicKey,
+      })
+      .remainingAccounts(
+        campaigns.map((pubkey) => ({
+          pubkey,
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
+      .signers([deployer])
+      .rpc();
+
+  // rewrite an account in place, e.g. to recreate a pre-migration deployment
+  const overwriteAccount = async (
+    address: PublicKey,
+    name: "ProgramState" | "Campaign",
+    data: any
+  ) => {
+    const account = (await context.banksClient.getAccount(address))!;
+    const encoded = await crowdfundingProgram.coder.accounts.encode(name, data);
+    const padded = Buffer.alloc(account.data.length);
+    encoded.copy(padded);
+    context.setAccount(address, { ...account, data: padded });
+  };
+
+  beforeAll(async () => {
+    bpsCreator = await newFundedKeypair();
+    bpsDonor = await newFundedKeypair();
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    originalFee = programState.platformFeeBps;
+  });
+
+  afterAll(async () => {
+    await setPlatformFee(originalFee.toNumber());
+  });
+
+  it("should charge 2.5% for a 250 bps fee", async () => {
+    await setPlatformFee(250);
+
+    const cid = await createCampaign(bpsCreator);
+    const withdrawAmount = new anchor.BN(4 * LAMPORTS_PER_SOL);
+    await donate(bpsDonor, cid, withdrawAmount);
+
+    const initialPlatformBalance = await context.banksClient.getBalance(
+      treasuryPda
+    );
+    await withdraw(bpsCreator, cid, withdrawAmount);
+    const finalPlatformBalance = await context.banksClient.getBalance(
+      treasuryPda
+    );
+
+    expect(Number(finalPlatformBalance - initialPlatformBalance)).toBe(
+      LAMPORTS_PER_SOL / 10
+    );
+  });
+
+  it("should reject migrating a deployment that already uses bps", async () => {
+    await expectError(migrateFeeToBps(), "FeeAlreadyMigrated");
+  });
+
+  it("should convert legacy whole-percent fees to bps once", async () => {
+    const [feeHistoryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_history")],
+      crowdfundingAddress
+    );
+    const savedState = (await context.banksClient.getAccount(programStatePda))!;
+    const savedHistory = (await context.banksClient.getAccount(feeHistoryPda))!;
+
+    const cid = await createCampaign(bpsCreator);
+    const campaignPda = findCampaignPda(cid);
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      campaignPda
+    );
+    await overwriteAccount(campaignPda, "Campaign", {
+      ...campaign,
+      feeOverride: new anchor.BN(2),
+    });
+
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    await overwriteAccount(programStatePda, "ProgramState", {
+      ...programState,
+      platformFeeBps: new anchor.BN(5),
+      feeInBps: false,
+    });
+
+    await migrateFeeToBps([campaignPda]);
+
+    const migratedState =
+      await crowdfundingProgram.account.programState.fetch(programStatePda);
+    expect(migratedState.platformFeeBps.toNumber()).toBe(500);
+    expect(migratedState.feeInBps).toBe(true);
+
+    const migratedCampaign = await crowdfundingProgram.account.campaign.fetch(
+      campaignPda
+    );
+    expect(migratedCampaign.feeOverride?.toNumber()).toBe(200);
+
+    await expectError(migrateFeeToBps([campaignPda]), "FeeAlreadyMigrated");
+
+    context.setAccount(programStatePda, savedState);
+    context.setAccount(feeHistoryPda, savedHistory);
+  });
+});
+
+describe("Close Campaign", () => {
+  let closeCreator: Keypair;
+  let closeDonor: Keypair;
+
+  const closeCampaign = (
+    cid: anchor.BN,
+    matchPool: PublicKey | null = null,
+    sponsor: PublicKey | null = null
+  ) =>
+    crowdfundingProgram.methods
+      .closeCampaign(cid)
+      .accounts({
+        creator: closeCreator.publicKey,
+        matchPool,
+        sponsor,
+      })
+      .signers([closeCreator])
+      .rpc();
+
+  const refund = (cid: anchor.BN, donor: Keypair, index: number) =>
+    crowdfundingProgram.methods
+      .refund(cid, new anchor.BN(index))
+      .accounts({
+        donor: donor.publicKey,
+        caller: closeCreator.publicKey,
+      })
+      .signers([closeCreator])
+      .rpc();
+
+  const deleteCampaign = (cid: anchor.BN) =>
+    crowdfundingProgram.methods
+      .deleteCampaign(cid)
+      .accounts({
+        creator: closeCreator.publicKey,
+      })
+      .signers([closeCreator])
+      .rpc();
+
+  beforeAll(async () => {
+    closeCreator = await newFundedKeypair();
+    closeDonor = await newFundedKeypair();
+  });
+
+  it("should reject closing an active campaign", async () => {
+    const cid = await createCampaign(closeCreator);
+    await expectError(closeCampaign(cid), "CampaignNotSettled");
+  });
+
+  it("should reject closing while donor funds remain", async () => {
+    const cid = await createCampaign(closeCreator);
+    await donate(closeDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+    await deleteCampaign(cid);
+
+    await expectError(closeCampaign(cid), "CampaignBalanceNotEmpty");
+  });
+
+  it("should return the rent once all usable funds are withdrawn", async () => {
+    const cid = await createCampaign(closeCreator);
+    const campaignPda = findCampaignPda(cid);
+    const amount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+    await donate(closeDonor, cid, amount);
+    await withdraw(closeCreator, cid, amount);
+    await deleteCampaign(cid);
+
+    const rent = await context.banksClient.getBalance(campaignPda);
+    const initialCreatorBalance = await context.banksClient.getBalance(
+      closeCreator.publicKey
+    );
+
+    await closeCampaign(cid);
+
+    const finalCreatorBalance = await context.banksClient.getBalance(
+      closeCreator.publicKey
+    );
+    expect(finalCreatorBalance - initialCreatorBalance).toBe(rent);
+    expect(await context.banksClient.getAccount(campaignPda)).toBeNull();
+  });
+
+  it("should close an expired campaign once its donors are refunded", async () => {
+    const deadline = (await currentTime()) + campaignDuration;
+    const cid = await createCampaign(closeCreator, {
+      deadline: new anchor.BN(deadline),
+    });
+    await donate(closeDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    await warpTo(deadline + 1);
+    await crowdfundingProgram.methods
+      .expireCampaign(cid)
+      .accounts({
+        caller: closeCreator.publicKey,
+      })
+      .signers([closeCreator])
+      .rpc();
+    await refund(cid, closeDonor, 1);
+
+    await closeCampaign(cid);
+    expect(
+      await context.banksClient.getAccount(findCampaignPda(cid))
+    ).toBeNull();
+  });
+
+  it("should return leftover match money to the sponsor", async () => {
+    const sponsor = await newFundedKeypair();
+    const cid = await createCampaign(closeCreator);
+    const matchPool = PublicKey.findProgramAddressSync(
+      [Buffer.from("match"), cid.toArrayLike(Buffer, "le", 8)],
+      crowdfundingAddress
+    )[0];
+    await crowdfundingProgram.methods
+      .fundMatchPool(
+        cid,
+        new anchor.BN(5 * LAMPORTS_PER_SOL),
+        new anchor.BN(10_000)
+      )
+      .accounts({
+        sponsor: sponsor.publicKey,
+      })
+      .signers([sponsor])
+      .rpc();
+
+    // the 2 SOL donation is matched 1:1, so 2 SOL stays behind after the refund
+    const amount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+    await donate(closeDonor, cid, amount, { matchPool });
+    await deleteCampaign(cid);
+    await refund(cid, closeDonor, 1);
+
+    await expectError(closeCampaign(cid), "CampaignBalanceNotEmpty");
+
+    const initialSponsorBalance = await context.banksClient.getBalance(
+      sponsor.publicKey
+    );
+    await closeCampaign(cid, matchPool, sponsor.publicKey);
+    const finalSponsorBalance = await context.banksClient.getBalance(
+      sponsor.publicKey
+    );
+    expect(Number(finalSponsorBalance - initialSponsorBalance)).toBe(
+      amount.toNumber()
+    );
+  });
+});
+
+describe("Donor Whitelist", () => {
+  let whitelistCreator: Keypair;
+  let listedDonor: Keypair;
+  let unlistedDonor: Keypair;
+  let cid: anchor.BN;
+
+  const findAllowedDonorPda = (donor: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("allowed"),
+        cid.toArrayLike(Buffer, "le", 8),
+        donor.toBuffer(),
+      ],
+      crowdfundingAddress
+    )[0];
+
+  const setWhitelist = (enabled: boolean) =>
+    crowdfundingProgram.methods
+      .setWhitelist(cid, enabled)
+      .accounts({
+        creator: whitelistCreator.publicKey,
+      })
+      .signers([whitelistCreator])
+      .rpc();
+
+  beforeAll(async () => {
+    whitelistCreator = await newFundedKeypair();
+    listedDonor = await newFundedKeypair();
+    unlistedDonor = await newFundedKeypair();
+    cid = await createCampaign(whitelistCreator, {
+      goal: new anchor.BN(20 * LAMPORTS_PER_SOL),
+    });
+
+    await crowdfundingProgram.methods
+      .addAllowedDonor(cid, listedDonor.publicKey)
+      .accounts({
+        creator: whitelistCreator.publicKey,
+      })
+      .signers([whitelistCreator])
+      .rpc();
+  });
+
+  it("should reject whitelist changes from anyone but the creator", async () => {
+    await expectError(
+      crowdfundingProgram.methods
+        .setWhitelist(cid, true)
+        .accounts({
+          creator: unlistedDonor.publicKey,
+        })
+        .signers([unlistedDonor])
+        .rpc(),
+      "Unauthorized"
+    );
+  });
+
+  it("should only accept listed donors while the whitelist is on", async () => {
+    await setWhitelist(true);
+
+    await expectError(
+      donate(unlistedDonor, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "DonorNotWhitelisted"
+    );
+
+    await donate(listedDonor, cid, new anchor.BN(LAMPORTS_PER_SOL), {
+      allowedDonor: findAllowedDonorPda(listedDonor.publicKey),
+    });
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.whitelistEnabled).toBe(true);
+    expect(campaign.amountRaised.toNumber()).toBe(LAMPORTS_PER_SOL);
+  });
+
+  it("should accept any donor once the whitelist is off", async () => {
+    await setWhitelist(false);
+
+    await donate(unlistedDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(2 * LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Anonymous Donations", () => {
+  it("should zero the donor in the event but keep the transaction owner", async () => {
+    const anonCreator = await newFundedKeypair();
+    const anonDonor = await newFundedKeypair();
+    const cid = await createCampaign(anonCreator);
+    const amount = new anchor.BN(LAMPORTS_PER_SOL);
+    const transaction = findDonationPda(
+      anonDonor.publicKey,
+      cid,
+      new anchor.BN(1)
+    );
+
+    const { events } = await crowdfundingProgram.methods
+      .donate(cid, amount, null, new anchor.BN(0), true)
+      .accounts({
+        donor: anonDonor.publicKey,
+        transaction,
+        matchPool: null,
+        priceFeed: null,
+        referralStats: null,
+        platformAddress: null,
+        allowedDonor: null,
+        beneficiary: null,
+        creatorProfile: null,
+      })
+      .signers([anonDonor])
+      .simulate();
+    const event = events.find((e) => e.name === "donationReceived")!.data;
+    expect(event.donor.toBase58()).toBe(PublicKey.default.toBase58());
+
+    await donate(anonDonor, cid, amount, { anonymous: true });
+
+    const record = await crowdfundingProgram.account.transaction.fetch(
+      transaction
+    );
+    expect(record.anonymous).toBe(true);
+    expect(record.owner.toBase58()).toBe(anonDonor.publicKey.toBase58());
+  });
+});
+
+describe("Withdrawal Pause", () => {
+  let pauseCreator: Keypair;
+  let pauseDonor: Keypair;
+  let cid: anchor.BN;
+
+  const setWithdrawalsPaused = (updater: Keypair, paused: boolean) =>
+    crowdfundingProgram.methods
+      .setWithdrawalsPaused(paused)
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  beforeAll(async () => {
+    pauseCreator = await newFundedKeypair();
+    pauseDonor = await newFundedKeypair();
+    cid = await createCampaign(pauseCreator);
+    await donate(pauseDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+  });
+
+  afterAll(async () => {
+    await setWithdrawalsPaused(deployer, false);
+  });
+
+  it("should reject pausing by a non-platform signer", async () => {
+    await expectError(setWithdrawalsPaused(pauseCreator, true), "Unauthorized");
+  });
+
+  it("should keep donations open while withdrawals are paused", async () => {
+    await setWithdrawalsPaused(deployer, true);
+
+    await donate(pauseDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+    await expectError(
+      withdraw(pauseCreator, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "WithdrawalsPaused"
+    );
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(3 * LAMPORTS_PER_SOL);
+  });
+
+  it("should allow withdrawals again once unpaused", async () => {
+    await setWithdrawalsPaused(deployer, false);
+
+    await withdraw(pauseCreator, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.withdrawnTotal.toNumber()).toBe(LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Soft Cap Withdrawal Gate", () => {
+  let gateCreator: Keypair;
+  let gateDonor: Keypair;
+  let cid: anchor.BN;
+
+  beforeAll(async () => {
+    gateCreator = await newFundedKeypair();
+    gateDonor = await newFundedKeypair();
+    cid = await createCampaign(gateCreator, {
+      goal: new anchor.BN(10 * LAMPORTS_PER_SOL),
+      softCap: new anchor.BN(3 * LAMPORTS_PER_SOL),
+      requireSoftCap: true,
+    });
+  });
+
+  it("should reject requiring a soft cap that is not set", async () => {
+    await expectError(
+      createCampaign(gateCreator, { requireSoftCap: true }),
+      "InvalidCapConfig"
+    );
+  });
+
+  it("should reject withdrawals below the soft cap", async () => {
+    await donate(gateDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+
+    await expectError(
+      withdraw(gateCreator, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "SoftCapNotReached"
+    );
+  });
+
+  it("should allow withdrawals once the soft cap is met", async () => {
+    await donate(gateDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+    await withdraw(gateCreator, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(3 * LAMPORTS_PER_SOL);
+    expect(campaign.withdrawnTotal.toNumber()).toBe(LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Record Slots", () => {
+  it("should stamp campaigns, donations and withdrawals with the current slot", async () => {
+    const slotCreator = await newFundedKeypair();
+    const slotDonor = await newFundedKeypair();
+
+    const { slot: startSlot } = await context.banksClient.getClock();
+    context.warpToSlot(startSlot + BigInt(5));
+    const { slot } = await context.banksClient.getClock();
+
+    const cid = await createCampaign(slotCreator);
+    const donation = await donate(
+      slotDonor,
+      cid,
+      new anchor.BN(2 * LAMPORTS_PER_SOL)
+    );
+    const withdrawal = await withdraw(
+      slotCreator,
+      cid,
+      new anchor.BN(LAMPORTS_PER_SOL)
+    );
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    const donationRecord = await crowdfundingProgram.account.transaction.fetch(
+      donation
+    );
+    const withdrawalRecord =
+      await crowdfundingProgram.account.transaction.fetch(withdrawal);
+
+    expect(slot).toBeGreaterThan(BigInt(0));
+    expect(campaign.createdSlot.toString()).toBe(slot.toString());
+    expect(donationRecord.slot.toString()).toBe(slot.toString());
+    expect(withdrawalRecord.slot.toString()).toBe(slot.toString());
+  });
+});
+
+describe("Per-Donor Cap", () => {
+  let capCreator: Keypair;
+  let capDonor: Keypair;
+  let cid: anchor.BN;
+
+  beforeAll(async () => {
+    capCreator = await newFundedKeypair();
+    capDonor = await newFundedKeypair();
+    cid = await createCampaign(capCreator, {
+      goal: new anchor.BN(20 * LAMPORTS_PER_SOL),
+    });
+
+    await crowdfundingProgram.methods
+      .setMaxPerDonor(cid, new anchor.BN(3 * LAMPORTS_PER_SOL))
+      .accounts({
+        creator: capCreator.publicKey,
+      })
+      .signers([capCreator])
+      .rpc();
+  });
+
+  it("should accept donations up to the cap", async () => {
+    await donate(capDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+    await donate(capDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.maxPerDonor.toNumber()).toBe(3 * LAMPORTS_PER_SOL);
+    expect(campaign.amountRaised.toNumber()).toBe(3 * LAMPORTS_PER_SOL);
+  });
+
+  it("should reject donations beyond the cap", async () => {
+    await expectError(
+      donate(capDonor, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "DonorCapExceeded"
+    );
+  });
+});
+
+describe("Partial Campaign Updates", () => {
+  let partialCreator: Keypair;
+  let cid: anchor.BN;
+
+  const updatePartial = (
+    signer: Keypair,
+    {
+      title = null as string | null,
+      description = null as string | null,
+      imageUrl = null as string | null,
+      goal = null as anchor.BN | null,
+    } = {}
+  ) =>
+    crowdfundingProgram.methods
+      .updateCampaignPartial(cid, title, description, imageUrl, goal)
+      .accounts({
+        creator: signer.publicKey,
+      })
+      .signers([signer])
+      .rpc();
+
+  beforeAll(async () => {
+    partialCreator = await newFundedKeypair();
+    cid = await createCampaign(partialCreator);
+  });
+
+  it("should update only the goal and keep the other fields", async () => {
+    const before = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    const newGoal = new anchor.BN(7 * LAMPORTS_PER_SOL);
+
+    await updatePartial(partialCreator, { goal: newGoal });
+
+    const after = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(after.goal.toString()).toBe(newGoal.toString());
+    expect(after.title).toBe(before.title);
+    expect(after.description).toBe(before.description);
+    expect(after.imageUrl).toBe(before.imageUrl);
+  });
+
+  it("should validate the provided fields", async () => {
+    await expectError(
+      updatePartial(partialCreator, { title: "x".repeat(65) }),
+      "TitleTooLong"
+    );
+    await expectError(
+      updatePartial(partialCreator, { goal: new anchor.BN(1) }),
+      "InvalidGoalAmount"
+    );
+  });
+
+  it("should reject updates from anyone but the creator", async () => {
+    const stranger = await newFundedKeypair();
+    await expectError(
+      updatePartial(stranger, { title: "Hijacked" }),
+      "Unauthorized"
+    );
+  });
+});
+
+describe("Treasury", () => {
+  let treasuryCreator: Keypair;
+  let treasuryDonor: Keypair;
+
+  const withdrawTreasury = (updater: Keypair, amount: anchor.BN) =>
+    crowdfundingProgram.methods
+      .withdrawTreasury(amount)
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  beforeAll(async () => {
+    treasuryCreator = await newFundedKeypair();
+    treasuryDonor = await newFundedKeypair();
+  });
+
+  it("should collect withdrawal fees in the treasury", async () => {
+    const cid = await createCampaign(treasuryCreator);
+    const amount = new anchor.BN(4 * LAMPORTS_PER_SOL);
+    await donate(treasuryDonor, cid, amount);
+
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const before = await crowdfundingProgram.account.treasury.fetch(
+      treasuryPda
+    );
+    const lamportsBefore = await context.banksClient.getBalance(treasuryPda);
+    const platformBefore = await context.banksClient.getBalance(
+      deployer.publicKey
+    );
+
+    await withdraw(treasuryCreator, cid, amount);
+
+    const expectedFee = amount
+      .mul(programState.platformFeeBps)
+      .div(new anchor.BN(10_000));
+    const after = await crowdfundingProgram.account.treasury.fetch(
+      treasuryPda
+    );
+    const lamportsAfter = await context.banksClient.getBalance(treasuryPda);
+    const platformAfter = await context.banksClient.getBalance(
+      deployer.publicKey
+    );
+
+    expect(after.totalFeesCollected.sub(before.totalFeesCollected).toString()).toBe(
+      expectedFee.toString()
+    );
+    expect((lamportsAfter - lamportsBefore).toString()).toBe(
+      expectedFee.toString()
+    );
+    expect(platformAfter).toBe(platformBefore);
+  });
+
+  it("should only let the platform address sweep the treasury", async () => {
+    await expectError(
+      withdrawTreasury(treasuryCreator, new anchor.BN(LAMPORTS_PER_SOL / 10)),
+      "Unauthorized"
+    );
+  });
+
+  it("should reject sweeping into the rent reserve", async () => {
+    const lamports = await context.banksClient.getBalance(treasuryPda);
+    await expectError(
+      withdrawTreasury(deployer, new anchor.BN(lamports.toString())),
+      "InsufficientFund"
+    );
+  });
+
+  it("should sweep accrued fees to the platform address", async () => {
+    const sweep = new anchor.BN(LAMPORTS_PER_SOL / 10);
+    const before = await crowdfundingProgram.account.treasury.fetch(
+      treasuryPda
+    );
+    const platformBefore = await context.banksClient.getBalance(
+      deployer.publicKey
+    );
+
+    await withdrawTreasury(deployer, sweep);
+
+    const after = await crowdfundingProgram.account.treasury.fetch(
+      treasuryPda
+    );
+    const platformAfter = await context.banksClient.getBalance(
+      deployer.publicKey
+    );
+    expect(Number(platformAfter - platformBefore)).toBe(sweep.toNumber());
+    expect(after.totalSwept.sub(before.totalSwept).toNumber()).toBe(
+      sweep.toNumber()
+    );
+  });
+});
+
+describe("Display Metadata", () => {
+  let displayCreator: Keypair;
+
+  beforeAll(async () => {
+    displayCreator = await newFundedKeypair();
+  });
+
+  it("should default to 9 decimals and SOL", async () => {
+    const cid = await createCampaign(displayCreator);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.displayDecimals).toBe(9);
+    expect(campaign.currencySymbol).toBe("SOL");
+  });
+
+  it("should persist custom display values", async () => {
+    const cid = await createCampaign(displayCreator, {
+      displayDecimals: 2,
+      currencySymbol: "EUR",
+    });
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.displayDecimals).toBe(2);
+    expect(campaign.currencySymbol).toBe("EUR");
+  });
+
+  it("should reject invalid display values", async () => {
+    await expectError(
+      createCampaign(displayCreator, { displayDecimals: 10 }),
+      "InvalidDisplayDecimals"
+    );
+    await expectError(
+      createCampaign(displayCreator, { currencySymbol: "TOOLONGSYM" }),
+      "InvalidCurrencySymbol"
+    );
+  });
+});
+
+describe("Cancellation Refunds", () => {
+  let cancelCreator: Keypair;
+  let cancelDonor: Keypair;
+  let cid: anchor.BN;
+  const donationAmount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+  beforeAll(async () => {
+    cancelCreator = await newFundedKeypair();
+    cancelDonor = await newFundedKeypair();
+    cid = await createCampaign(cancelCreator);
+    await donate(cancelDonor, cid, donationAmount);
+  });
+
+  it("should open refunds and emit RefundsEnabled on cancel", async () => {
+    const { events } = await crowdfundingProgram.methods
+      .deleteCampaign(cid)
+      .accounts({
+        creator: cancelCreator.publicKey,
+      })
+      .signers([cancelCreator])
+      .simulate();
+    const event = events.find((e) => e.name === "refundsEnabled")!.data;
+    expect(event.cid.toNumber()).toBe(cid.toNumber());
+    expect(event.balance.toNumber()).toBe(donationAmount.toNumber());
+
+    await crowdfundingProgram.methods
+      .deleteCampaign(cid)
+      .accounts({
+        creator: cancelCreator.publicKey,
+      })
+      .signers([cancelCreator])
+      .rpc();
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.refundsOpen).toBe(true);
+  });
+
+  it("should block the creator from withdrawing", async () => {
+    await expectError(
+      withdraw(cancelCreator, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "RefundsOpen"
+    );
+  });
+
+  it("should let the donor be refunded", async () => {
+    const initialDonorBalance = await context.banksClient.getBalance(
+      cancelDonor.publicKey
+    );
+
+    await crowdfundingProgram.methods
+      .refund(cid, new anchor.BN(1))
+      .accounts({
+        donor: cancelDonor.publicKey,
+        caller: cancelCreator.publicKey,
+      })
+      .signers([cancelCreator])
+      .rpc();
+
+    const finalDonorBalance = await context.banksClient.getBalance(
+      cancelDonor.publicKey
+    );
+    expect(Number(finalDonorBalance - initialDonorBalance)).toBe(
+      donationAmount.toNumber()
+    );
+  });
+
+  it("should block withdrawing once a campaign expires unfunded", async () => {
+    const deadline = (await currentTime()) + campaignDuration;
+    const expiredCid = await createCampaign(cancelCreator, {
+      deadline: new anchor.BN(deadline),
+    });
+    await donate(cancelDonor, expiredCid, donationAmount);
+
+    await warpTo(deadline + 1);
+    await crowdfundingProgram.methods
+      .expireCampaign(expiredCid)
+      .accounts({
+        caller: cancelDonor.publicKey,
+      })
+      .signers([cancelDonor])
+      .rpc();
+
+    await expectError(
+      withdraw(cancelCreator, expiredCid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "RefundsOpen"
+    );
+  });
+});
+
+describe("Admin Deactivation", () => {
+  let moderatedCreator: Keypair;
+  let randomSigner: Keypair;
+  let cid: anchor.BN;
+
+  const adminDeactivate = (updater: Keypair) =>
+    crowdfundingProgram.methods
+      .adminDeactivateCampaign(cid)
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  beforeAll(async () => {
+    moderatedCreator = await newFundedKeypair();
+    randomSigner = await newFundedKeypair();
+    cid = await createCampaign(moderatedCreator);
+  });
+
+  it("should reject deactivation by a random signer", async () => {
+    await expectError(adminDeactivate(randomSigner), "Unauthorized");
+  });
+
+  it("should let the platform address deactivate someone else's campaign", async () => {
+    const { events } = await crowdfundingProgram.methods
+      .adminDeactivateCampaign(cid)
+      .accounts({
+        updater: deployer.publicKey,
+      })
+      .signers([deployer])
+      .simulate();
+    const event = events.find(
+      (e) => e.name === "campaignDeactivatedByAdmin"
+    )!.data;
+    expect(event.creator.toBase58()).toBe(
+      moderatedCreator.publicKey.toBase58()
+    );
+    expect(event.admin.toBase58()).toBe(deployer.publicKey.toBase58());
+
+    await adminDeactivate(deployer);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.active).toBe(false);
+    expect(campaign.status).toEqual({ cancelled: {} });
+    expect(campaign.refundsOpen).toBe(true);
+  });
+});
+
+describe("Proportional Refunds", () => {
+  let proCreator: Keypair;
+  let proDonors: Keypair[];
+  let cid: anchor.BN;
+  const donations = [2, 2, 4].map(
+    (sol) => new anchor.BN(sol * LAMPORTS_PER_SOL)
+  );
+
+  const refundProportional = (index: number) =>
+    crowdfundingProgram.methods
+      .refundProportional(cid, new anchor.BN(index))
+      .accounts({
+        donor: proDonors[index - 1].publicKey,
+        caller: proCreator.publicKey,
+      })
+      .signers([proCreator])
+      .rpc();
+
+  const refundableAmount = (index: number) =>
+    crowdfundingProgram.methods
+      .refundableAmount(cid, [new anchor.BN(index)])
+      .accounts({
+        donor: proDonors[index - 1].publicKey,
+      })
+      .remainingAccounts([
+        {
+          pubkey: findDonationPda(
+            proDonors[index - 1].publicKey,
+            cid,
+            new anchor.BN(index)
+          ),
+          isWritable: false,
+          isSigner: false,
+        },
+      ])
+      .view();
+
+  beforeAll(async () => {
+    proCreator = await newFundedKeypair();
+    proDonors = [
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+    ];
+    cid = await createCampaign(proCreator, {
+      goal: new anchor.BN(20 * LAMPORTS_PER_SOL),
+    });
+
+    for (let i = 0; i < donations.length; i++) {
+      await donate(proDonors[i], cid, donations[i]);
+    }
+
+    // half of the 8 SOL pot leaves before the campaign is cancelled
+    await withdraw(proCreator, cid, new anchor.BN(4 * LAMPORTS_PER_SOL));
+    await crowdfundingProgram.methods
+      .deleteCampaign(cid)
+      .accounts({
+        creator: proCreator.publicKey,
+      })
+      .signers([proCreator])
+      .rpc();
+  });
+
+  it("should pay every donor the same share of what is left", async () => {
+    // claims do not have to follow donation order
+    for (const index of [3, 1, 2]) {
+      const before = await context.banksClient.getBalance(
+        proDonors[index - 1].publicKey
+      );
+      await refundProportional(index);
+      const after = await context.banksClient.getBalance(
+        proDonors[index - 1].publicKey
+      );
+
+      expect(Number(after - before)).toBe(
+        donations[index - 1].divn(2).toNumber()
+      );
+
+      // the view quotes the remaining claims at the same fixed ratio
+      if (index === 3) {
+        const quoted = await refundableAmount(1);
+        expect(quoted.toNumber()).toBe(donations[0].divn(2).toNumber());
+      }
+    }
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.balance.toNumber()).toBe(0);
+    expect(campaign.refundedTotal.toNumber()).toBe(4 * LAMPORTS_PER_SOL);
+  });
+
+  it("should not refund the same donation twice", async () => {
+    await expectError(refundProportional(1), "AlreadyRefunded");
+  });
+
+  it("should not reactivate once proportional refunds start", async () => {
+    await expectError(
+      crowdfundingProgram.methods
+        .reactivateCampaign(cid)
+        .accounts({
+          creator: proCreator.publicKey,
+        })
+        .signers([proCreator])
+        .rpc(),
+      "RefundsInProgress"
+    );
+  });
+
+  it("should leave sponsor matches out of the refund ratio", async () => {
+    const sponsor = await newFundedKeypair();
+    const donor = await newFundedKeypair();
+    const matchedCid = await createCampaign(proCreator, {
+      goal: new anchor.BN(20 * LAMPORTS_PER_SOL),
+    });
+    await crowdfundingProgram.methods
+      .fundMatchPool(
+        matchedCid,
+        new anchor.BN(10 * LAMPORTS_PER_SOL),
+        new anchor.BN(10_000)
+      )
+      .accounts({
+        sponsor: sponsor.publicKey,
+      })
+      .signers([sponsor])
+      .rpc();
+
+    // 2 SOL donated and matched 1:1, then 3 of the 4 SOL pot is withdrawn
+    await donate(donor, matchedCid, new anchor.BN(2 * LAMPORTS_PER_SOL), {
+      matchPool: PublicKey.findProgramAddressSync(
+        [Buffer.from("match"), matchedCid.toArrayLike(Buffer, "le", 8)],
+        crowdfundingAddress
+      )[0],
+    });
+    await withdraw(proCreator, matchedCid, new anchor.BN(3 * LAMPORTS_PER_SOL));
+    await crowdfundingProgram.methods
+      .deleteCampaign(matchedCid)
+      .accounts({
+        creator: proCreator.publicKey,
+      })
+      .signers([proCreator])
+      .rpc();
+
+    const before = await context.banksClient.getBalance(donor.publicKey);
+    await crowdfundingProgram.methods
+      .refundProportional(matchedCid, new anchor.BN(1))
+      .accounts({
+        donor: donor.publicKey,
+        caller: proCreator.publicKey,
+      })
+      .signers([proCreator])
+      .rpc();
+    const after = await context.banksClient.getBalance(donor.publicKey);
+
+    // the donor is owed 2 SOL and the 1 SOL left all goes to them
+    expect(Number(after - before)).toBe(LAMPORTS_PER_SOL);
+  });
+
+  it("should pay the floored dust to the last claimant", async () => {
+    const donors = [
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+    ];
+    const dustCid = await createCampaign(proCreator, {
+      goal: new anchor.BN(20 * LAMPORTS_PER_SOL),
+    });
+    for (const donor of donors) {
+      await donate(donor, dustCid, new anchor.BN(LAMPORTS_PER_SOL));
+    }
+
+    // 1 SOL left for 3 SOL of donations does not split evenly
+    await withdraw(proCreator, dustCid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+    await crowdfundingProgram.methods
+      .deleteCampaign(dustCid)
+      .accounts({
+        creator: proCreator.publicKey,
+      })
+      .signers([proCreator])
+      .rpc();
+
+    let paid = 0;
+    for (let index = 1; index <= donors.length; index++) {
+      const before = await context.banksClient.getBalance(
+        donors[index - 1].publicKey
+      );
+      await crowdfundingProgram.methods
+        .refundProportional(dustCid, new anchor.BN(index))
+        .accounts({
+          donor: donors[index - 1].publicKey,
+          caller: proCreator.publicKey,
+        })
+        .signers([proCreator])
+        .rpc();
+      const after = await context.banksClient.getBalance(
+        donors[index - 1].publicKey
+      );
+      paid += Number(after - before);
+    }
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(dustCid)
+    );
+    expect(campaign.balance.toNumber()).toBe(0);
+    expect(paid).toBe(LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Auto-Forward Donations", () => {
+  let forwardCreator: Keypair;
+  let forwardBeneficiary: Keypair;
+  let forwardDonor: Keypair;
+  let cid: anchor.BN;
+
+  beforeAll(async () => {
+    forwardCreator = await newFundedKeypair();
+    forwardBeneficiary = await newFundedKeypair();
+    forwardDonor = await newFundedKeypair();
+    cid = await createCampaign(forwardCreator, {
+      beneficiary: forwardBeneficiary.publicKey,
+      autoForward: true,
+    });
+  });
+
+  it("should forward each donation minus the fee to the beneficiary", async () => {
+    const amount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+    const state = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const fee = amount
+      .muln(state.platformFeeBps.toNumber())
+      .addn(9_999)
+      .divn(10_000);
+
+    const beneficiaryBefore = await context.banksClient.getBalance(
+      forwardBeneficiary.publicKey
+    );
+    const treasuryBefore = await context.banksClient.getBalance(treasuryPda);
+
+    await donate(forwardDonor, cid, amount, {
+      beneficiary: forwardBeneficiary.publicKey,
+    });
+
+    const beneficiaryAfter = await context.banksClient.getBalance(
+      forwardBeneficiary.publicKey
+    );
+    const treasuryAfter = await context.banksClient.getBalance(treasuryPda);
+    expect(Number(beneficiaryAfter - beneficiaryBefore)).toBe(
+      amount.sub(fee).toNumber()
+    );
+    expect(Number(treasuryAfter - treasuryBefore)).toBe(fee.toNumber());
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(amount.toNumber());
+    expect(campaign.balance.toNumber()).toBe(0);
+    expect(campaign.donors.toNumber()).toBe(1);
+  });
+
+  it("should reject a donation without the beneficiary account", async () => {
+    await expectError(
+      donate(forwardDonor, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "InvalidBeneficiary"
+    );
+  });
+
+  it("should reject withdrawals", async () => {
+    await expectError(
+      withdraw(forwardCreator, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "AutoForwardEnabled"
+    );
+  });
+
+  it("should hold the donation that freezes the campaign", async () => {
+    const frozenCid = await createCampaign(forwardCreator, {
+      beneficiary: forwardBeneficiary.publicKey,
+      autoForward: true,
+    });
+    await crowdfundingProgram.methods
+      .setFreezeOnGoal(frozenCid, true)
+      .accounts({
+        creator: forwardCreator.publicKey,
+      })
+      .signers([forwardCreator])
+      .rpc();
+
+    const beneficiaryBefore = await context.banksClient.getBalance(
+      forwardBeneficiary.publicKey
+    );
+    await donate(forwardDonor, frozenCid, campaignGoal, {
+      beneficiary: forwardBeneficiary.publicKey,
+    });
+    const beneficiaryAfter = await context.banksClient.getBalance(
+      forwardBeneficiary.publicKey
+    );
+
+    // nothing moves until the platform has reviewed the campaign
+    expect(beneficiaryAfter).toBe(beneficiaryBefore);
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(frozenCid)
+    );
+    expect(campaign.frozen).toBe(true);
+    expect(campaign.balance.toNumber()).toBe(campaignGoal.toNumber());
+  });
+});
+
+describe("Campaign Migration", () => {
+  // mirrors CAMPAIGN_VERSION in the program
+  const campaignVersion = 10;
+  let migrateCreator: Keypair;
+  let cid: anchor.BN;
+
+  const migrateCampaign = () =>
+    crowdfundingProgram.methods
+      .migrateCampaign(cid)
+      .accounts({
+        payer: migrateCreator.publicKey,
+      })
+      .signers([migrateCreator])
+      .rpc();
+
+  beforeAll(async () => {
+    migrateCreator = await newFundedKeypair();
+    cid = await createCampaign(migrateCreator);
+  });
+
+  it("should stamp new campaigns with the current version", async () => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.version).toBe(campaignVersion);
+
+    await expectError(migrateCampaign(), "AlreadyMigrated");
+  });
+
+  it("should grow an old-layout campaign to the current layout", async () => {
+    const address = findCampaignPda(cid);
+    const account = (await context.banksClient.getAccount(address))!;
+    const campaign = await crowdfundingProgram.account.campaign.fetch(address);
+
+    // an older layout is the same data minus the most recently appended field
+    const encoded = await crowdfundingProgram.coder.accounts.encode(
+      "Campaign",
+      { ...campaign, version: 0 }
+    );
+    context.setAccount(address, {
+      ...account,
+      data: encoded.subarray(0, encoded.length - 1),
+    });
+
+    await migrateCampaign();
+
+    const migrated = (await context.banksClient.getAccount(address))!;
+    expect(migrated.data.length).toBe(account.data.length);
+
+    const upgraded = await crowdfundingProgram.account.campaign.fetch(address);
+    expect(upgraded.version).toBe(campaignVersion);
+    expect(upgraded.title).toBe(campaign.title);
+    expect(upgraded.goal.toNumber()).toBe(campaign.goal.toNumber());
+  });
+
+  it("should backfill fields an old layout never had", async () => {
+    const oldCid = await createCampaign(migrateCreator);
+    const address = findCampaignPda(oldCid);
+    const account = (await context.banksClient.getAccount(address))!;
+    const campaign = await crowdfundingProgram.account.campaign.fetch(address);
+
+    // a cancelled campaign from before beneficiaries, currencies and status
+    const encoded = await crowdfundingProgram.coder.accounts.encode(
+      "Campaign",
+      {
+        ...campaign,
+        version: 0,
+        active: false,
+        beneficiary: PublicKey.default,
+        displayDecimals: 0,
+        currencySymbol: "",
+        status: { active: {} },
+        refundsOpen: false,
+      }
+    );
+    context.setAccount(address, {
+      ...account,
+      data: encoded.subarray(0, encoded.length - 1),
+    });
+
+    await crowdfundingProgram.methods
+      .migrateCampaign(oldCid)
+      .accounts({
+        payer: migrateCreator.publicKey,
+      })
+      .signers([migrateCreator])
+      .rpc();
+
+    const upgraded = await crowdfundingProgram.account.campaign.fetch(address);
+    expect(upgraded.beneficiary.toBase58()).toBe(
+      migrateCreator.publicKey.toBase58()
+    );
+    expect(upgraded.displayDecimals).toBe(9);
+    expect(upgraded.currencySymbol).toBe("SOL");
+    expect(upgraded.status).toEqual({ cancelled: {} });
+    expect(upgraded.refundsOpen).toBe(true);
+  });
+});
+
+describe("Donation Leaderboard", () => {
+  let boardCreator: Keypair;
+  let boardDonors: Keypair[];
+  let cid: anchor.BN;
+
+  const fetchLeaderboard = () =>
+    crowdfundingProgram.account.leaderboard.fetch(
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("leaderboard"), cid.toArrayLike(Buffer, "le", 8)],
+        crowdfundingAddress
+      )[0]
+    );
+
+  beforeAll(async () => {
+    boardCreator = await newFundedKeypair();
+    boardDonors = [
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+    ];
+    cid = await createCampaign(boardCreator, {
+      goal: new anchor.BN(100 * LAMPORTS_PER_SOL),
+    });
+  });
+
+  it("should rank donors by their total contribution", async () => {
+    await donate(boardDonors[0], cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+    await donate(boardDonors[1], cid, new anchor.BN(5 * LAMPORTS_PER_SOL));
+    await donate(boardDonors[2], cid, new anchor.BN(3 * LAMPORTS_PER_SOL));
+
+    const leaderboard = await fetchLeaderboard();
+    expect(leaderboard.cid.toNumber()).toBe(cid.toNumber());
+    expect(leaderboard.entries.map((e) => e.donor.toBase58())).toEqual(
+      [1, 2, 0].map((i) => boardDonors[i].publicKey.toBase58())
+    );
+    expect(leaderboard.entries.map((e) => e.total.toNumber())).toEqual(
+      [5, 3, 2].map((sol) => sol * LAMPORTS_PER_SOL)
+    );
+  });
+
+  it("should move a donor up when their total grows", async () => {
+    await donate(boardDonors[0], cid, new anchor.BN(4 * LAMPORTS_PER_SOL));
+
+    const leaderboard = await fetchLeaderboard();
+    expect(leaderboard.entries.length).toBe(3);
+    expect(leaderboard.entries[0].donor.toBase58()).toBe(
+      boardDonors[0].publicKey.toBase58()
+    );
+    expect(leaderboard.entries[0].total.toNumber()).toBe(6 * LAMPORTS_PER_SOL);
+  });
+
+  it("should keep anonymous donations off the board", async () => {
+    const anonymousDonor = await newFundedKeypair();
+    await donate(anonymousDonor, cid, new anchor.BN(10 * LAMPORTS_PER_SOL), {
+      anonymous: true,
+    });
+
+    const leaderboard = await fetchLeaderboard();
+    expect(
+      leaderboard.entries.some(
+        (e) => e.donor.toBase58() === anonymousDonor.publicKey.toBase58()
+      )
+    ).toBe(false);
+  });
+});
+
+describe("Goal Reached Timestamp", () => {
+  let goalCreator: Keypair;
+  let goalDonor: Keypair;
+  let cid: anchor.BN;
+
+  beforeAll(async () => {
+    goalCreator = await newFundedKeypair();
+    goalDonor = await newFundedKeypair();
+    cid = await createCampaign(goalCreator, {
+      goal: new anchor.BN(3 * LAMPORTS_PER_SOL),
+    });
+  });
+
+  it("should stay zero until the goal is reached", async () => {
+    await donate(goalDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.goalReachedAt.toNumber()).toBe(0);
+  });
+
+  it("should record when the goal was first crossed", async () => {
+    const crossedAt = await currentTime();
+    await donate(goalDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+
+    let campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.goalReachedAt.toNumber()).toBe(crossedAt);
+
+    await warpTo(crossedAt + 3600);
+    await donate(goalDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+
+    campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.goalReachedAt.toNumber()).toBe(crossedAt);
+  });
+});
+
+describe("Force Platform Address", () => {
+  let interimPlatform: Keypair;
+
+  const forceSetPlatformAddress = (updater: Keypair, newAddress: PublicKey) =>
+    crowdfundingProgram.methods
+      .forceSetPlatformAddress(newAddress)
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater]);
+
+  beforeAll(async () => {
+    interimPlatform = await newFundedKeypair();
+  });
+
+  it("should reject callers other than the current platform address", async () => {
+    await expectError(
+      forceSetPlatformAddress(interimPlatform, interimPlatform.publicKey).rpc(),
+      "Unauthorized"
+    );
+  });
+
+  it("should reassign the platform address immediately", async () => {
+    const { events } = await forceSetPlatformAddress(
+      deployer,
+      interimPlatform.publicKey
+    ).simulate();
+    const event = events.find((e) => e.name === "platformAddressChanged")!.data;
+    expect(event.previousAddress.toBase58()).toBe(deployer.publicKey.toBase58());
+    expect(event.newAddress.toBase58()).toBe(
+      interimPlatform.publicKey.toBase58()
+    );
+
+    await forceSetPlatformAddress(deployer, interimPlatform.publicKey).rpc();
+
+    let state = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    expect(state.platformAddress.toBase58()).toBe(
+      interimPlatform.publicKey.toBase58()
+    );
+
+    // the old address lost its rights on the spot, the new one hands control back
+    await expectError(
+      forceSetPlatformAddress(deployer, deployer.publicKey).rpc(),
+      "Unauthorized"
+    );
+    await forceSetPlatformAddress(interimPlatform, deployer.publicKey).rpc();
+
+    state = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    expect(state.platformAddress.toBase58()).toBe(deployer.publicKey.toBase58());
+  });
+});
+
+(receiptHashEnabled ? describe : describe.skip)("Donation Receipts", () => {
+  it("should store a receipt hash clients can rebuild from the event", async () => {
+    const receiptCreator = await newFundedKeypair();
+    const receiptDonor = await newFundedKeypair();
+    const cid = await createCampaign(receiptCreator);
+    const amount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    const { events } = await crowdfundingProgram.methods
+      .donate(cid, amount, null, new anchor.BN(0), false)
+      .accounts({
+        donor: receiptDonor.publicKey,
+        transaction: findDonationPda(
+          receiptDonor.publicKey,
+          cid,
+          campaign.donors.add(new anchor.BN(1))
+        ),
+        matchPool: null,
+        priceFeed: null,
+        referralStats: null,
+        platformAddress: null,
+        allowedDonor: null,
+        beneficiary: null,
+        creatorProfile: null,
+      })
+      .signers([receiptDonor])
+      .simulate();
+    const event = events.find((e) => e.name === "donationReceived")!.data;
+
+    const transaction = await donate(receiptDonor, cid, amount);
+    const record = await crowdfundingProgram.account.transaction.fetch(
+      transaction
+    );
+
+    const expected = keccak_256(
+      Buffer.concat([
+        event.cid.toArrayLike(Buffer, "le", 8),
+        event.donor.toBuffer(),
+        event.amount.toArrayLike(Buffer, "le", 8),
+        event.timestamp.toArrayLike(Buffer, "le", 8),
+      ])
+    );
+    expect(Buffer.from(record.receiptHash)).toEqual(Buffer.from(expected));
+  });
+});
+
+describe("Stretch Goals", () => {
+  let stretchCreator: Keypair;
+  let stretchDonor: Keypair;
+
+  const raiseGoal = (cid: anchor.BN, newGoal: anchor.BN) =>
+    crowdfundingProgram.methods
+      .raiseGoal(cid, newGoal)
+      .accounts({
+        creator: stretchCreator.publicKey,
+      })
+      .signers([stretchCreator]);
+
+  beforeAll(async () => {
+    stretchCreator = await newFundedKeypair();
+    stretchDonor = await newFundedKeypair();
+  });
+
+  it("should resume donations once a funded goal is raised", async () => {
+    const cid = await createCampaign(stretchCreator, {
+      goal: new anchor.BN(2 * LAMPORTS_PER_SOL),
+    });
+    await donate(stretchDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+    await expectError(
+      donate(stretchDonor, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "CampaignGoalActualized"
+    );
+
+    const newGoal = new anchor.BN(5 * LAMPORTS_PER_SOL);
+    const { events } = await raiseGoal(cid, newGoal).simulate();
+    const event = events.find((e) => e.name === "goalRaised")!.data;
+    expect(event.previousGoal.toNumber()).toBe(2 * LAMPORTS_PER_SOL);
+    expect(event.newGoal.toNumber()).toBe(newGoal.toNumber());
+
+    await raiseGoal(cid, newGoal).rpc();
+    await donate(stretchDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.goal.toNumber()).toBe(newGoal.toNumber());
+    expect(campaign.amountRaised.toNumber()).toBe(3 * LAMPORTS_PER_SOL);
+  });
+
+  it("should never lower the goal after donations started", async () => {
+    const cid = await createCampaign(stretchCreator, {
+      goal: new anchor.BN(4 * LAMPORTS_PER_SOL),
+    });
+    await donate(stretchDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    await expectError(
+      raiseGoal(cid, new anchor.BN(3 * LAMPORTS_PER_SOL)).rpc(),
+      "InvalidGoalAmount"
+    );
+    await expectError(
+      crowdfundingProgram.methods
+        .updateCampaign(
+          cid,
+          campaignTitle,
+          campaignDescription,
+          campaignImageUrl,
+          new anchor.BN(3 * LAMPORTS_PER_SOL),
+          new anchor.BN(0),
+          []
+        )
+        .accounts({
+          creator: stretchCreator.publicKey,
+        })
+        .signers([stretchCreator])
+        .rpc(),
+      "GoalDecreaseNotAllowed"
+    );
+  });
+
+  it("should reopen a completed campaign", async () => {
+    const now = await currentTime();
+    const cid = await createCampaign(stretchCreator, {
+      goal: new anchor.BN(2 * LAMPORTS_PER_SOL),
+      deadline: new anchor.BN(now + 2 * 24 * 60 * 60),
+    });
+    await donate(stretchDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+
+    await warpTo(now + 3 * 24 * 60 * 60);
+    await crowdfundingProgram.methods
+      .expireCampaign(cid)
+      .accounts({
+        caller: stretchDonor.publicKey,
+      })
+      .signers([stretchDonor])
+      .rpc();
+
+    let campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.status).toEqual({ completed: {} });
+
+    await raiseGoal(cid, new anchor.BN(4 * LAMPORTS_PER_SOL)).rpc();
+
+    campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.status).toEqual({ active: {} });
+    expect(campaign.active).toBe(true);
+  });
+});
+
+describe("Batch Refunds", () => {
+  let batchCreator: Keypair;
+  let batchDonors: Keypair[];
+  const amounts = [1, 2, 3].map((sol) => new anchor.BN(sol * LAMPORTS_PER_SOL));
+
+  // (transaction, donor) pairs for donations 1..n, in donation order
+  const refundMany = (cid: anchor.BN, order: number[]) =>
+    crowdfundingProgram.methods
+      .refundMany(cid)
+      .accounts({
+        caller: batchCreator.publicKey,
+      })
+      .remainingAccounts(
+        order.flatMap((index) => [
+          {
+            pubkey: findDonationPda(
+              batchDonors[index - 1].publicKey,
+              cid,
+              new anchor.BN(index)
+            ),
+            isWritable: true,
+            isSigner: false,
+          },
+          {
+            pubkey: batchDonors[index - 1].publicKey,
+            isWritable: true,
+            isSigner: false,
+          },
+        ])
+      )
+      .signers([batchCreator])
+      .rpc();
+
+  const cancelledCampaign = async () => {
+    const cid = await createCampaign(batchCreator, {
+      goal: new anchor.BN(20 * LAMPORTS_PER_SOL),
+    });
+    for (let i = 0; i < batchDonors.length; i++) {
+      await donate(batchDonors[i], cid, amounts[i]);
+    }
+    await crowdfundingProgram.methods
+      .deleteCampaign(cid)
+      .accounts({
+        creator: batchCreator.publicKey,
+      })
+      .signers([batchCreator])
+      .rpc();
+    return cid;
+  };
+
+  beforeAll(async () => {
+    batchCreator = await newFundedKeypair();
+    batchDonors = [
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+      await newFundedKeypair(),
+    ];
+  });
+
+  it("should refund several donors in one transaction", async () => {
+    const cid = await cancelledCampaign();
+    const before = await Promise.all(
+      batchDonors.map((d) => context.banksClient.getBalance(d.publicKey))
+    );
+
+    await refundMany(cid, [1, 2, 3]);
+
+    for (let i = 0; i < batchDonors.length; i++) {
+      const after = await context.banksClient.getBalance(
+        batchDonors[i].publicKey
+      );
+      expect(Number(after - before[i])).toBe(amounts[i].toNumber());
+
+      const record = await crowdfundingProgram.account.transaction.fetch(
+        findDonationPda(batchDonors[i].publicKey, cid, new anchor.BN(i + 1))
+      );
+      expect(record.credited).toBe(false);
+    }
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.balance.toNumber()).toBe(0);
+    expect(campaign.refundedTotal.toNumber()).toBe(6 * LAMPORTS_PER_SOL);
+    expect(campaign.refundCursor.toNumber()).toBe(3);
+  });
+
+  it("should roll back the whole batch if one pair is invalid", async () => {
+    const cid = await cancelledCampaign();
+
+    await expectError(refundMany(cid, [1, 3]), "RefundOutOfOrder");
+
+    const record = await crowdfundingProgram.account.transaction.fetch(
+      findDonationPda(batchDonors[0].publicKey, cid, new anchor.BN(1))
+    );
+    expect(record.credited).toBe(true);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.refundCursor.toNumber()).toBe(0);
+    expect(campaign.balance.toNumber()).toBe(6 * LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Completion Receipts", () => {
+  let receiptCreator: Keypair;
+  let receiptDonor: Keypair;
+
+  const mintReceipt = (cid: anchor.BN, recipient: PublicKey) =>
+    crowdfundingProgram.methods
+      .mintCompletionReceipt(cid)
+      .accounts({
+        creator: receiptCreator.publicKey,
+        recipient,
+      })
+      .signers([receiptCreator])
+      .rpc();
+
+  const findReceiptMintPda = (cid: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("receipt_mint"), cid.toArrayLike(Buffer, "le", 8)],
+      crowdfundingAddress
+    )[0];
+
+  beforeAll(async () => {
+    receiptCreator = await newFundedKeypair();
+    receiptDonor = await newFundedKeypair();
+  });
+
+  it("should reject minting before the campaign completes", async () => {
+    const cid = await createCampaign(receiptCreator);
+
+    await expectError(
+      mintReceipt(cid, receiptDonor.publicKey),
+      "CampaignNotFinished"
+    );
+  });
+
+  it("should mint exactly one receipt after completion", async () => {
+    const now = await currentTime();
+    const cid = await createCampaign(receiptCreator, {
+      goal: new anchor.BN(2 * LAMPORTS_PER_SOL),
+      deadline: new anchor.BN(now + 2 * 24 * 60 * 60),
+    });
+    await donate(receiptDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+
+    await warpTo(now + 3 * 24 * 60 * 60);
+    await crowdfundingProgram.methods
+      .expireCampaign(cid)
+      .accounts({
+        caller: receiptDonor.publicKey,
+      })
+      .signers([receiptDonor])
+      .rpc();
+
+    await mintReceipt(cid, receiptDonor.publicKey);
+
+    const tokenAccount = await context.banksClient.getAccount(
+      getAssociatedTokenAddressSync(
+        findReceiptMintPda(cid),
+        receiptDonor.publicKey
+      )
+    );
+    expect(AccountLayout.decode(tokenAccount!.data).amount).toBe(BigInt(1));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.receiptMinted).toBe(true);
+
+    // the receipt mint already exists, so a second attempt fails on init
+    await expect(mintReceipt(cid, receiptCreator.publicKey)).rejects.toThrow();
+  });
+});
+
+describe("Perk Tiers", () => {
+  let perkCreator: Keypair;
+  let cid: anchor.BN;
+  const thresholds = [2, 5, 10].map(
+    (sol) => new anchor.BN(sol * LAMPORTS_PER_SOL)
+  );
+
+  const setPerkThresholds = (perkThresholds: anchor.BN[]) =>
+    crowdfundingProgram.methods
+      .setPerkThresholds(cid, perkThresholds)
+      .accounts({
+        creator: perkCreator.publicKey,
+      })
+      .signers([perkCreator])
+      .rpc();
+
+  const tierFor = async (sol: number) => {
+    const donor = await newFundedKeypair();
+    await donate(donor, cid, new anchor.BN(sol * LAMPORTS_PER_SOL));
+
+    const { events } = await crowdfundingProgram.methods
+      .checkPerkTier(cid)
+      .accounts({
+        donor: donor.publicKey,
+      })
+      .signers([donor])
+      .simulate();
+    const event = events.find((e) => e.name === "perkTier")!.data;
+    expect(event.donor.toBase58()).toBe(donor.publicKey.toBase58());
+    return event.tier;
+  };
+
+  beforeAll(async () => {
+    perkCreator = await newFundedKeypair();
+    cid = await createCampaign(perkCreator, {
+      goal: new anchor.BN(100 * LAMPORTS_PER_SOL),
+    });
+    await setPerkThresholds(thresholds);
+  });
+
+  it("should reject thresholds that are not ascending", async () => {
+    await expectError(
+      setPerkThresholds([thresholds[1], thresholds[0]]),
+      "InvalidPerkThresholds"
+    );
+  });
+
+  it("should report no tier below the first threshold", async () => {
+    expect(await tierFor(1)).toBe(0);
+  });
+
+  it("should count a threshold the donor reached exactly", async () => {
+    expect(await tierFor(5)).toBe(2);
+  });
+
+  it("should report the top tier above the last threshold", async () => {
+    expect(await tierFor(12)).toBe(3);
+  });
+});
+
+describe("Donation Record Addresses", () => {
+  it("should give repeat donations from one wallet distinct records", async () => {
+    const repeatCreator = await newFundedKeypair();
+    const repeatDonor = await newFundedKeypair();
+    const cid = await createCampaign(repeatCreator);
+
+    const first = await donate(repeatDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+    const second = await donate(
+      repeatDonor,
+      cid,
+      new anchor.BN(2 * LAMPORTS_PER_SOL)
+    );
+    expect(first.toBase58()).not.toBe(second.toBase58());
+
+    const records = await Promise.all(
+      [first, second].map((address) =>
+        crowdfundingProgram.account.transaction.fetch(address)
+      )
+    );
+    expect(records.map((r) => r.amount.toNumber())).toEqual([
+      LAMPORTS_PER_SOL,
+      2 * LAMPORTS_PER_SOL,
+    ]);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.donors.toNumber()).toBe(2);
+    expect(campaign.uniqueDonors.toNumber()).toBe(1);
+  });
+});
+
+describe("Campaign Media", () => {
+  let mediaCreator: Keypair;
+  let cid: anchor.BN;
+  const media = [
+    "https://example.com/cover.mp4",
+    "https://example.com/gallery-1.jpg",
+  ];
+
+  const updateMedia = (updater: Keypair, mediaUrls: string[]) =>
+    crowdfundingProgram.methods
+      .updateMedia(cid, mediaUrls)
+      .accounts({
+        creator: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  beforeAll(async () => {
+    mediaCreator = await newFundedKeypair();
+    cid = await createCampaign(mediaCreator, { mediaUrls: media.slice(0, 1) });
+  });
+
+  it("should store media set at creation", async () => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.mediaUrls).toEqual(media.slice(0, 1));
+  });
+
+  it("should let the creator replace the media", async () => {
+    await updateMedia(mediaCreator, media);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.mediaUrls).toEqual(media);
+  });
+
+  it("should reject more than four media URLs", async () => {
+    await expectError(
+      updateMedia(mediaCreator, [...media, ...media, media[0]]),
+      "TooManyMediaUrls"
+    );
+  });
+
+  it("should reject non-https media URLs", async () => {
+    await expectError(
+      updateMedia(mediaCreator, ["http://example.com/cover.mp4"]),
+      "InvalidMediaUrl"
+    );
+  });
+
+  it("should reject media updates from anyone but the creator", async () => {
+    const stranger = await newFundedKeypair();
+    await expectError(updateMedia(stranger, media), "Unauthorized");
+  });
+});
+
+describe("Minimum Share Of Remaining Goal", () => {
+  let shareCreator: Keypair;
+  let shareDonor: Keypair;
+  let cid: anchor.BN;
+
+  const setMinDonationBps = (bps: number) =>
+    crowdfundingProgram.methods
+      .setMinDonationBps(cid, new anchor.BN(bps))
+      .accounts({
+        creator: shareCreator.publicKey,
+      })
+      .signers([shareCreator])
+      .rpc();
+
+  beforeAll(async () => {
+    shareCreator = await newFundedKeypair();
+    shareDonor = await newFundedKeypair();
+    cid = await createCampaign(shareCreator, {
+      goal: new anchor.BN(20 * LAMPORTS_PER_SOL),
+    });
+    await donate(shareDonor, cid, new anchor.BN(16 * LAMPORTS_PER_SOL));
+  });
+
+  it("should reject a share above 100%", async () => {
+    await expectError(setMinDonationBps(10_001), "InvalidMinDonationBps");
+  });
+
+  it("should reject a donation below the share of what is left", async () => {
+    // 4 SOL remaining at 50% requires at least 2 SOL
+    await setMinDonationBps(5_000);
+
+    await expectError(
+      donate(shareDonor, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "DonationBelowRemainingShare"
+    );
+  });
+
+  it("should accept a donation that covers the share", async () => {
+    await donate(shareDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(18 * LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Withdrawal Ordering", () => {
+  it("should leave campaign state untouched when the payout fails", async () => {
+    const orderCreator = await newFundedKeypair();
+    const orderDonor = await newFundedKeypair();
+    const partners = [await newFundedKeypair(1), await newFundedKeypair(1)];
+    const cid = await createCampaign(orderCreator, {
+      splits: [
+        { recipient: partners[0].publicKey, bps: 5_000 },
+        { recipient: partners[1].publicKey, bps: 5_000 },
+      ],
+    });
+    await donate(orderDonor, cid, new anchor.BN(4 * LAMPORTS_PER_SOL));
+
+    const before = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    const lamportsBefore = await context.banksClient.getBalance(
+      findCampaignPda(cid)
+    );
+
+    // recipients passed in the wrong order fail the split check mid-payout
+    await expectError(
+      crowdfundingProgram.methods
+        .withdraw(cid, new anchor.BN(2 * LAMPORTS_PER_SOL))
+        .accounts({
+          creator: orderCreator.publicKey,
+          transaction: findWithdrawalPda(
+            orderCreator.publicKey,
+            cid,
+            before.withdrawals.add(new anchor.BN(1))
+          ),
+          programState: programStatePda,
+          beneficiary: before.beneficiary,
+        })
+        .remainingAccounts(
+          [partners[1], partners[0]].map((partner) => ({
+            pubkey: partner.publicKey,
+            isWritable: true,
+            isSigner: false,
+          }))
+        )
+        .signers([orderCreator])
+        .rpc(),
+      "InvalidSplitConfig"
+    );
+
+    const after = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(after.balance.toNumber()).toBe(before.balance.toNumber());
+    expect(after.withdrawals.toNumber()).toBe(before.withdrawals.toNumber());
+    expect(after.withdrawnTotal.toNumber()).toBe(
+      before.withdrawnTotal.toNumber()
+    );
+    expect(await context.banksClient.getBalance(findCampaignPda(cid))).toBe(
+      lamportsBefore
+    );
+  });
+});
+
+describe("Rent Buffer", () => {
+  let bufferCreator: Keypair;
+  let bufferDonor: Keypair;
+  let cid: anchor.BN;
+
+  const setRentBuffer = (updater: Keypair, lamports: number) =>
+    crowdfundingProgram.methods
+      .setRentBuffer(new anchor.BN(lamports))
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  beforeAll(async () => {
+    bufferCreator = await newFundedKeypair();
+    bufferDonor = await newFundedKeypair();
+    cid = await createCampaign(bufferCreator);
+    await donate(bufferDonor, cid, new anchor.BN(5 * LAMPORTS_PER_SOL));
+  });
+
+  it("should only let the platform set the buffer", async () => {
+    await expectError(
+      setRentBuffer(bufferCreator, LAMPORTS_PER_SOL),
+      "Unauthorized"
+    );
+  });
+
+  it("should hold the buffer back from withdrawals", async () => {
+    await setRentBuffer(deployer, LAMPORTS_PER_SOL);
+
+    await expectError(
+      withdraw(bufferCreator, cid, new anchor.BN(5 * LAMPORTS_PER_SOL)),
+      "InsufficientFund"
+    );
+    await withdraw(bufferCreator, cid, new anchor.BN(4 * LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.balance.toNumber()).toBe(LAMPORTS_PER_SOL);
+
+    await setRentBuffer(deployer, 0);
+  });
+});
+
+describe("Creator Campaign Index", () => {
+  it("should list a creator's campaigns on their profile", async () => {
+    const indexCreator = await newFundedKeypair();
+    const cids = [
+      await createCampaign(indexCreator),
+      await createCampaign(indexCreator),
+      await createCampaign(indexCreator),
+    ];
+
+    const profilePda = PublicKey.findProgramAddressSync(
+      [Buffer.from("creator_profile"), indexCreator.publicKey.toBuffer()],
+      crowdfundingAddress
+    )[0];
+    let profile = await crowdfundingProgram.account.creatorProfile.fetch(
+      profilePda
+    );
+    expect(profile.campaignIds.map((id) => id.toNumber())).toEqual(
+      cids.map((cid) => cid.toNumber())
+    );
+
+    // closing a campaign drops it from the index
+    await crowdfundingProgram.methods
+      .deleteCampaign(cids[1])
+      .accounts({
+        creator: indexCreator.publicKey,
+      })
+      .signers([indexCreator])
+      .rpc();
+    await crowdfundingProgram.methods
+      .closeCampaign(cids[1])
+      .accounts({
+        creator: indexCreator.publicKey,
+      })
+      .signers([indexCreator])
+      .rpc();
+
+    profile = await crowdfundingProgram.account.creatorProfile.fetch(
+      profilePda
+    );
+    expect(profile.campaignIds.map((id) => id.toNumber())).toEqual([
+      cids[0].toNumber(),
+      cids[2].toNumber(),
+    ]);
+  });
+});
+
+describe("Finalize Campaign", () => {
+  let sweepCreator: Keypair;
+  let sweepDonor: Keypair;
+  let cranker: Keypair;
+  let cid: anchor.BN;
+  let deadline: number;
+  const raised = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+  const finalize = async () => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    return crowdfundingProgram.methods
+      .finalizeCampaign(cid)
+      .accounts({
+        caller: cranker.publicKey,
+        transaction: findWithdrawalPda(
+          sweepCreator.publicKey,
+          cid,
+          campaign.withdrawals.add(new anchor.BN(1))
+        ),
+        beneficiary: campaign.beneficiary,
+      })
+      .signers([cranker])
+      .rpc();
+  };
+
+  beforeAll(async () => {
+    sweepCreator = await newFundedKeypair();
+    sweepDonor = await newFundedKeypair();
+    cranker = await newFundedKeypair();
+    deadline = (await currentTime()) + 2 * 24 * 60 * 60;
+    cid = await createCampaign(sweepCreator, {
+      goal: raised,
+      deadline: new anchor.BN(deadline),
+    });
+    await crowdfundingProgram.methods
+      .setSweepOnComplete(cid, true)
+      .accounts({
+        creator: sweepCreator.publicKey,
+      })
+      .signers([sweepCreator])
+      .rpc();
+    await donate(sweepDonor, cid, raised);
+  });
+
+  it("should reject finalizing before the campaign completes", async () => {
+    await expectError(finalize(), "CampaignNotFinished");
+  });
+
+  it("should sweep the balance to the beneficiary once completed", async () => {
+    await warpTo(deadline + 1);
+    await crowdfundingProgram.methods
+      .expireCampaign(cid)
+      .accounts({
+        caller: cranker.publicKey,
+      })
+      .signers([cranker])
+      .rpc();
+
+    const state = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const fee = raised
+      .mul(state.platformFeeBps)
+      .addn(9_999)
+      .divn(10_000);
+    const before = await context.banksClient.getBalance(sweepCreator.publicKey);
+
+    await finalize();
+
+    const after = await context.banksClient.getBalance(sweepCreator.publicKey);
+    expect(Number(after - before)).toBe(raised.sub(fee).toNumber());
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.finalized).toBe(true);
+    expect(campaign.balance.toNumber()).toBe(0);
+
+    const record = await crowdfundingProgram.account.transaction.fetch(
+      findWithdrawalPda(sweepCreator.publicKey, cid, new anchor.BN(1))
+    );
+    expect(record.amount.toNumber()).toBe(raised.toNumber());
+  });
+
+  it("should only finalize once", async () => {
+    await expectError(finalize(), "AlreadyFinalized");
+  });
+});
+
+describe("Wrapped SOL Donations", () => {
+  let wsolCreator: Keypair;
+  let wsolDonor: Keypair;
+  let donorWsol: PublicKey;
+  let cid: anchor.BN;
+
+  beforeAll(async () => {
+    wsolCreator = await newFundedKeypair();
+    wsolDonor = await newFundedKeypair();
+    cid = await createCampaign(wsolCreator);
+
+    // wrap 5 SOL into the donor's associated wSOL account
+    donorWsol = getAssociatedTokenAddressSync(NATIVE_MINT, wsolDonor.publicKey);
+    const wrapTransaction = new Transaction().add(
+      createAssociatedTokenAccountInstruction(
+        wsolDonor.publicKey,
+        donorWsol,
+        wsolDonor.publicKey,
+        NATIVE_MINT
+      ),
+      SystemProgram.transfer({
+        fromPubkey: wsolDonor.publicKey,
+        toPubkey: donorWsol,
+        lamports: 5 * LAMPORTS_PER_SOL,
+      }),
+      createSyncNativeInstruction(donorWsol)
+    );
+    await provider.sendAndConfirm!(wrapTransaction, [wsolDonor]);
+  });
+
+  it("should unwrap a wSOL donation into the campaign's native balance", async () => {
+    const amount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+    const campaignPda = findCampaignPda(cid);
+    const lamportsBefore = await context.banksClient.getBalance(campaignPda);
+
+    await crowdfundingProgram.methods
+      .donateWsol(cid, amount)
+      .accounts({
+        donor: wsolDonor.publicKey,
+        transaction: findDonationPda(
+          wsolDonor.publicKey,
+          cid,
+          new anchor.BN(1)
+        ),
+        donorTokenAccount: donorWsol,
+        wsolMint: NATIVE_MINT,
+      })
+      .signers([wsolDonor])
+      .rpc();
+
+    const lamportsAfter = await context.banksClient.getBalance(campaignPda);
+    expect(Number(lamportsAfter - lamportsBefore)).toBe(amount.toNumber());
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      campaignPda
+    );
+    expect(campaign.balance.toNumber()).toBe(amount.toNumber());
+    expect(campaign.amountRaised.toNumber()).toBe(amount.toNumber());
+    expect(campaign.uniqueDonors.toNumber()).toBe(1);
+
+    const tokenAccount = await context.banksClient.getAccount(donorWsol);
+    expect(AccountLayout.decode(tokenAccount!.data).amount).toBe(
+      BigInt(3 * LAMPORTS_PER_SOL)
+    );
+
+    const leaderboard = await crowdfundingProgram.account.leaderboard.fetch(
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("leaderboard"), cid.toArrayLike(Buffer, "le", 8)],
+        crowdfundingAddress
+      )[0]
+    );
+    expect(leaderboard.entries[0].donor.toBase58()).toBe(
+      wsolDonor.publicKey.toBase58()
+    );
+    expect(leaderboard.entries[0].total.toNumber()).toBe(amount.toNumber());
+  });
+});
+
+describe("Exact Goal", () => {
+  it("should complete the campaign on the donation that lands on the goal and reject the next", async () => {
+    const exactCreator = await newFundedKeypair();
+    const exactDonor = await newFundedKeypair();
+    const goal = new anchor.BN(3 * LAMPORTS_PER_SOL);
+    const cid = await createCampaign(exactCreator, { goal });
+
+    await donate(exactDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+    await donate(exactDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(goal.toNumber());
+    expect(campaign.goalReachedAt.toNumber()).toBeGreaterThan(0);
+    expect(campaign.status).toEqual({ completed: {} });
+
+    await expectError(
+      donate(exactDonor, cid, new anchor.BN(LAMPORTS_PER_SOL)),
+      "CampaignGoalActualized"
+    );
+  });
+});
+
+describe("Amount Floors", () => {
+  const setAmountFloors = (
+    updater: Keypair,
+    donationFloor: number,
+    withdrawalFloor: number
+  ) =>
+    crowdfundingProgram.methods
+      .setAmountFloors(
+        new anchor.BN(donationFloor),
+        new anchor.BN(withdrawalFloor)
+      )
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  it("should start both floors at 1 SOL", async () => {
+    const state = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    expect(state.minDonationFloor.toNumber()).toBe(LAMPORTS_PER_SOL);
+    expect(state.minWithdrawalFloor.toNumber()).toBe(LAMPORTS_PER_SOL);
+  });
+
+  it("should only let the platform change the floors", async () => {
+    const stranger = await newFundedKeypair();
+    await expectError(
+      setAmountFloors(stranger, LAMPORTS_PER_SOL / 2, LAMPORTS_PER_SOL / 2),
+      "Unauthorized"
+    );
+    await expectError(
+      setAmountFloors(deployer, 0, LAMPORTS_PER_SOL),
+      "InvalidAmountFloor"
+    );
+  });
+
+  it("should accept a small donation once the floor is lowered", async () => {
+    const floorCreator = await newFundedKeypair();
+    const floorDonor = await newFundedKeypair();
+    const cid = await createCampaign(floorCreator);
+    const small = new anchor.BN(LAMPORTS_PER_SOL / 2);
+
+    await expectError(donate(floorDonor, cid, small), "InvalidDonationAmount");
+
+    await setAmountFloors(deployer, LAMPORTS_PER_SOL / 2, LAMPORTS_PER_SOL / 2);
+    await donate(floorDonor, cid, small);
+    await withdraw(floorCreator, cid, small);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(small.toNumber());
+    expect(campaign.withdrawnTotal.toNumber()).toBe(small.toNumber());
+
+    await setAmountFloors(deployer, LAMPORTS_PER_SOL, LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Freeze On Goal", () => {
+  const goal = new anchor.BN(2 * LAMPORTS_PER_SOL);
+  let freezeCreator: Keypair;
+  let freezeDonor: Keypair;
+  let cid: anchor.BN;
+
+  const unfreeze = (updater: Keypair) =>
+    crowdfundingProgram.methods
+      .unfreezeCampaign(cid)
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  beforeAll(async () => {
+    freezeCreator = await newFundedKeypair();
+    freezeDonor = await newFundedKeypair();
+    cid = await createCampaign(freezeCreator, { goal });
+    await crowdfundingProgram.methods
+      .setFreezeOnGoal(cid, true)
+      .accounts({
+        creator: freezeCreator.publicKey,
+      })
+      .signers([freezeCreator])
+      .rpc();
+  });
+
+  it("should stay open until the goal is reached", async () => {
+    await expectError(unfreeze(deployer), "CampaignNotFrozen");
+  });
+
+  it("should freeze donations and withdrawals on reaching the goal", async () => {
+    await donate(freezeDonor, cid, goal);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.frozen).toBe(true);
+
+    const oneSol = new anchor.BN(LAMPORTS_PER_SOL);
+    await expectError(donate(freezeDonor, cid, oneSol), "CampaignFrozen");
+    await expectError(withdraw(freezeCreator, cid, oneSol), "CampaignFrozen");
+  });
+
+  it("should only let the platform unfreeze", async () => {
+    const stranger = await newFundedKeypair();
+    await expectError(unfreeze(stranger), "Unauthorized");
+    await expectError(unfreeze(freezeCreator), "Unauthorized");
+  });
+
+  it("should allow withdrawals once the platform unfreezes", async () => {
+    await unfreeze(deployer);
+
+    let campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.frozen).toBe(false);
+
+    await withdraw(freezeCreator, cid, goal);
+
+    campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.withdrawnTotal.toNumber()).toBe(goal.toNumber());
+    expect(campaign.frozen).toBe(false);
+  });
+});
+
+describe("Reconcile Donors", () => {
+  const reconcile = (updater: Keypair, cid: anchor.BN, count: number) =>
+    crowdfundingProgram.methods
+      .reconcileDonors(cid, new anchor.BN(count))
+      .accounts({
+        updater: updater.publicKey,
+      })
+      .signers([updater])
+      .rpc();
+
+  it("should reset a drifted donor count to the verified value", async () => {
+    const reconcileCreator = await newFundedKeypair();
+    const reconcileDonor = await newFundedKeypair();
+    const cid = await createCampaign(reconcileCreator);
+    await donate(reconcileDonor, cid, new anchor.BN(LAMPORTS_PER_SOL));
+
+    // simulate drift by pushing the counter past the real transaction PDAs
+    await reconcile(deployer, cid, 7);
+    let campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.donors.toNumber()).toBe(7);
+
+    await expectError(reconcile(reconcileCreator, cid, 1), "Unauthorized");
+
+    const { events } = await crowdfundingProgram.methods
+      .reconcileDonors(cid, new anchor.BN(1))
+      .accounts({
+        updater: deployer.publicKey,
+      })
+      .signers([deployer])
+      .simulate();
+    const event = events.find((e) => e.name === "donorsReconciled")!.data;
+    expect(event.previousCount.toNumber()).toBe(7);
+    expect(event.actualCount.toNumber()).toBe(1);
+
+    await reconcile(deployer, cid, 1);
+    campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.donors.toNumber()).toBe(1);
+
+    // the next donation lands on the PDA right after the verified count
+    await donate(reconcileDonor, cid, new anchor.BN(2 * LAMPORTS_PER_SOL));
+    const transaction = await crowdfundingProgram.account.transaction.fetch(
+      findDonationPda(reconcileDonor.publicKey, cid, new anchor.BN(2))
+    );
+    expect(transaction.amount.toNumber()).toBe(2 * LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Recurring Pledges", () => {
+  const amountPerPeriod = new anchor.BN(LAMPORTS_PER_SOL);
+  const periodSeconds = 30 * 24 * 60 * 60;
+  let pledgeCreator: Keypair;
+  let pledgeDonor: Keypair;
+  let cranker: Keypair;
+  let donorWsol: PublicKey;
+  let pledgePda: PublicKey;
+  let cid: anchor.BN;
+
+  const executePledge = async (pledgeCid: anchor.BN = cid) => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(pledgeCid)
+    );
+    await crowdfundingProgram.methods
+      .executePledge(pledgeCid)
+      .accounts({
+        caller: cranker.publicKey,
+        donor: pledgeDonor.publicKey,
+        transaction: findDonationPda(
+          pledgeDonor.publicKey,
+          pledgeCid,
+          campaign.donors.add(new anchor.BN(1))
+        ),
+        donorTokenAccount: donorWsol,
+        wsolMint: NATIVE_MINT,
+      })
+      .signers([cranker])
+      .rpc();
+  };
+
+  beforeAll(async () => {
+    pledgeCreator = await newFundedKeypair();
+    pledgeDonor = await newFundedKeypair();
+    cranker = await newFundedKeypair();
+    cid = await createCampaign(pledgeCreator);
+    [pledgePda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("pledge"),
+        cid.toArrayLike(Buffer, "le", 8),
+        pledgeDonor.publicKey.toBuffer(),
+      ],
+      crowdfundingAddress
+    );
+
+    await crowdfundingProgram.methods
+      .createPledge(cid, amountPerPeriod, new anchor.BN(periodSeconds))
+      .accounts({
+        donor: pledgeDonor.publicKey,
+      })
+      .signers([pledgeDonor])
+      .rpc();
+
+    // wrap 5 SOL and let the pledge PDA pull up to 3 periods of it
+    donorWsol = getAssociatedTokenAddressSync(
+      NATIVE_MINT,
+      pledgeDonor.publicKey
+    );
+    const approveTransaction = new Transaction().add(
+      createAssociatedTokenAccountInstruction(
+        pledgeDonor.publicKey,
+        donorWsol,
+        pledgeDonor.publicKey,
+        NATIVE_MINT
+      ),
+      SystemProgram.transfer({
+        fromPubkey: pledgeDonor.publicKey,
+        toPubkey: donorWsol,
+        lamports: 5 * LAMPORTS_PER_SOL,
+      }),
+      createSyncNativeInstruction(donorWsol),
+      createApproveInstruction(
+        donorWsol,
+        pledgePda,
+        pledgeDonor.publicKey,
+        BigInt(3 * LAMPORTS_PER_SOL)
+      )
+    );
+    await provider.sendAndConfirm!(approveTransaction, [pledgeDonor]);
+  });
+
+  it("should reject a zero period", async () => {
+    const other = await newFundedKeypair();
+    await expectError(
+      crowdfundingProgram.methods
+        .createPledge(cid, amountPerPeriod, new anchor.BN(0))
+        .accounts({
+          donor: other.publicKey,
+        })
+        .signers([other])
+        .rpc(),
+      "InvalidPledge"
+    );
+  });
+
+  it("should execute the first period as soon as it is created", async () => {
+    const before = await crowdfundingProgram.account.pledge.fetch(pledgePda);
+
+    await executePledge();
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(amountPerPeriod.toNumber());
+
+    const pledge = await crowdfundingProgram.account.pledge.fetch(pledgePda);
+    expect(pledge.executedCount.toNumber()).toBe(1);
+    expect(pledge.nextDueTs.toNumber()).toBe(
+      before.nextDueTs.toNumber() + periodSeconds
+    );
+  });
+
+  it("should reject executing before the next period is due", async () => {
+    await expectError(executePledge(), "PledgeNotDue");
+  });
+
+  it("should execute again once the period has passed", async () => {
+    const pledgeBefore = await crowdfundingProgram.account.pledge.fetch(
+      pledgePda
+    );
+    await warpTo(pledgeBefore.nextDueTs.toNumber());
+
+    await executePledge();
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(
+      2 * amountPerPeriod.toNumber()
+    );
+
+    const tokenAccount = await context.banksClient.getAccount(donorWsol);
+    expect(AccountLayout.decode(tokenAccount!.data).amount).toBe(
+      BigInt(3 * LAMPORTS_PER_SOL)
+    );
+  });
+
+  it("should record executions like any other donation", async () => {
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.uniqueDonors.toNumber()).toBe(1);
+    expect(campaign.topDonor.toBase58()).toBe(
+      pledgeDonor.publicKey.toBase58()
+    );
+
+    const record = await crowdfundingProgram.account.donorRecord.fetch(
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("donor_record"),
+          cid.toArrayLike(Buffer, "le", 8),
+          pledgeDonor.publicKey.toBuffer(),
+        ],
+        crowdfundingAddress
+      )[0]
+    );
+    expect(record.donationCount.toNumber()).toBe(2);
+    expect(record.totalContributed.toNumber()).toBe(
+      2 * amountPerPeriod.toNumber()
+    );
+  });
+
+  it("should stop executing once cancelled", async () => {
+    await crowdfundingProgram.methods
+      .cancelPledge(cid)
+      .accounts({
+        donor: pledgeDonor.publicKey,
+      })
+      .signers([pledgeDonor])
+      .rpc();
+
+    const pledge = await crowdfundingProgram.account.pledge.fetch(pledgePda);
+    expect(pledge.active).toBe(false);
+
+    await warpTo(pledge.nextDueTs.toNumber());
+    await expectError(executePledge(), "PledgeInactive");
+  });
+
+  it("should hold a pledge back until the campaign cooldown passes", async () => {
+    const cooldownCid = await createCampaign(pledgeCreator, {
+      donationCooldown: new anchor.BN(2 * periodSeconds),
+    });
+    const [cooldownPledge] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("pledge"),
+        cooldownCid.toArrayLike(Buffer, "le", 8),
+        pledgeDonor.publicKey.toBuffer(),
+      ],
+      crowdfundingAddress
+    );
+    await crowdfundingProgram.methods
+      .createPledge(cooldownCid, amountPerPeriod, new anchor.BN(periodSeconds))
+      .accounts({
+        donor: pledgeDonor.publicKey,
+      })
+      .signers([pledgeDonor])
+      .rpc();
+    await provider.sendAndConfirm!(
+      new Transaction().add(
+        createApproveInstruction(
+          donorWsol,
+          cooldownPledge,
+          pledgeDonor.publicKey,
+          BigInt(2 * LAMPORTS_PER_SOL)
+        )
+      ),
+      [pledgeDonor]
+    );
+
+    await executePledge(cooldownCid);
+
+    const pledge = await crowdfundingProgram.account.pledge.fetch(
+      cooldownPledge
+    );
+    await warpTo(pledge.nextDueTs.toNumber());
+    await expectError(executePledge(cooldownCid), "PledgeNotDue");
+  });
+});
+
+describe("Campaign Flags", () => {
+  let flagCreator: Keypair;
+  let reporterA: Keypair;
+  let reporterB: Keypair;
+  let cid: anchor.BN;
+
+  const flagCampaign = (reporter: Keypair) =>
+    crowdfundingProgram.methods
+      .flagCampaign(cid)
+      .accounts({
+        reporter: reporter.publicKey,
+      })
+      .signers([reporter]);
+
+  const setFlagThreshold = (threshold: number) =>
+    crowdfundingProgram.methods
+      .setFlagThreshold(new anchor.BN(threshold))
+      .accounts({
+        updater: deployer.publicKey,
+      })
+      .signers([deployer])
+      .rpc();
+
+  beforeAll(async () => {
+    flagCreator = await newFundedKeypair();
+    reporterA = await newFundedKeypair();
+    reporterB = await newFundedKeypair();
+    cid = await createCampaign(flagCreator);
+    await donate(reporterA, cid, new anchor.BN(LAMPORTS_PER_SOL));
+    await donate(reporterB, cid, new anchor.BN(LAMPORTS_PER_SOL));
+    await setFlagThreshold(1);
+  });
+
+  it("should only accept flags from donors", async () => {
+    const stranger = await newFundedKeypair();
+    await expectError(flagCampaign(stranger).rpc(), "AccountNotInitialized");
+  });
+
+  it("should count one flag per wallet", async () => {
+    await flagCampaign(reporterA).rpc();
+    let campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.flags.toNumber()).toBe(1);
+
+    await expect(flagCampaign(reporterA).rpc()).rejects.toThrow();
+
+    const { events } = await flagCampaign(reporterB).simulate();
+    const event = events.find((e) => e.name === "campaignFlagged")!.data;
+    expect(event.flags.toNumber()).toBe(2);
+    expect(event.threshold.toNumber()).toBe(1);
+
+    await flagCampaign(reporterB).rpc();
+    campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.flags.toNumber()).toBe(2);
+  });
+
+  it("should let only the platform clear flags", async () => {
+    const clearFlags = (updater: Keypair) =>
+      crowdfundingProgram.methods
+        .clearFlags(cid)
+        .accounts({
+          updater: updater.publicKey,
+        })
+        .signers([updater])
+        .rpc();
+
+    await expectError(clearFlags(flagCreator), "Unauthorized");
+    await clearFlags(deployer);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.flags.toNumber()).toBe(0);
+
+    await setFlagThreshold(3);
+  });
+});
+
+describe("Donation Increment", () => {
+  it("should only accept multiples of the campaign increment", async () => {
+    const incrementCreator = await newFundedKeypair();
+    const incrementDonor = await newFundedKeypair();
+    const cid = await createCampaign(incrementCreator);
+    const increment = new anchor.BN(LAMPORTS_PER_SOL / 10);
+
+    await crowdfundingProgram.methods
+      .setDonationIncrement(cid, increment)
+      .accounts({
+        creator: incrementCreator.publicKey,
+      })
+      .signers([incrementCreator])
+      .rpc();
+
+    await expectError(
+      donate(incrementDonor, cid, new anchor.BN(LAMPORTS_PER_SOL + 12_345)),
+      "InvalidDonationIncrement"
+    );
+
+    const valid = new anchor.BN(1.5 * LAMPORTS_PER_SOL);
+    await donate(incrementDonor, cid, valid);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.donationIncrement.toNumber()).toBe(increment.toNumber());
+    expect(campaign.amountRaised.toNumber()).toBe(valid.toNumber());
+  });
+});
+
+describe("Campaign Snapshot", () => {
+  let snapshotCreator: Keypair;
+
+  beforeAll(async () => {
+    snapshotCreator = await newFundedKeypair();
+  });
+
+  it("should emit a snapshot when a campaign is created", async () => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const nextCid = programState.campaignCount.add(new anchor.BN(1));
+    const deadline = new anchor.BN((await currentTime()) + 7 * 24 * 60 * 60);
+
+    const { events } = await crowdfundingProgram.methods
+      .createCampaign(
+        campaignTitle,
+        campaignDescription,
+        campaignImageUrl,
+        campaignGoal,
+        new anchor.BN(0),
+        deadline,
+        null,
+        [],
+        new anchor.BN(0),
+        [],
+        new anchor.BN(0),
+        false,
+        new anchor.BN(0),
+        new anchor.BN(0),
+        false,
+        null,
+        null,
+        false,
+        [],
+        new anchor.BN(0)
+      )
+      .accounts({
+        creator: snapshotCreator.publicKey,
+        campaign: findCampaignPda(nextCid),
+        programState: programStatePda,
+        platformAddress: deployer.publicKey,
+        category: null,
+        seedTransaction: null,
+        seedDonorRecord: null,
+        seedLeaderboard: null,
+      })
+      .signers([snapshotCreator])
+      .simulate();
+    const event = events.find((e) => e.name === "campaignSnapshot")!.data;
+
+    expect(event.cid.toNumber()).toBe(nextCid.toNumber());
+    expect(event.creator.toBase58()).toBe(snapshotCreator.publicKey.toBase58());
+    expect(event.title).toBe(campaignTitle);
+    expect(event.goal.toNumber()).toBe(campaignGoal.toNumber());
+    expect(event.deadline.toNumber()).toBe(deadline.toNumber());
+    expect(event.status).toEqual({ active: {} });
+  });
+
+  it("should emit a snapshot when a campaign is updated", async () => {
+    const cid = await createCampaign(snapshotCreator);
+    const newTitle = "Snapshot Title";
+    const newGoal = new anchor.BN(20 * LAMPORTS_PER_SOL);
+
+    const { events } = await crowdfundingProgram.methods
+      .updateCampaign(
+        cid,
+        newTitle,
+        campaignDescription,
+        campaignImageUrl,
+        newGoal,
+        new anchor.BN(0),
+        []
+      )
+      .accounts({
+        creator: snapshotCreator.publicKey,
+      })
+      .signers([snapshotCreator])
+      .simulate();
+    const event = events.find((e) => e.name === "campaignSnapshot")!.data;
+
+    expect(event.cid.toNumber()).toBe(cid.toNumber());
+    expect(event.creator.toBase58()).toBe(snapshotCreator.publicKey.toBase58());
+    expect(event.title).toBe(newTitle);
+    expect(event.goal.toNumber()).toBe(newGoal.toNumber());
+    expect(event.deadline.toNumber()).toBe(0);
+    expect(event.status).toEqual({ active: {} });
+  });
+});
+
+describe("Creator Seed", () => {
+  it("should record the creator's opening donation as a labelled seed", async () => {
+    const seedCreator = await newFundedKeypair();
+    const seedDonor = await newFundedKeypair();
+    const seed = new anchor.BN(2 * LAMPORTS_PER_SOL);
+
+    const cid = await createCampaign(seedCreator, { creatorSeedAmount: seed });
+
+    let campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(seed.toNumber());
+    expect(campaign.balance.toNumber()).toBe(seed.toNumber());
+    expect(campaign.creatorSeedAmount.toNumber()).toBe(seed.toNumber());
+    expect(campaign.donors.toNumber()).toBe(1);
+    expect(campaign.uniqueDonors.toNumber()).toBe(1);
+
+    const seedDonorRecord = await crowdfundingProgram.account.donorRecord.fetch(
+      findSeedDonorRecordPda(cid, seedCreator.publicKey)
+    );
+    expect(seedDonorRecord.donorRank.toNumber()).toBe(1);
+    expect(seedDonorRecord.totalContributed.toNumber()).toBe(seed.toNumber());
+
+    const seedTransaction = await crowdfundingProgram.account.transaction.fetch(
+      findDonationPda(seedCreator.publicKey, cid, new anchor.BN(1))
+    );
+    expect(seedTransaction.creatorSeed).toBe(true);
+    expect(seedTransaction.owner.toBase58()).toBe(
+      seedCreator.publicKey.toBase58()
+    );
+    expect(seedTransaction.amount.toNumber()).toBe(seed.toNumber());
+
+    // regular donations carry on after the seed and are not labelled
+    const amount = new anchor.BN(LAMPORTS_PER_SOL);
+    await donate(seedDonor, cid, amount);
+    const donation = await crowdfundingProgram.account.transaction.fetch(
+      findDonationPda(seedDonor.publicKey, cid, new anchor.BN(2))
+    );
+    expect(donation.creatorSeed).toBe(false);
+
+    campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(seed.add(amount).toNumber());
+    expect(campaign.creatorSeedAmount.toNumber()).toBe(seed.toNumber());
+  });
+
+  it("should reject seeding an auto-forward campaign", async () => {
+    const seedCreator = await newFundedKeypair();
+
+    await expectError(
+      createCampaign(seedCreator, {
+        autoForward: true,
+        creatorSeedAmount: new anchor.BN(LAMPORTS_PER_SOL),
+      }),
+      "AutoForwardEnabled"
+    );
+  });
+});
+
+describe("Title And Description Policy", () => {
+  let policyCreator: Keypair;
+
+  const createWithText = async (title: string, description: string) => {
+    const programState = await crowdfundingProgram.account.programState.fetch(
+      programStatePda
+    );
+    const cid = programState.campaignCount.add(new anchor.BN(1));
+
+    await crowdfundingProgram.methods
+      .createCampaign(
+        title,
+        description,
+        campaignImageUrl,
+        campaignGoal,
+        new anchor.BN(0),
+        new anchor.BN(0),
+        null,
+        [],
+        new anchor.BN(0),
+        [],
+        new anchor.BN(0),
+        false,
+        new anchor.BN(0),
+        new anchor.BN(0),
+        false,
+        null,
+        null,
+        false,
+        [],
+        new anchor.BN(0)
+      )
+      .accounts({
+        creator: policyCreator.publicKey,
+        campaign: findCampaignPda(cid),
+        programState: programStatePda,
+        platformAddress: deployer.publicKey,
+        category: null,
+        seedTransaction: null,
+        seedDonorRecord: null,
+        seedLeaderboard: null,
+      })
+      .signers([policyCreator])
+      .rpc();
+
+    return cid;
+  };
+
+  const updateText = (cid: anchor.BN, title: string, description: string) =>
+    crowdfundingProgram.methods
+      .updateCampaign(
+        cid,
+        title,
+        description,
+        campaignImageUrl,
+        campaignGoal,
+        new anchor.BN(0),
+        []
+      )
+      .accounts({
+        creator: policyCreator.publicKey,
+      })
+      .signers([policyCreator])
+      .rpc();
+
+  beforeAll(async () => {
+    policyCreator = await newFundedKeypair();
+  });
+
+  it("should reject a whitespace-only title", async () => {
+    await expectError(
+      createWithText("   \t ", campaignDescription),
+      "InvalidTitle"
+    );
+  });
+
+  it("should reject a title containing a newline", async () => {
+    await expectError(
+      createWithText("Save the\nWhales", campaignDescription),
+      "InvalidTitle"
+    );
+  });
+
+  it("should reject a blank or control-character description", async () => {
+    await expectError(
+      createWithText(campaignTitle, "  "),
+      "InvalidDescription"
+    );
+    await expectError(
+      createWithText(campaignTitle, "Bell \u0007 character"),
+      "InvalidDescription"
+    );
+  });
+
+  it("should accept a normal title and a multi-line description", async () => {
+    const cid = await createWithText(
+      "Clean Water Wells",
+      "Phase one: drilling.\nPhase two: pumps."
+    );
+
+    await expectError(
+      updateText(cid, "Clean\rWater", campaignDescription),
+      "InvalidTitle"
+    );
+    await updateText(cid, "Clean Water Wells 2", campaignDescription);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.title).toBe("Clean Water Wells 2");
+  });
+});
+
+describe("Withdrawal Preview", () => {
+  it("should preview exactly what the real withdrawal pays out", async () => {
+    const previewCreator = await newFundedKeypair();
+    const previewDonor = await newFundedKeypair();
+    const payee = Keypair.generate();
+    const cid = await createCampaign(previewCreator, {
+      beneficiary: payee.publicKey,
+    });
+    await donate(previewDonor, cid, new anchor.BN(5 * LAMPORTS_PER_SOL));
+
+    const amount = new anchor.BN(2 * LAMPORTS_PER_SOL);
+    const preview = async () => {
+      const { events } = await crowdfundingProgram.methods
+        .previewWithdrawal(cid, amount)
+        .accounts({
+          creator: previewCreator.publicKey,
+          beneficiary: payee.publicKey,
+        })
+        .signers([previewCreator])
+        .simulate();
+      return events.find((e) => e.name === "withdrawalPreview")!.data;
+    };
+
+    const before = await preview();
+    expect(before.requested.toNumber()).toBe(amount.toNumber());
+    expect(before.platformFee.add(before.creatorAmount).toNumber()).toBe(
+      amount.toNumber()
+    );
+
+    // the preview leaves the campaign untouched
+    let campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.withdrawals.toNumber()).toBe(0);
+    expect(campaign.balance.toNumber()).toBe(5 * LAMPORTS_PER_SOL);
+
+    const treasuryBefore = await context.banksClient.getBalance(treasuryPda);
+    await withdraw(previewCreator, cid, amount);
+    const treasuryAfter = await context.banksClient.getBalance(treasuryPda);
+    const payeeBalance = await context.banksClient.getBalance(payee.publicKey);
+
+    expect(Number(payeeBalance)).toBe(before.creatorAmount.toNumber());
+    expect(Number(treasuryAfter - treasuryBefore)).toBe(
+      before.platformFee.toNumber()
+    );
+
+    const after = await preview();
+    expect(after.usableBalance.toNumber()).toBe(
+      before.usableBalance.sub(amount).toNumber()
+    );
+
+    campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.withdrawals.toNumber()).toBe(1);
+  });
+
+  it("should run the same checks as withdraw", async () => {
+    const previewCreator = await newFundedKeypair();
+    const cid = await createCampaign(previewCreator);
+
+    await expectError(
+      crowdfundingProgram.methods
+        .previewWithdrawal(cid, new anchor.BN(LAMPORTS_PER_SOL))
+        .accounts({
+          creator: previewCreator.publicKey,
+          beneficiary: previewCreator.publicKey,
+        })
+        .signers([previewCreator])
+        .rpc(),
+      "WithdrawalExceedsBalance"
+    );
+  });
+});
+
+describe("Tiered Match Ratios", () => {
+  let tierCreator: Keypair;
+  let tierSponsor: Keypair;
+  let tierDonor: Keypair;
+  let matchPool: PublicKey;
+  let cid: anchor.BN;
+
+  const setMatchTiers = (
+    tiers: { belowProgressBps: anchor.BN; ratioBps: anchor.BN }[]
+  ) =>
+    crowdfundingProgram.methods
+      .setMatchTiers(cid, tiers)
+      .accounts({
+        sponsor: tierSponsor.publicKey,
+      })
+      .signers([tierSponsor])
+      .rpc();
+
+  const matchedFor = async (amount: number) => {
+    const donation = await donate(
+      tierDonor,
+      cid,
+      new anchor.BN(amount * LAMPORTS_PER_SOL),
+      { matchPool }
+    );
+    const transaction = await crowdfundingProgram.account.transaction.fetch(
+      donation
+    );
+    return transaction.matchedAmount.toNumber();
+  };
+
+  beforeAll(async () => {
+    tierCreator = await newFundedKeypair();
+    tierSponsor = await newFundedKeypair();
+    tierDonor = await newFundedKeypair();
+    cid = await createCampaign(tierCreator);
+    matchPool = PublicKey.findProgramAddressSync(
+      [Buffer.from("match"), cid.toArrayLike(Buffer, "le", 8)],
+      crowdfundingAddress
+    )[0];
+
+    await crowdfundingProgram.methods
+      .fundMatchPool(
+        cid,
+        new anchor.BN(10 * LAMPORTS_PER_SOL),
+        new anchor.BN(10_000)
+      )
+      .accounts({
+        sponsor: tierSponsor.publicKey,
+      })
+      .signers([tierSponsor])
+      .rpc();
+  });
+
+  it("should reject tiers that do not ascend", async () => {
+    await expectError(
+      setMatchTiers([
+        { belowProgressBps: new anchor.BN(5_000), ratioBps: new anchor.BN(0) },
+        { belowProgressBps: new anchor.BN(2_500), ratioBps: new anchor.BN(0) },
+      ]),
+      "InvalidMatchTiers"
+    );
+  });
+
+  it("should step the match ratio down as the campaign fills", async () => {
+    // 2:1 below 25% of the 10 SOL goal, 1:1 below 50%, nothing after
+    await setMatchTiers([
+      {
+        belowProgressBps: new anchor.BN(2_500),
+        ratioBps: new anchor.BN(20_000),
+      },
+      {
+        belowProgressBps: new anchor.BN(5_000),
+        ratioBps: new anchor.BN(10_000),
+      },
+    ]);
+
+    // 0% funded: 1 SOL is matched 2:1, leaving the campaign at 30%
+    expect(await matchedFor(1)).toBe(2 * LAMPORTS_PER_SOL);
+    // 30% funded: 1 SOL is matched 1:1, leaving the campaign exactly at 50%
+    expect(await matchedFor(1)).toBe(LAMPORTS_PER_SOL);
+    // a bound is exclusive, so sitting on 50% falls past the last tier
+    expect(await matchedFor(1)).toBe(0);
+
+    const campaign = await crowdfundingProgram.account.campaign.fetch(
+      findCampaignPda(cid)
+    );
+    expect(campaign.amountRaised.toNumber()).toBe(6 * LAMPORTS_PER_SOL);
+  });
+});
+
+describe("Campaign Resize", () => {
+  it("should grow a campaign to the current layout for its creator", async () => {
+    const resizeCreator = await newFundedKeypair();
+    const stranger = await newFundedKeypair();
+    const cid = await createCampaign(resizeCreator);
+    const address = findCampaignPda(cid);
+    const account = (await context.banksClient.getAccount(address))!;
+    const campaign = await crowdfundingProgram.account.campaign.fetch(address);
+
+    // an older layout is the same data minus the last appended u64 field
+    const encoded = await crowdfundingProgram.coder.accounts.encode(
+      "Campaign",
+      { ...campaign, version: campaign.version - 1 }
+    );
+    context.setAccount(address, {
+      ...account,
+      data: encoded.subarray(0, encoded.length - 8),
+    });
+
+    const setDonationIncrement = (increment: number) =>
+      crowdfundingProgram.methods
+        .setDonationIncrement(cid, new anchor.BN(increment))
+        .accounts({
+          creator: resizeCreator.publicKey,
+        })
+        .signers([resizeCreator])
+        .rpc();
+    const resizeCampaign = (creator: Keypair) =>
+      crowdfundingProgram.methods
+        .resizeCampaign(cid)
+        .accounts({
+          creator: creator.publicKey,
+        })
+        .signers([creator])
+        .rpc();
+
+    await expectError(
+      setDonationIncrement(LAMPORTS_PER_SOL),
+      "AccountDidNotDeserialize"
+    );
+    await expectError(resizeCampaign(stranger), "Unauthorized");
+
+    await resizeCampaign(resizeCreator);
+
+    const resized = (await context.banksClient.getAccount(address))!;
+    expect(resized.data.length).toBe(account.data.length);
+
+    const grown = await crowdfundingProgram.account.campaign.fetch(address);
+    expect(grown.title).toBe(campaign.title);
+    expect(grown.goal.toNumber()).toBe(campaign.goal.toNumber());
+    expect(grown.creator.toBase58()).toBe(resizeCreator.publicKey.toBase58());
+    expect(grown.matchedTotal.toNumber()).toBe(0);
+    expect(grown.version).toBe(campaign.version);
+
+    // instructions that need the full layout work again
+    await setDonationIncrement(LAMPORTS_PER_SOL / 10);
+    const updated = await crowdfundingProgram.account.campaign.fetch(address);
+    expect(updated.donationIncrement.toNumber()).toBe(LAMPORTS_PER_SOL / 10);
+  });
+});
+
+describe("Donatable Status", () => {
+  // mirrors DonatableReason in the program
+  const reasons = {
+    donatable: 0,
+    programPaused: 1,
+    expired: 5,
+    goalReached: 6,
+  };
+  let statusCreator: Keypair;
+  let statusDonor: Keypair;
+
+  const donatableStatus = async (cid: anchor.BN) => {
+    const { events } = await crowdfundingProgram.methods
+      .donatableStatus(cid)
+      .simulate();
+    return events.find((e) => e.name === "donatableStatus")!.data;
+  };
+
+  beforeAll(async () => {
+    statusCreator = await newFundedKeypair();
+    statusDonor = await newFundedKeypair();
+  });
+
+  it("should report an open campaign as donatable", async () => {
+    const cid = await createCampaign(statusCreator);
+    const status = await donatableStatus(cid);
+    expect(status.donatable).toBe(true);
+    expect(status.reason).toBe(reasons.donatable);
+  });
+
+  it("should report a campaign that reached its goal", async () => {
+    const goal = new anchor.BN(2 * LAMPORTS_PER_SOL);
+    const cid = await createCampaign(statusCreator, { goal });
+    await donate(statusDonor, cid, goal);
+
+    const status = await donatableStatus(cid);
+    expect(status.donatable).toBe(false);
+    expect(status.reason).toBe(reasons.goalReached);
+  });
+
+  it("should report a paused program", async () => {
+    const cid = await createCampaign(statusCreator);
+    const setProgramPaused = (paused: boolean) =>
+      crowdfundingProgram.methods
+        .setProgramPaused(paused)
+        .accounts({
+          updater: deployer.publicKey,
+        })
+        .signers([deployer])
+        .rpc();
+
+    await setProgramPaused(true);
+    const status = await donatableStatus(cid);
+    await setProgramPaused(false);
+
+    expect(status.donatable).toBe(false);
+    expect(status.reason).toBe(reasons.programPaused);
+  });
+
+  it("should report an expired campaign", async () => {
+    const deadline = (await currentTime()) + 2 * 24 * 60 * 60;
+    const cid = await createCampaign(statusCreator, {
+      deadline: new anchor.BN(deadline),
+    });
+    await warpTo(deadline + 1);
+
+    const status = await donatableStatus(cid);
+    expect(status.donatable).toBe(false);
+    expect(status.reason).toBe(reasons.expired);
+  });
+});
 });